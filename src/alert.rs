@@ -0,0 +1,12 @@
+/// Short beep to get the table's attention, e.g. when a day timer runs out
+pub(crate) fn play_alert() {
+    let Ok(context) = web_sys::AudioContext::new() else {
+        return;
+    };
+    let Ok(oscillator) = context.create_oscillator() else {
+        return;
+    };
+    let _ = oscillator.connect_with_audio_node(&context.destination());
+    let _ = oscillator.start();
+    let _ = oscillator.stop_with_when(context.current_time() + 0.5);
+}
@@ -0,0 +1,298 @@
+//! Small server players connect to with their phones over the local network. The storyteller's
+//! app posts the public state of the game and private messages for individual players, and each
+//! player's page polls for them. Only uses std so it runs without an internet connection
+//!
+//! Usage: companion [port]. It prints a secret the storyteller enters in their app, and only
+//! requests carrying it can change what players see. Each player opens the link the app gives
+//! them, which carries their own token, so nobody can read anyone else's messages
+#![allow(clippy::needless_return)]
+
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, RandomState},
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::SystemTime,
+};
+
+const DEFAULT_PORT: u16 = 3030;
+const PLAYER_PAGE: &str = include_str!("companion.html");
+
+#[derive(Default)]
+struct Companion {
+    /// Needed to post anything, so only the storyteller's app can
+    secret: String,
+    /// Latest public state as sent by the storyteller's app, passed along as is
+    public_state: String,
+    /// Private messages for each player's token, oldest first
+    messages: HashMap<String, Vec<String>>,
+}
+
+impl Companion {
+    fn new(secret: String) -> Self {
+        Self {
+            secret,
+            ..Default::default()
+        }
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    body: String,
+}
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn ok(content_type: &'static str, body: String) -> Self {
+        Self {
+            status: "200 OK",
+            content_type,
+            body,
+        }
+    }
+
+    fn empty(status: &'static str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain",
+            body: String::new(),
+        }
+    }
+}
+
+fn main() {
+    let port = std::env::args()
+        .nth(1)
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(DEFAULT_PORT);
+    let listener = match TcpListener::bind(("0.0.0.0", port)) {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("Could not listen on port {}: {}", port, err);
+            return;
+        }
+    };
+    let secret = random_token();
+    println!("Companion server listening on port {}", port);
+    println!("Storyteller secret: {}", secret);
+
+    let companion = Arc::new(Mutex::new(Companion::new(secret)));
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let companion = companion.clone();
+        thread::spawn(move || {
+            if let Err(err) = handle_connection(stream, &companion) {
+                eprintln!("Connection error: {}", err);
+            }
+        });
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, companion: &Mutex<Companion>) -> std::io::Result<()> {
+    let request = match read_request(&mut stream)? {
+        Some(request) => request,
+        None => return write_response(&mut stream, Response::empty("400 Bad Request"), false),
+    };
+    // The storyteller's app is served from somewhere else, so it needs to be let in to post.
+    // Nothing else gets to read from another site
+    let allow_origin = request.method != "GET";
+    let response = route(request, &mut companion.lock().unwrap());
+    return write_response(&mut stream, response, allow_origin);
+}
+
+fn route(request: Request, companion: &mut Companion) -> Response {
+    if request.method == "POST" && request.query.get("secret") != Some(&companion.secret) {
+        return Response::empty("403 Forbidden");
+    }
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => Response::ok("text/html; charset=utf-8", PLAYER_PAGE.to_string()),
+        ("GET", "/public") => {
+            let body = if companion.public_state.is_empty() {
+                "null".to_string()
+            } else {
+                companion.public_state.clone()
+            };
+            Response::ok("application/json", body)
+        }
+        ("GET", "/messages") => {
+            let token = request.query.get("token").cloned().unwrap_or_default();
+            let messages = companion.messages.get(&token).cloned().unwrap_or_default();
+            Response::ok(
+                "application/json",
+                serde_json::to_string(&messages).unwrap(),
+            )
+        }
+        ("POST", "/public") => {
+            companion.public_state = request.body;
+            Response::empty("204 No Content")
+        }
+        ("POST", "/message") => {
+            let message: serde_json::Value = match serde_json::from_str(&request.body) {
+                Ok(message) => message,
+                Err(_) => return Response::empty("400 Bad Request"),
+            };
+            let (Some(token), Some(text)) = (message["token"].as_str(), message["text"].as_str())
+            else {
+                return Response::empty("400 Bad Request");
+            };
+            companion
+                .messages
+                .entry(token.to_string())
+                .or_default()
+                .push(text.to_string());
+            Response::empty("204 No Content")
+        }
+        ("POST", "/reset") => {
+            *companion = Companion::new(std::mem::take(&mut companion.secret));
+            Response::empty("204 No Content")
+        }
+        ("OPTIONS", _) => Response::empty("204 No Content"),
+        _ => Response::empty("404 Not Found"),
+    }
+}
+
+fn read_request(stream: &mut TcpStream) -> std::io::Result<Option<Request>> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Ok(None);
+    };
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length")
+        {
+            content_length = value.trim().parse().unwrap_or(0);
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    return Ok(Some(Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: parse_query(query),
+        body: String::from_utf8_lossy(&body).into_owned(),
+    }));
+}
+
+fn write_response(
+    stream: &mut TcpStream,
+    response: Response,
+    allow_origin: bool,
+) -> std::io::Result<()> {
+    let cors = if allow_origin {
+        "Access-Control-Allow-Origin: *\r\nAccess-Control-Allow-Headers: *\r\n"
+    } else {
+        ""
+    };
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len(),
+        cors
+    );
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(response.body.as_bytes())?;
+    return stream.flush();
+}
+
+/// Hard to guess string from the randomly seeded hasher std uses for HashMaps
+fn random_token() -> String {
+    return (0..2)
+        .map(|_| format!("{:016x}", RandomState::new().hash_one(SystemTime::now())))
+        .collect();
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (percent_decode(key), percent_decode(value)))
+        .collect()
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    return String::from_utf8_lossy(&decoded).into_owned();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes() {
+        let mut companion = Companion::new("secret".to_string());
+        let request = |method: &str, path: &str, query: &str, body: &str| Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            query: parse_query(query),
+            body: body.to_string(),
+        };
+
+        let message = r#"{"token":"ann","text":"You are the Chef"}"#;
+        let response = route(request("POST", "/message", "", message), &mut companion);
+        assert_eq!(response.status, "403 Forbidden");
+        let response = route(
+            request("POST", "/message", "secret=guess", message),
+            &mut companion,
+        );
+        assert_eq!(response.status, "403 Forbidden");
+        route(
+            request("POST", "/message", "secret=secret", message),
+            &mut companion,
+        );
+
+        let response = route(request("GET", "/messages", "token=ann", ""), &mut companion);
+        assert_eq!(response.body, r#"["You are the Chef"]"#);
+        let response = route(request("GET", "/messages", "token=bob", ""), &mut companion);
+        assert_eq!(response.body, "[]");
+
+        let response = route(request("GET", "/public", "", ""), &mut companion);
+        assert_eq!(response.body, "null");
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use leptos::{leptos_dom::logging::console_error, prelude::*, wasm_bindgen::JsValue};
+use serde_derive::Serialize;
+
+use crate::engine::{night_order::percent_encode, state::public::PublicState};
+
+/// The companion server players connect to with their phones, if the storyteller has connected
+/// to one. See src/bin/companion.rs
+#[derive(Clone, Copy)]
+pub(crate) struct CompanionServer(pub(crate) RwSignal<Option<Connection>>);
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Connection {
+    pub(crate) address: String,
+    /// Printed by the server when it starts. Posts without it are turned away
+    secret: String,
+    /// The token in each player's link, by name. Only the player holding it can read their
+    /// messages
+    tokens: HashMap<String, String>,
+}
+
+impl Connection {
+    pub(crate) fn new(address: String, secret: String) -> Self {
+        Self {
+            address: address.trim_end_matches('/').to_string(),
+            secret,
+            tokens: HashMap::new(),
+        }
+    }
+
+    /// The player's token, made up the first time it's asked for
+    pub(crate) fn token(&mut self, name: &str) -> String {
+        return self
+            .tokens
+            .entry(name.to_string())
+            .or_insert_with(|| {
+                format!(
+                    "{:016x}{:016x}",
+                    rand::random::<u64>(),
+                    rand::random::<u64>()
+                )
+            })
+            .clone();
+    }
+
+    /// The link the storyteller gives the player, so only they see their messages
+    pub(crate) fn player_link(&mut self, name: &str) -> String {
+        let token = self.token(name);
+        return format!("{}/?token={}", self.address, percent_encode(&token));
+    }
+
+    fn url(&self, path: &str) -> String {
+        return format!(
+            "{}{}?secret={}",
+            self.address,
+            path,
+            percent_encode(&self.secret)
+        );
+    }
+}
+
+/// Something only one player should see
+#[derive(Serialize)]
+struct PrivateMessage<'a> {
+    token: &'a str,
+    text: &'a str,
+}
+
+pub(crate) fn send_public_state(connection: &Connection, public_state: &PublicState) {
+    match serde_json::to_string(public_state) {
+        Ok(body) => post(&connection.url("/public"), body),
+        Err(err) => console_error(format!("Could not serialize public state: {}", err).as_str()),
+    }
+}
+
+pub(crate) fn send_private_message(connection: &mut Connection, player: &str, text: &str) {
+    let token = connection.token(player);
+    match serde_json::to_string(&PrivateMessage {
+        token: &token,
+        text,
+    }) {
+        Ok(body) => post(&connection.url("/message"), body),
+        Err(err) => console_error(format!("Could not serialize message: {}", err).as_str()),
+    }
+}
+
+pub(crate) fn reset(connection: &Connection) {
+    post(&connection.url("/reset"), String::new());
+}
+
+// Plain text bodies keep this a simple request, so the browser doesn't need to ask the server
+// about CORS first
+fn post(url: &str, body: String) {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+    let init = web_sys::RequestInit::new();
+    init.set_method("POST");
+    init.set_body(&JsValue::from_str(&body));
+    // Fire and forget. Players just see stale info if the server is down
+    let _ = window.fetch_with_str_and_init(url, &init);
+}
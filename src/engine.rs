@@ -1,7 +1,13 @@
 #![allow(dead_code, clippy::needless_return)]
 pub(crate) mod change_request;
+pub(crate) mod error;
+pub(crate) mod night_order;
 pub(crate) mod player;
-pub(crate) mod setup;
+pub(crate) mod report;
+#[cfg(test)]
+pub(crate) mod scenario;
+#[cfg(test)]
+pub(crate) mod simulation;
 pub(crate) mod state;
 
 // use leptos::prelude::RwSignal;
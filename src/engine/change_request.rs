@@ -1,15 +1,32 @@
-use crate::engine::player::Player;
+use crate::engine::{
+    error::GameError,
+    player::{CharacterType, Player, roles::Role},
+};
 
 use super::{
     player::roles::RoleNames,
     state::{PlayerIndex, State},
 };
-use std::{fmt::Debug, ops::Deref, sync::Arc};
+use std::{
+    fmt::{Debug, Display},
+    ops::Deref,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub(crate) enum ChangeType {
     ChoosePlayers(usize),
     ChooseRoles(usize),
+    /// A role for each of this many players
+    ChoosePlayersAndRoles(usize),
+    YesNo,
+    ChooseNumber {
+        min: usize,
+        max: usize,
+    },
     Voting,
     NoStoryteller,
     // Display
@@ -20,6 +37,10 @@ pub(crate) enum ChangeType {
 pub(crate) enum ChangeArgs {
     PlayerIndices(Vec<PlayerIndex>),
     Roles(Vec<RoleNames>),
+    PlayersAndRoles(Vec<(PlayerIndex, RoleNames)>),
+    YesNo(bool),
+    Number(usize),
+    /// Also the answer to an optional change request that was skipped
     Blank,
 }
 
@@ -37,6 +58,46 @@ impl ChangeArgs {
             _ => Err(ChangeError::WrongArgType),
         }
     }
+
+    pub fn extract_players_and_roles(&self) -> Result<Vec<(PlayerIndex, RoleNames)>, ChangeError> {
+        match self {
+            ChangeArgs::PlayersAndRoles(pairs) => Ok(pairs.to_owned()),
+            _ => Err(ChangeError::WrongArgType),
+        }
+    }
+
+    pub fn extract_yes_no(&self) -> Result<bool, ChangeError> {
+        match self {
+            ChangeArgs::YesNo(answer) => Ok(*answer),
+            _ => Err(ChangeError::WrongArgType),
+        }
+    }
+
+    pub fn extract_number(&self) -> Result<usize, ChangeError> {
+        match self {
+            ChangeArgs::Number(number) => Ok(*number),
+            _ => Err(ChangeError::WrongArgType),
+        }
+    }
+
+    /// Whether this is the kind of answer the change type asks for
+    fn answers(&self, change_type: ChangeType) -> bool {
+        matches!(
+            (change_type, self),
+            (ChangeType::ChoosePlayers(_), ChangeArgs::PlayerIndices(_))
+                | (ChangeType::ChooseRoles(_), ChangeArgs::Roles(_))
+                | (
+                    ChangeType::ChoosePlayersAndRoles(_),
+                    ChangeArgs::PlayersAndRoles(_)
+                )
+                | (ChangeType::YesNo, ChangeArgs::YesNo(_))
+                | (ChangeType::ChooseNumber { .. }, ChangeArgs::Number(_))
+                | (
+                    ChangeType::Voting | ChangeType::NoStoryteller | ChangeType::Display,
+                    ChangeArgs::Blank
+                )
+        )
+    }
 }
 
 pub fn check_len<T>(vec: &[T], desired_len: usize) -> Result<(), ChangeError> {
@@ -51,10 +112,185 @@ pub fn check_len<T>(vec: &[T], desired_len: usize) -> Result<(), ChangeError> {
     Ok(())
 }
 
+/// The answer an information ability gives its player. Kept with the change request that shows
+/// it, so the storyteller can give something else when the ability malfunctions
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Information {
+    Number(usize),
+    Players(Vec<PlayerIndex>),
+    Roles(Vec<RoleNames>),
+    /// One of the players is the role
+    PlayersAndRole(Vec<PlayerIndex>, RoleNames),
+    YesNo(bool),
+}
+
+/// Turns information into what the storyteller should show the player
+pub(crate) type InfoFormat = fn(&Information) -> String;
+
+impl Information {
+    /// How the storyteller picks a different answer of the same kind
+    pub(crate) fn change_type(&self, player_count: usize) -> ChangeType {
+        match self {
+            Information::Number(_) => ChangeType::ChooseNumber {
+                min: 0,
+                max: player_count,
+            },
+            Information::Players(player_indices) => ChangeType::ChoosePlayers(player_indices.len()),
+            Information::Roles(roles) => ChangeType::ChooseRoles(roles.len()),
+            Information::PlayersAndRole(player_indices, _) => {
+                ChangeType::ChoosePlayersAndRoles(player_indices.len())
+            }
+            Information::YesNo(_) => ChangeType::YesNo,
+        }
+    }
+
+    /// The answer the storyteller picked for a change request made by change_type
+    pub(crate) fn answered_by(&self, args: &ChangeArgs) -> Result<Information, ChangeError> {
+        match self {
+            Information::Number(_) => Ok(Information::Number(args.extract_number()?)),
+            Information::Players(_) => Ok(Information::Players(args.extract_player_indicies()?)),
+            Information::Roles(_) => Ok(Information::Roles(args.clone().extract_roles()?)),
+            Information::PlayersAndRole(..) => {
+                // Constraint::SameRole already made sure every player is paired with one role
+                let pairs = args.extract_players_and_roles()?;
+                let (_, role) = *pairs.first().ok_or(ChangeError::BlankArgs)?;
+                let player_indices = pairs.iter().map(|(player_index, _)| *player_index);
+                Ok(Information::PlayersAndRole(player_indices.collect(), role))
+            }
+            Information::YesNo(_) => Ok(Information::YesNo(args.extract_yes_no()?)),
+        }
+    }
+}
+
+impl Display for Information {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Information::Number(number) => write!(f, "{}", number),
+            Information::Players(player_indices) => {
+                let seats: Vec<String> = player_indices
+                    .iter()
+                    .map(|player_index| format!("seat {}", player_index + 1))
+                    .collect();
+                f.write_str(&seats.join(", "))
+            }
+            Information::Roles(roles) => {
+                let roles: Vec<String> = roles.iter().map(|role| role.to_string()).collect();
+                f.write_str(&roles.join(", "))
+            }
+            Information::PlayersAndRole(player_indices, role) => {
+                let seats: Vec<String> = player_indices
+                    .iter()
+                    .map(|player_index| format!("seat {}", player_index + 1))
+                    .collect();
+                write!(f, "{} is the {}", seats.join(" or "), role)
+            }
+            Information::YesNo(true) => f.write_str("Yes"),
+            Information::YesNo(false) => f.write_str("No"),
+        }
+    }
+}
+
+/// A rule about what can be selected for a change request. The engine checks them before the
+/// state change runs, and the interface uses them to disable what can't be picked
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Constraint {
+    /// Between min and max players or roles, inclusive. Without one, exactly the number in the
+    /// change type has to be chosen
+    Count {
+        min: usize,
+        max: usize,
+    },
+    /// Nothing can be chosen twice
+    Distinct,
+    /// The player whose ability it is can't choose themselves
+    NotSelf(PlayerIndex),
+    Alive,
+    Dead,
+    /// Only players whose true character type is this
+    CharacterType(CharacterType),
+    /// Players that were chosen earlier in the same ability
+    NotChosen(Vec<PlayerIndex>),
+    /// Only roles of this character type
+    RoleType(CharacterType),
+    /// Only roles that nobody has
+    RoleNotInPlay,
+    /// Every player is paired with the same role
+    SameRole,
+}
+
+impl Constraint {
+    /// Why the player can't be chosen, if they can't
+    fn player_reason(&self, player_index: PlayerIndex, player: &Player) -> Option<String> {
+        let allowed = match self {
+            Constraint::NotSelf(self_index) => *self_index != player_index,
+            Constraint::Alive => !player.dead,
+            Constraint::Dead => player.dead,
+            Constraint::CharacterType(character_type) => {
+                player.role.get_true_character_type() == *character_type
+            }
+            Constraint::NotChosen(chosen) => !chosen.contains(&player_index),
+            _ => true,
+        };
+        if allowed {
+            return None;
+        }
+
+        let reason = match self {
+            Constraint::NotSelf(_) => format!("{} can't choose themselves", player.name),
+            Constraint::Alive => format!("{} is dead", player.name),
+            Constraint::Dead => format!("{} is alive", player.name),
+            Constraint::CharacterType(character_type) => {
+                format!("{} isn't a {}", player.name, character_type)
+            }
+            _ => format!("{} was already chosen", player.name),
+        };
+        return Some(reason);
+    }
+
+    /// Why the role can't be chosen, if it can't
+    fn role_reason(&self, role: RoleNames, state: &State) -> Option<String> {
+        match self {
+            Constraint::RoleType(character_type) if role.get_type() != *character_type => {
+                Some(format!("The {} isn't a {}", role, character_type))
+            }
+            Constraint::RoleNotInPlay
+                if state
+                    .get_players()
+                    .iter()
+                    .any(|player| player.role.to_role_name() == role) =>
+            {
+                Some(format!("The {} is already in play", role))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Constraint::Count { min, max } if min == max => write!(f, "Choose exactly {}", min),
+            Constraint::Count { min, max } => write!(f, "Choose {} to {}", min, max),
+            Constraint::Distinct => f.write_str("All different"),
+            Constraint::NotSelf(_) => f.write_str("Not themselves"),
+            Constraint::Alive => f.write_str("Living players only"),
+            Constraint::Dead => f.write_str("Dead players only"),
+            Constraint::CharacterType(character_type) => {
+                write!(f, "{} players only", character_type)
+            }
+            Constraint::NotChosen(_) => f.write_str("Not someone already chosen"),
+            Constraint::RoleType(character_type) => write!(f, "{} roles only", character_type),
+            Constraint::RoleNotInPlay => f.write_str("Roles not in play only"),
+            Constraint::SameRole => f.write_str("The same role for everyone"),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct ChangeRequest {
     change_type: ChangeType,
-    filter_func: Option<FilterFuncPtr>,
+    constraints: Vec<Constraint>,
+    optional: bool,
     state_change_func: Option<StateChangeFuncPtr>,
     // on_success: Option<SuccessFunc>
     description: String,
@@ -67,12 +303,26 @@ impl ChangeRequest {
     ) -> ChangeRequestBuilder {
         ChangeRequestBuilder {
             change_type,
-            filter_func: None,
+            constraints: vec![],
+            optional: false,
             state_change_func: None,
+            information: None,
             description,
+            source_player_index: None,
+            id: NEXT_CHANGE_REQUEST_ID.fetch_add(1, Ordering::Relaxed),
         }
     }
 
+    /// Shows a player what their ability learned
+    pub(crate) fn show_information(
+        information: Information,
+        format: InfoFormat,
+    ) -> ChangeRequestBuilder {
+        let mut builder = Self::new_builder(ChangeType::Display, format(&information));
+        builder.information = Some((information, format));
+        builder
+    }
+
     pub(crate) fn get_change_type(&self) -> ChangeType {
         self.change_type
     }
@@ -81,13 +331,181 @@ impl ChangeRequest {
         self.description.clone()
     }
 
-    pub(crate) fn get_filter_func(&self) -> Option<&FilterFuncPtr> {
-        match &self.filter_func {
-            Some(filter_func) => Some(filter_func),
-            None => None,
+    pub(crate) fn get_constraints(&self) -> &Vec<Constraint> {
+        &self.constraints
+    }
+
+    /// Whether the storyteller can skip this, answering with [`ChangeArgs::Blank`]
+    pub(crate) fn is_optional(&self) -> bool {
+        self.optional
+    }
+
+    /// How many players or roles can be chosen, if this asks for a choice
+    pub(crate) fn count_range(&self) -> Option<(usize, usize)> {
+        let count = self
+            .constraints
+            .iter()
+            .find_map(|constraint| match constraint {
+                Constraint::Count { min, max } => Some((*min, *max)),
+                _ => None,
+            });
+        match self.change_type {
+            ChangeType::ChoosePlayers(num)
+            | ChangeType::ChooseRoles(num)
+            | ChangeType::ChoosePlayersAndRoles(num) => Some(count.unwrap_or((num, num))),
+            _ => None,
         }
     }
 
+    /// Why the player can't be chosen for this change request, if they can't
+    pub(crate) fn player_disabled_reason(
+        &self,
+        player_index: PlayerIndex,
+        player: &Player,
+    ) -> Option<String> {
+        self.constraints
+            .iter()
+            .find_map(|constraint| constraint.player_reason(player_index, player))
+    }
+
+    /// Why the role can't be chosen for this change request, if it can't
+    pub(crate) fn role_disabled_reason(&self, role: RoleNames, state: &State) -> Option<String> {
+        self.constraints
+            .iter()
+            .find_map(|constraint| constraint.role_reason(role, state))
+    }
+
+    /// Checks the selection against every constraint
+    pub(crate) fn validate(&self, state: &State, args: &ChangeArgs) -> Result<(), GameError> {
+        if matches!(args, ChangeArgs::Blank) && self.optional {
+            return Ok(());
+        }
+        if !args.answers(self.change_type) {
+            return Err(match args {
+                ChangeArgs::Blank => ChangeError::BlankArgs,
+                _ => ChangeError::WrongArgType,
+            }
+            .into());
+        }
+
+        let (selected, roles): (usize, bool) = match args {
+            ChangeArgs::PlayerIndices(player_indices) => (player_indices.len(), false),
+            ChangeArgs::Roles(roles) => (roles.len(), true),
+            ChangeArgs::PlayersAndRoles(pairs) => (pairs.len(), false),
+            ChangeArgs::Number(number) => {
+                if let ChangeType::ChooseNumber { min, max } = self.change_type
+                    && !(min..=max).contains(number)
+                {
+                    return Err(ChangeError::NumberOutOfRange {
+                        min,
+                        max,
+                        got: *number,
+                    }
+                    .into());
+                }
+                return Ok(());
+            }
+            ChangeArgs::YesNo(_) | ChangeArgs::Blank => return Ok(()),
+        };
+
+        if let Some((min, max)) = self.count_range()
+            && !(min..=max).contains(&selected)
+        {
+            return Err(match (min == max, roles) {
+                (true, false) => ChangeError::WrongNumberOfSelectedPlayers {
+                    wanted: min,
+                    got: selected,
+                },
+                (true, true) => ChangeError::WrongNumberOfSelectedRoles {
+                    wanted: min,
+                    got: selected,
+                },
+                (false, _) => ChangeError::SelectionOutOfRange {
+                    min,
+                    max,
+                    got: selected,
+                },
+            }
+            .into());
+        }
+
+        match args {
+            ChangeArgs::PlayerIndices(player_indices) => {
+                self.validate_players(state, player_indices)
+            }
+            ChangeArgs::Roles(roles) => self.validate_roles(state, roles),
+            ChangeArgs::PlayersAndRoles(pairs) => {
+                let (player_indices, roles): (Vec<PlayerIndex>, Vec<RoleNames>) =
+                    pairs.iter().copied().unzip();
+                self.validate_players(state, &player_indices)?;
+                if self.constraints.contains(&Constraint::SameRole)
+                    && roles.iter().any(|role| *role != roles[0])
+                {
+                    return Err(ChangeError::InvalidSelectedRole {
+                        reason: "Pair every player with the same role".into(),
+                    }
+                    .into());
+                }
+                // A role can go to more than one player, so only the players have to be distinct
+                for role in roles {
+                    if let Some(reason) = self.role_disabled_reason(role, state) {
+                        return Err(ChangeError::InvalidSelectedRole { reason }.into());
+                    }
+                }
+                return Ok(());
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_players(
+        &self,
+        state: &State,
+        player_indices: &[PlayerIndex],
+    ) -> Result<(), GameError> {
+        for (position, player_index) in player_indices.iter().enumerate() {
+            let player = state.get_player(*player_index)?;
+            let repeated = self.constraints.contains(&Constraint::Distinct)
+                && player_indices[..position].contains(player_index);
+            let reason = match repeated {
+                true => Some(format!("{} was chosen twice", player.name)),
+                false => self.player_disabled_reason(*player_index, player),
+            };
+            if let Some(reason) = reason {
+                return Err(ChangeError::InvalidSelectedPlayer { reason }.into());
+            }
+        }
+        return Ok(());
+    }
+
+    fn validate_roles(&self, state: &State, roles: &[RoleNames]) -> Result<(), GameError> {
+        for (position, role) in roles.iter().enumerate() {
+            let repeated = self.constraints.contains(&Constraint::Distinct)
+                && roles[..position].contains(role);
+            let reason = match repeated {
+                true => Some(format!("The {} was chosen twice", role)),
+                false => self.role_disabled_reason(*role, state),
+            };
+            if let Some(reason) = reason {
+                return Err(ChangeError::InvalidSelectedRole { reason }.into());
+            }
+        }
+        return Ok(());
+    }
+
+    /// Validates the selection, then makes the change. Everything that answers a change request
+    /// should go through here
+    pub(crate) fn apply(&self, state: &mut State, args: ChangeArgs) -> ChangeResult {
+        self.validate(state, &args)?;
+        state.applying_change_request = Some(self.get_description());
+        let result = state.in_ability(|state| match &self.state_change_func {
+            Some(state_change_func) => state_change_func.call(state, args),
+            None => Ok(()),
+        });
+        state.applying_change_request = None;
+        return result;
+    }
+
     pub(crate) fn get_state_change_func(&self) -> Option<&StateChangeFuncPtr> {
         match &self.state_change_func {
             Some(state_change_func) => Some(state_change_func),
@@ -109,19 +527,32 @@ impl Debug for ChangeRequest {
     }
 }
 
+/// Tells queued change requests apart, so an ability's own requests can be taken back off the
+/// queue wherever they ended up
+pub(crate) type ChangeRequestId = usize;
+
+static NEXT_CHANGE_REQUEST_ID: AtomicUsize = AtomicUsize::new(0);
+
 #[derive(Clone, Debug)]
 pub(crate) struct ChangeRequestBuilder {
     pub(crate) change_type: ChangeType,
-    pub(crate) filter_func: Option<FilterFuncPtr>,
+    pub(crate) constraints: Vec<Constraint>,
+    pub(crate) optional: bool,
     pub(crate) state_change_func: Option<StateChangeFuncPtr>,
+    /// The true answer this shows, for information abilities
+    pub(crate) information: Option<(Information, InfoFormat)>,
     pub(crate) description: String,
+    /// The player whose ability or listener queued this, once it has been made suppressible
+    pub(crate) source_player_index: Option<PlayerIndex>,
+    pub(crate) id: ChangeRequestId,
 }
 
 impl ChangeRequestBuilder {
     pub(crate) fn build(self) -> ChangeRequest {
         ChangeRequest {
             change_type: self.change_type,
-            filter_func: self.filter_func,
+            constraints: self.constraints,
+            optional: self.optional,
             state_change_func: self.state_change_func,
             description: self.description,
         }
@@ -141,8 +572,15 @@ impl ChangeRequestBuilder {
         self
     }
 
-    pub(crate) fn filter_func(mut self, filter_func: FilterFuncPtr) -> Self {
-        self.filter_func = Some(filter_func);
+    pub(crate) fn constraint(mut self, constraint: Constraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Lets the storyteller skip the change request, for abilities the player doesn't have to
+    /// use. The state change function gets [`ChangeArgs::Blank`] when it's skipped
+    pub(crate) fn optional(mut self) -> Self {
+        self.optional = true;
         self
     }
 
@@ -157,28 +595,6 @@ impl ChangeRequestBuilder {
     }
 }
 
-pub(crate) type FilterFunc = dyn Fn(PlayerIndex, &Player) -> bool + Send + Sync;
-#[derive(Clone)]
-pub(crate) struct FilterFuncPtr(Arc<FilterFunc>);
-impl FilterFuncPtr {
-    pub fn new<F>(func: F) -> Self
-    where
-        F: Fn(PlayerIndex, &Player) -> bool + Send + Sync + 'static,
-    {
-        Self(Arc::new(func))
-    }
-
-    pub fn call(&self, player_index: PlayerIndex, player: &Player) -> bool {
-        self.0(player_index, player)
-    }
-}
-
-impl Debug for FilterFuncPtr {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_tuple("FilterFuncPtr").finish()
-    }
-}
-
 // impl From<Arc<CheckFunc>> for CheckFuncPtr {
 //     fn from
 //         value: Arc<dyn Fn(&State, &ChangeArgs) -> Result<bool, ChangeError> + Send + Sync>,
@@ -222,20 +638,162 @@ impl Debug for StateChangeFuncPtr {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum ChangeError {
     InvalidSelectedPlayer { reason: String },
     InvalidSelectedRole { reason: String },
     WrongNumberOfSelectedPlayers { wanted: usize, got: usize },
     WrongNumberOfSelectedRoles { wanted: usize, got: usize },
+    SelectionOutOfRange { min: usize, max: usize, got: usize },
+    NumberOutOfRange { min: usize, max: usize, got: usize },
     WrongArgType,
     BlankArgs,
 }
 
-pub type ChangeResult = Result<(), ChangeError>;
+impl Display for ChangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeError::InvalidSelectedPlayer { reason }
+            | ChangeError::InvalidSelectedRole { reason } => f.write_str(reason),
+            ChangeError::WrongNumberOfSelectedPlayers { wanted, got } => {
+                write!(f, "Select {} players, not {}", wanted, got)
+            }
+            ChangeError::WrongNumberOfSelectedRoles { wanted, got } => {
+                write!(f, "Select {} roles, not {}", wanted, got)
+            }
+            ChangeError::SelectionOutOfRange { min, max, got } => {
+                write!(f, "Select {} to {}, not {}", min, max, got)
+            }
+            ChangeError::NumberOutOfRange { min, max, got } => {
+                write!(f, "Choose a number from {} to {}, not {}", min, max, got)
+            }
+            ChangeError::WrongArgType => f.write_str("That isn't the kind of answer asked for"),
+            ChangeError::BlankArgs => f.write_str("Nothing was selected"),
+        }
+    }
+}
+
+/// State change functions can fail because of what was selected, or because the change isn't
+/// allowed in the game anymore
+pub type ChangeResult = Result<(), GameError>;
 
 // impl From<ChangeRequestBuilder> for ChangeResult {
 //     fn from(value: ChangeRequestBuilder) -> Self {
 //         Ok(Some(value))
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{
+        player::roles::RoleNames,
+        scenario::Scenario,
+        state::{
+            DayStep, Step,
+            status_effects::{StatusEffect, StatusType},
+        },
+    };
+
+    use super::*;
+
+    fn town() -> Scenario {
+        return Scenario::new(&[
+            ("Alice", RoleNames::Chef),
+            ("Bob", RoleNames::Empath),
+            ("Carol", RoleNames::Butler),
+            ("Dan", RoleNames::Poisoner),
+            ("Eve", RoleNames::Imp),
+        ]);
+    }
+
+    #[test]
+    fn test_richer_inputs() {
+        let mut scenario = town();
+        let state = &scenario.state;
+
+        let yes_no = ChangeRequest::new_builder(ChangeType::YesNo, "Yes or no".into()).build();
+        assert!(yes_no.validate(state, &ChangeArgs::YesNo(true)).is_ok());
+        assert_eq!(
+            yes_no.validate(state, &ChangeArgs::Number(1)),
+            Err(ChangeError::WrongArgType.into())
+        );
+        assert_eq!(
+            yes_no.validate(state, &ChangeArgs::Blank),
+            Err(ChangeError::BlankArgs.into())
+        );
+
+        let number = ChangeRequest::new_builder(
+            ChangeType::ChooseNumber { min: 0, max: 3 },
+            "Pick a number".into(),
+        )
+        .build();
+        assert!(number.validate(state, &ChangeArgs::Number(3)).is_ok());
+        assert_eq!(
+            number.validate(state, &ChangeArgs::Number(4)),
+            Err(ChangeError::NumberOutOfRange {
+                min: 0,
+                max: 3,
+                got: 4
+            }
+            .into())
+        );
+
+        let pairs = ChangeRequest::new_builder(
+            ChangeType::ChoosePlayersAndRoles(2),
+            "Guess two roles".into(),
+        )
+        .constraint(Constraint::Distinct)
+        .constraint(Constraint::RoleType(CharacterType::Townsfolk))
+        .build();
+        let valid = ChangeArgs::PlayersAndRoles(vec![(0, RoleNames::Chef), (1, RoleNames::Chef)]);
+        assert!(pairs.validate(state, &valid).is_ok());
+        let repeated =
+            ChangeArgs::PlayersAndRoles(vec![(0, RoleNames::Chef), (0, RoleNames::Monk)]);
+        assert!(pairs.validate(state, &repeated).is_err());
+        let outsider =
+            ChangeArgs::PlayersAndRoles(vec![(0, RoleNames::Chef), (1, RoleNames::Butler)]);
+        assert!(pairs.validate(state, &outsider).is_err());
+        let same_role = ChangeRequest::new_builder(
+            ChangeType::ChoosePlayersAndRoles(2),
+            "One of them is the Chef".into(),
+        )
+        .constraint(Constraint::SameRole)
+        .build();
+        assert!(same_role.validate(state, &valid).is_ok());
+        let mixed = ChangeArgs::PlayersAndRoles(vec![(0, RoleNames::Chef), (1, RoleNames::Monk)]);
+        assert!(same_role.validate(state, &mixed).is_err());
+
+        // Optional change requests can be skipped, and the state change sees the skip
+        let poison_if_used = ChangeRequest::new_builder(
+            ChangeType::ChoosePlayersAndRoles(1),
+            "Poison someone, maybe".into(),
+        )
+        .optional()
+        .state_change_func(StateChangeFuncPtr::new(|state, args| {
+            if let ChangeArgs::Blank = args {
+                return Ok(());
+            }
+            let (player_index, _) = args.extract_players_and_roles()?[0];
+            state
+                .get_player_mut(player_index)
+                .unwrap()
+                .add_status(StatusEffect::new(StatusType::Poisoned, 3, None));
+            Ok(())
+        }));
+        scenario.advance_to(Step::Day(DayStep::Discussion));
+        scenario
+            .state
+            .change_request_queue
+            .push_back(poison_if_used.clone());
+        scenario
+            .skip()
+            .assert_no_status("Bob", StatusType::Poisoned);
+        scenario
+            .state
+            .change_request_queue
+            .push_back(poison_if_used);
+        scenario
+            .answer_pairs(&[("Bob", RoleNames::Imp)])
+            .assert_status("Bob", StatusType::Poisoned);
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt::Display;
+
+use crate::engine::{
+    change_request::ChangeError,
+    player::roles::RoleNames,
+    state::{PlayerIndex, Step},
+};
+
+/// Anything the engine refuses to do, with a message that can be shown to the storyteller
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum GameError {
+    Setup(SetupError),
+    /// A change request was answered with something it can't accept
+    InvalidSelection(ChangeError),
+    /// The action isn't allowed in the current part of the game
+    IllegalAction {
+        action: &'static str,
+        step: Step,
+    },
+    NoSuchPlayer(PlayerIndex),
+    /// A reminder, note or claim that the player doesn't have
+    NoSuchEntry {
+        player_index: PlayerIndex,
+        kind: &'static str,
+        index: usize,
+    },
+    /// Nothing to cancel, or the ability already finished resolving
+    NoAbilityInProgress,
+    NotMad {
+        player_index: PlayerIndex,
+        role: RoleNames,
+    },
+    NotTraveller(PlayerIndex),
+    /// The role is on a script but has no ability implemented yet
+    UnsupportedRole(RoleNames),
+    /// No one's death ends the game, so the storyteller has to decide when it's over
+    NoWinCondition,
+}
+
+/// Problems with the players and roles a game is created from
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum SetupError {
+    UnsupportedPlayerCount(usize),
+    RoleCountMismatch { players: usize, roles: usize },
+    NoDemon,
+}
+
+impl Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::Setup(setup_error) => write!(f, "Can't set up the game: {}", setup_error),
+            GameError::InvalidSelection(change_error) => {
+                write!(f, "Invalid selection: {}", change_error)
+            }
+            GameError::IllegalAction { action, step } => {
+                write!(f, "Can't {} during {}", action, step)
+            }
+            GameError::NoSuchPlayer(player_index) => {
+                write!(f, "There is no player in seat {}", player_index + 1)
+            }
+            GameError::NoSuchEntry {
+                player_index,
+                kind,
+                index,
+            } => write!(
+                f,
+                "The player in seat {} has no {} #{}",
+                player_index + 1,
+                kind,
+                index + 1
+            ),
+            GameError::NoAbilityInProgress => f.write_str("No ability is being resolved"),
+            GameError::NotTraveller(player_index) => write!(
+                f,
+                "The player in seat {} isn't a traveller",
+                player_index + 1
+            ),
+            GameError::NotMad { player_index, role } => write!(
+                f,
+                "The player in seat {} isn't mad about being the {}",
+                player_index + 1,
+                role
+            ),
+            GameError::UnsupportedRole(role) => write!(f, "The {} isn't supported yet", role),
+            GameError::NoWinCondition => {
+                f.write_str("No player's death ends the game, the storyteller decides")
+            }
+        }
+    }
+}
+
+impl Display for SetupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetupError::UnsupportedPlayerCount(count) => {
+                write!(f, "{} players isn't supported, it has to be 5 to 15", count)
+            }
+            SetupError::RoleCountMismatch { players, roles } => {
+                write!(f, "There are {} players but {} roles", players, roles)
+            }
+            SetupError::NoDemon => f.write_str("There is no demon among the roles"),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+impl From<SetupError> for GameError {
+    fn from(value: SetupError) -> Self {
+        GameError::Setup(value)
+    }
+}
+
+impl From<ChangeError> for GameError {
+    fn from(value: ChangeError) -> Self {
+        GameError::InvalidSelection(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        engine::state::State, initialization::CharacterTypeCounts, scripts::trouble_brewing,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_game_errors() {
+        let player_names: Vec<String> = (0..5).map(|seat| format!("P{}", seat)).collect();
+        let no_demon = vec![
+            RoleNames::Chef,
+            RoleNames::Virgin,
+            RoleNames::Soldier,
+            RoleNames::Spy,
+            RoleNames::Mayor,
+        ];
+        assert_eq!(
+            State::new(no_demon, player_names.clone(), trouble_brewing()).unwrap_err(),
+            GameError::Setup(SetupError::NoDemon)
+        );
+        assert_eq!(
+            State::new(
+                vec![RoleNames::Imp],
+                player_names.clone(),
+                trouble_brewing()
+            )
+            .unwrap_err(),
+            GameError::Setup(SetupError::RoleCountMismatch {
+                players: 5,
+                roles: 1
+            })
+        );
+        assert_eq!(
+            CharacterTypeCounts::new(3).unwrap_err().to_string(),
+            "Can't set up the game: 3 players isn't supported, it has to be 5 to 15"
+        );
+
+        let roles = vec![
+            RoleNames::Chef,
+            RoleNames::Virgin,
+            RoleNames::Soldier,
+            RoleNames::Spy,
+            RoleNames::Imp,
+        ];
+        let mut state = State::new(roles, player_names, trouble_brewing()).unwrap();
+        let err = state.nominate_player(0, 1).unwrap_err();
+        assert_eq!(err.to_string(), "Can't nominate during Start");
+        assert_eq!(state.set_dead(7, true), Err(GameError::NoSuchPlayer(7)));
+        assert!(state.get_player(7).is_err());
+        assert!(matches!(
+            RoleNames::Gossip.convert(),
+            Err(GameError::UnsupportedRole(RoleNames::Gossip))
+        ));
+        assert!(matches!(
+            state.remove_note(0, 0),
+            Err(GameError::NoSuchEntry { kind: "note", .. })
+        ));
+        assert!(matches!(
+            state.skip_wake(0),
+            Err(GameError::NoSuchEntry { index: 0, .. })
+        ));
+        assert!(state.move_wake(0, 1).is_err());
+    }
+}
@@ -0,0 +1,532 @@
+use std::fmt::Display;
+
+use crate::{
+    engine::{player::roles::RoleNames, state::State},
+    initialization::Script,
+};
+
+/// Every official character (and storyteller step) that acts on the first night, by id, in the
+/// order they act
+const FIRST_NIGHT: &[&str] = &[
+    "dusk",
+    "lordoftyphon",
+    "kazali",
+    "apprentice",
+    "barista",
+    "bureaucrat",
+    "thief",
+    "boffin",
+    "philosopher",
+    "alchemist",
+    "poppygrower",
+    "yaggababble",
+    "magician",
+    "minioninfo",
+    "snitch",
+    "lunatic",
+    "summoner",
+    "demoninfo",
+    "king",
+    "sailor",
+    "marionette",
+    "engineer",
+    "preacher",
+    "lilmonsta",
+    "lleech",
+    "xaan",
+    "poisoner",
+    "widow",
+    "courtier",
+    "wizard",
+    "snakecharmer",
+    "godfather",
+    "organgrinder",
+    "devilsadvocate",
+    "eviltwin",
+    "witch",
+    "cerenovus",
+    "fearmonger",
+    "harpy",
+    "mezepheles",
+    "pukka",
+    "pixie",
+    "huntsman",
+    "damsel",
+    "amnesiac",
+    "washerwoman",
+    "librarian",
+    "investigator",
+    "chef",
+    "empath",
+    "fortuneteller",
+    "butler",
+    "grandmother",
+    "clockmaker",
+    "dreamer",
+    "seamstress",
+    "steward",
+    "knight",
+    "noble",
+    "balloonist",
+    "shugenja",
+    "villageidiot",
+    "bountyhunter",
+    "nightwatchman",
+    "cultleader",
+    "spy",
+    "ogre",
+    "highpriestess",
+    "general",
+    "chambermaid",
+    "mathematician",
+    "dawn",
+    "leviathan",
+    "vizier",
+];
+
+/// Every official character (and storyteller step) that acts on the other nights, by id, in the
+/// order they act
+const OTHER_NIGHTS: &[&str] = &[
+    "dusk",
+    "barista",
+    "bureaucrat",
+    "thief",
+    "philosopher",
+    "poppygrower",
+    "sailor",
+    "engineer",
+    "preacher",
+    "xaan",
+    "poisoner",
+    "courtier",
+    "innkeeper",
+    "wizard",
+    "gambler",
+    "acrobat",
+    "snakecharmer",
+    "monk",
+    "organgrinder",
+    "devilsadvocate",
+    "witch",
+    "cerenovus",
+    "pithag",
+    "fearmonger",
+    "harpy",
+    "mezepheles",
+    "scarletwoman",
+    "summoner",
+    "lunatic",
+    "exorcist",
+    "lycanthrope",
+    "legion",
+    "imp",
+    "zombuul",
+    "pukka",
+    "shabaloth",
+    "po",
+    "fanggu",
+    "nodashii",
+    "vortox",
+    "lordoftyphon",
+    "vigormortis",
+    "ojo",
+    "alhadikhia",
+    "lleech",
+    "lilmonsta",
+    "yaggababble",
+    "kazali",
+    "assassin",
+    "godfather",
+    "gossip",
+    "hatter",
+    "barber",
+    "sweetheart",
+    "sage",
+    "banshee",
+    "professor",
+    "choirboy",
+    "huntsman",
+    "damsel",
+    "amnesiac",
+    "farmer",
+    "tinker",
+    "moonchild",
+    "grandmother",
+    "ravenkeeper",
+    "empath",
+    "fortuneteller",
+    "undertaker",
+    "dreamer",
+    "flowergirl",
+    "towncrier",
+    "oracle",
+    "seamstress",
+    "juggler",
+    "balloonist",
+    "villageidiot",
+    "king",
+    "bountyhunter",
+    "nightwatchman",
+    "cultleader",
+    "butler",
+    "spy",
+    "highpriestess",
+    "general",
+    "chambermaid",
+    "mathematician",
+    "dawn",
+    "leviathan",
+];
+
+/// Characters whose setup has to happen before everyone else's. The Drunk has to know which
+/// Townsfolk they think they are before that Townsfolk's setup
+const SETUP_FIRST: &[&str] = &["drunk"];
+
+/// Where the character (or storyteller step) with this id acts on the first night, if they do.
+/// Numbered the same way as a script's firstNight, counting from 1 since 0 means they don't wake
+pub(crate) fn first_night_order(id: &str) -> Option<usize> {
+    FIRST_NIGHT
+        .iter()
+        .position(|entry| *entry == id)
+        .map(|order| order + 1)
+}
+
+/// Where the character (or storyteller step) with this id acts on the other nights, if they do.
+/// Numbered the same way as a script's otherNight
+pub(crate) fn other_night_order(id: &str) -> Option<usize> {
+    OTHER_NIGHTS
+        .iter()
+        .position(|entry| *entry == id)
+        .map(|order| order + 1)
+}
+
+/// Where the character with this id does their setup. Anything that isn't set up first goes in
+/// first night order, then other night order
+pub(crate) fn setup_order(id: &str) -> usize {
+    if let Some(order) = SETUP_FIRST.iter().position(|entry| *entry == id) {
+        return order;
+    }
+
+    let after_setup_first = SETUP_FIRST.len();
+    if let Some(order) = first_night_order(id) {
+        return after_setup_first + order;
+    }
+    let after_first_night = after_setup_first + FIRST_NIGHT.len();
+    if let Some(order) = other_night_order(id) {
+        return after_first_night + order;
+    }
+
+    after_first_night + OTHER_NIGHTS.len()
+}
+
+/// A step of the night order. Besides the roles, the storyteller has a few steps of their own
+/// every night
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum NightOrderEntry {
+    Dusk,
+    MinionInfo,
+    DemonInfo,
+    Role(RoleNames),
+    Dawn,
+}
+
+impl NightOrderEntry {
+    pub(crate) fn id(&self) -> String {
+        match self {
+            NightOrderEntry::Dusk => "dusk".to_string(),
+            NightOrderEntry::MinionInfo => "minioninfo".to_string(),
+            NightOrderEntry::DemonInfo => "demoninfo".to_string(),
+            NightOrderEntry::Role(role) => role.id(),
+            NightOrderEntry::Dawn => "dawn".to_string(),
+        }
+    }
+
+    /// The text the storyteller reads when they get to this step of the night
+    pub(crate) fn reminder(&self, first_night: bool) -> &'static str {
+        match self {
+            NightOrderEntry::Dusk => "Start the Night Phase.",
+            NightOrderEntry::MinionInfo => {
+                "If there are 7 or more players, wake all Minions: Show the THIS IS THE DEMON token. Point to the Demon. Show the THESE ARE YOUR MINIONS token. Point to the other Minions."
+            }
+            NightOrderEntry::DemonInfo => {
+                "If there are 7 or more players, wake the Demon: Show the THESE ARE YOUR MINIONS token. Point to all Minions. Show the THESE CHARACTERS ARE NOT IN PLAY token. Show 3 not-in-play good character tokens."
+            }
+            NightOrderEntry::Dawn => "Wait for a few seconds. End the Night Phase.",
+            NightOrderEntry::Role(role) => role_reminder(*role, first_night),
+        }
+    }
+}
+
+impl Display for NightOrderEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NightOrderEntry::Dusk => f.write_str("Dusk"),
+            NightOrderEntry::MinionInfo => f.write_str("Minion Info"),
+            NightOrderEntry::DemonInfo => f.write_str("Demon Info"),
+            NightOrderEntry::Role(role) => write!(f, "{}", role),
+            NightOrderEntry::Dawn => f.write_str("Dawn"),
+        }
+    }
+}
+
+fn role_reminder(role: RoleNames, first_night: bool) -> &'static str {
+    match role {
+        RoleNames::Poisoner => "The Poisoner points to a player. That player is poisoned.",
+        RoleNames::Washerwoman => {
+            "Show the Townsfolk character token. Point to both the TOWNSFOLK and WRONG players."
+        }
+        RoleNames::Librarian => {
+            "Show the Outsider character token. Point to both the OUTSIDER and WRONG players."
+        }
+        RoleNames::Investigator => {
+            "Show the Minion character token. Point to both the MINION and WRONG players."
+        }
+        RoleNames::Chef => {
+            "Show the finger signal (0, 1, 2, ...) for the number of pairs of neighbouring evil players."
+        }
+        RoleNames::Empath => {
+            "Show the finger signal (0, 1, 2) for the number of evil alive neighbours of the Empath."
+        }
+        RoleNames::Fortuneteller => {
+            "The Fortune Teller points to two players. Give the head signal (nod yes, shake no) for whether one of those players is the Demon."
+        }
+        RoleNames::Butler => "The Butler points to a player. Mark that player as MASTER.",
+        RoleNames::Spy => "Show the Grimoire for as long as the Spy needs.",
+        RoleNames::Monk => {
+            "The Monk points to a player. That player is safe from the Demon tonight."
+        }
+        RoleNames::ScarletWoman => {
+            "If the Scarlet Woman became the Demon today, show them the YOU ARE card, then the Demon token."
+        }
+        RoleNames::Imp => {
+            "The Imp points to a player. That player dies. If the Imp chose themselves: Replace the character of 1 alive Minion with a spare Imp token. Show them the YOU ARE card, then the Imp token."
+        }
+        RoleNames::Ravenkeeper => {
+            "If the Ravenkeeper died tonight, the Ravenkeeper points to a player. Show that player's character token."
+        }
+        RoleNames::Undertaker => "If a player was executed today, show their character token.",
+        RoleNames::Gossip => {
+            "If the Gossip's public statement was true, choose a player not protected from dying tonight. That player dies."
+        }
+        RoleNames::Pixie if first_night => "Show the Pixie 1 in-play Townsfolk character token.",
+        RoleNames::Cerenovus => {
+            "The Cerenovus points to a player, then to a character on their sheet. Wake that player. Show the 'This character selected you' card, then the Cerenovus token. Show the selected character token. If the player is not mad about being that character tomorrow, they can be executed."
+        }
+        RoleNames::Harpy => {
+            "The Harpy points to two players. Wake the first player the Harpy pointed to. Show the 'This character selected you' card, then the Harpy token. Point to the second player."
+        }
+        RoleNames::Innkeeper if !first_night => {
+            "The previously protected and drunk players lose those markers. The Innkeeper points to two players. Those players are protected. One is drunk."
+        }
+        _ => "",
+    }
+}
+
+/// The full order of the first night and the other nights for every role on a script, whether
+/// or not it is in play
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct NightOrderSheet {
+    pub(crate) first_night: Vec<NightOrderEntry>,
+    pub(crate) other_nights: Vec<NightOrderEntry>,
+}
+
+/// An entry of the sheet as it stands in a game
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct NightOrderRow {
+    pub(crate) entry: NightOrderEntry,
+    pub(crate) in_play: bool,
+    pub(crate) dead: bool,
+}
+
+impl NightOrderSheet {
+    pub(crate) fn new(script: &Script) -> Self {
+        let build = |first_night: bool| {
+            let mut entries = vec![NightOrderEntry::Dusk, NightOrderEntry::Dawn];
+            if first_night {
+                entries.push(NightOrderEntry::MinionInfo);
+                entries.push(NightOrderEntry::DemonInfo);
+            }
+            entries.extend(script.roles.iter().map(|role| NightOrderEntry::Role(*role)));
+
+            let mut entries: Vec<(usize, NightOrderEntry)> = entries
+                .into_iter()
+                .filter_map(|entry| {
+                    let order = match (entry, first_night) {
+                        (NightOrderEntry::Role(role), true) => script.first_night_order(role),
+                        (NightOrderEntry::Role(role), false) => script.other_night_order(role),
+                        (_, true) => first_night_order(&entry.id()),
+                        (_, false) => other_night_order(&entry.id()),
+                    }?;
+                    Some((order, entry))
+                })
+                .collect();
+
+            entries.sort_by_key(|(order, _)| *order);
+            entries.into_iter().map(|(_, entry)| entry).collect()
+        };
+
+        Self {
+            first_night: build(true),
+            other_nights: build(false),
+        }
+    }
+
+    /// The entries of one of the nights, marking which roles are in the game and which ones only
+    /// belong to dead players
+    pub(crate) fn rows(&self, first_night: bool, state: &State) -> Vec<NightOrderRow> {
+        let entries = if first_night {
+            &self.first_night
+        } else {
+            &self.other_nights
+        };
+
+        entries
+            .iter()
+            .map(|entry| {
+                let NightOrderEntry::Role(role) = entry else {
+                    return NightOrderRow {
+                        entry: *entry,
+                        in_play: true,
+                        dead: false,
+                    };
+                };
+                let players: Vec<bool> = state
+                    .get_players()
+                    .iter()
+                    .filter(|player| player.role.wakes_as() == *role)
+                    .map(|player| player.dead)
+                    .collect();
+
+                NightOrderRow {
+                    entry: *entry,
+                    in_play: !players.is_empty(),
+                    dead: !players.is_empty() && players.iter().all(|dead| *dead),
+                }
+            })
+            .collect()
+    }
+
+    /// A standalone page of both nights that can be printed
+    pub(crate) fn to_html(&self, state: &State) -> String {
+        let table = |first_night: bool| {
+            self.rows(first_night, state)
+                .iter()
+                .map(|row| {
+                    let class = match (row.in_play, row.dead) {
+                        (_, true) => "dead",
+                        (true, false) => "in-play",
+                        (false, false) => "",
+                    };
+                    format!(
+                        "<tr class=\"{}\"><td>{}</td><td>{}</td></tr>",
+                        class,
+                        escape_html(&row.entry.to_string()),
+                        escape_html(row.entry.reminder(first_night))
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+
+        format!(
+            "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Night Order</title>
+<style>
+body {{ font-family: sans-serif; }}
+table {{ border-collapse: collapse; width: 100%; margin-bottom: 2rem; }}
+td {{ border: 1px solid black; padding: 4px; }}
+tr.in-play td:first-child {{ font-weight: bold; }}
+tr.dead {{ color: gray; }}
+</style>
+</head>
+<body>
+<h1>First Night</h1>
+<table>
+{}
+</table>
+<h1>Other Nights</h1>
+<table>
+{}
+</table>
+</body>
+</html>",
+            table(true),
+            table(false)
+        )
+    }
+
+    /// The page from to_html as a data url, so it can be downloaded without a server
+    pub(crate) fn to_data_url(&self, state: &State) -> String {
+        format!(
+            "data:text/html;charset=utf-8,{}",
+            percent_encode(&self.to_html(state))
+        )
+    }
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub(crate) fn percent_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{engine::player::roles::Role, scripts::trouble_brewing};
+
+    use super::*;
+
+    #[test]
+    fn test_night_order_sheet() {
+        let sheet = NightOrderSheet::new(&trouble_brewing());
+
+        assert_eq!(sheet.first_night.first(), Some(&NightOrderEntry::Dusk));
+        assert_eq!(sheet.first_night.last(), Some(&NightOrderEntry::Dawn));
+        let position = |role| {
+            sheet
+                .first_night
+                .iter()
+                .position(|entry| *entry == role)
+                .unwrap()
+        };
+        assert!(position(NightOrderEntry::MinionInfo) < position(NightOrderEntry::DemonInfo));
+        assert!(
+            position(NightOrderEntry::Role(RoleNames::Poisoner))
+                < position(NightOrderEntry::Role(RoleNames::Washerwoman))
+        );
+        assert!(!sheet.other_nights.contains(&NightOrderEntry::MinionInfo));
+    }
+
+    #[test]
+    fn test_night_order_table() {
+        // Every implemented role that wakes needs a place in the table
+        let roles = trouble_brewing().roles;
+        for role in roles {
+            let role_data = role.convert().unwrap();
+            if role_data.wakes_night_one() {
+                assert!(first_night_order(&role.id()).is_some(), "{role} missing");
+            }
+            if role_data.wakes_other_nights() {
+                assert!(other_night_order(&role.id()).is_some(), "{role} missing");
+            }
+        }
+
+        assert_eq!(setup_order("drunk"), 0);
+        assert!(setup_order("washerwoman") < setup_order("fortuneteller"));
+    }
+}
@@ -3,14 +3,21 @@ use std::fmt::{Debug, Display};
 use reactive_stores::Store;
 
 use crate::engine::{
-    change_request::{ChangeArgs, ChangeRequestBuilder, ChangeResult, StateChangeFuncPtr},
-    player::roles::{Role, Roles},
+    change_request::{
+        ChangeRequest, ChangeRequestBuilder, Constraint, Information, StateChangeFuncPtr,
+    },
+    player::{
+        notes::Note,
+        roles::{Role, Roles},
+    },
     state::{
         PlayerIndex, State,
+        log::Event,
         status_effects::{CleanupPhase, StatusEffect},
     },
 };
 
+pub(crate) mod notes;
 pub(crate) mod roles;
 
 #[derive(Clone, Copy, Hash, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -60,8 +67,13 @@ pub(crate) struct Player {
     pub(crate) role: Roles,
     pub(crate) dead: bool,
     pub(crate) ghost_vote: bool,
+    /// The person in this seat left the game. Their seat is treated as if it doesn't exist
+    pub(crate) left: bool,
+    /// The storyteller marked this player as a traveller. Only travellers can be exiled
+    pub(crate) traveller: bool,
     pub(crate) alignment: Alignment,
     pub(crate) status_effects: Vec<StatusEffect>,
+    pub(crate) notes: Vec<Note>,
 }
 
 impl Player {
@@ -71,9 +83,12 @@ impl Player {
             name,
             role: role.clone(),
             ghost_vote: true,
+            left: false,
+            traveller: false,
             dead: false,
             alignment,
             status_effects: vec![],
+            notes: vec![],
         }
     }
 
@@ -154,16 +169,45 @@ impl Player {
     // }
     //
 
+    /// The alignment this player registers as. A droisoned player loses any misregistration
+    /// their role would normally give them
     pub(crate) fn get_alignment(&self) -> Alignment {
+        if self
+            .ability_suppression(PlayerBehaviors::PassiveAbility)
+            .is_some()
+        {
+            return self.role.get_default_alignment();
+        }
         self.role.get_alignment()
     }
 
+    /// The character type this player registers as. A droisoned player loses any
+    /// misregistration their role would normally give them
     pub(crate) fn get_character_type(&self) -> CharacterType {
+        if self
+            .ability_suppression(PlayerBehaviors::PassiveAbility)
+            .is_some()
+        {
+            return self.role.get_true_character_type();
+        }
         self.role.get_character_type()
     }
 
-    pub(crate) fn setup_order(&self) -> Option<usize> {
-        self.role.setup_order()
+    /// Returns the reason this player's ability does not work for the given behavior (for
+    /// example "Poisoned" or "Drunk"), or None if it works as normal
+    pub(crate) fn ability_suppression(&self, behavior: PlayerBehaviors) -> Option<String> {
+        if self.role.ability_malfunctions() {
+            return Some("Drunk".to_string());
+        }
+
+        self.get_statuses()
+            .iter()
+            .find(|se| {
+                se.behavior_types
+                    .as_ref()
+                    .is_some_and(|behaviors| behaviors.contains(&behavior))
+            })
+            .map(|se| se.to_string())
     }
 
     pub(crate) fn setup_ability(
@@ -171,11 +215,14 @@ impl Player {
         player_index: PlayerIndex,
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
-        self.role.setup_ability(player_index, state)
-    }
-
-    pub(crate) fn night_one_order(&self) -> Option<usize> {
-        self.role.night_one_order()
+        let cr = self.role.setup_ability(player_index, state)?;
+        suppressible(
+            player_index,
+            PlayerBehaviors::SetupAbility,
+            cr,
+            self.ability_suppression(PlayerBehaviors::SetupAbility),
+        )
+        .into()
     }
 
     pub(crate) fn night_one_ability(
@@ -184,34 +231,15 @@ impl Player {
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
         let cr = self.role.night_one_ability(player_index, state)?;
-        // Check for poison or drunk effects (for now the only ones that can affect abilities)
-        let status_effect = self.get_statuses().iter().find(|se| {
-            se.behavior_types.as_ref().is_some_and(|behaviors| {
-                behaviors
-                    .iter()
-                    .any(|behavior| matches!(behavior, PlayerBehaviors::NightOneAbility))
-            })
-        });
-
-        if let Some(status_effect) = status_effect {
-            // for cr in res.iter_mut() {
-            //     cr.state_change_func = None;
-            //     cr.description = format!("(*{}*) ", status_effect) + cr.description.as_str();
-            // }
-            // FIX: This needs apply to all the crs in the chain, not just the first one (although
-            // this technically disables the chain so we might need to play around that)
-            let new_cr = drunkify(player_index, cr, status_effect.to_string());
-            return new_cr.into();
-        }
-
-        return cr.into();
+        suppressible(
+            player_index,
+            PlayerBehaviors::NightOneAbility,
+            cr,
+            self.ability_suppression(PlayerBehaviors::NightOneAbility),
+        )
+        .into()
     }
 
-    /// If the role has an ability that acts during the night (not including night one), this method should be overwritten and return
-    /// Some(order) in which the ability acts
-    pub fn night_order(&self) -> Option<usize> {
-        self.role.night_order()
-    }
     /// If the role has an ability that acts during the night (not including night one), this method should be overwritten and resolve the night ability
     pub fn night_ability(
         &self,
@@ -219,26 +247,13 @@ impl Player {
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
         let cr = self.role.night_ability(player_index, state)?;
-        let status_effect = self.get_statuses().iter().find(|se| {
-            se.behavior_types.as_ref().is_some_and(|behaviors| {
-                behaviors
-                    .iter()
-                    .any(|behavior| matches!(behavior, PlayerBehaviors::NightAbility))
-            })
-        });
-
-        if let Some(status_effect) = status_effect {
-            // for cr in res.iter_mut() {
-            //     cr.state_change_func = None;
-            //     cr.description = format!("(*{}*) ", status_effect) + cr.description.as_str();
-            // }
-            // FIX: This needs apply to all the crs in the chain, not just the first one (although
-            // this technically disables the chain so we might need to play around that)
-            let new_cr = drunkify(player_index, cr, status_effect.to_string());
-            return new_cr.into();
-        }
-
-        return cr.into();
+        suppressible(
+            player_index,
+            PlayerBehaviors::NightAbility,
+            cr,
+            self.ability_suppression(PlayerBehaviors::NightAbility),
+        )
+        .into()
     }
 
     /// If the role has an ability that acts during the day (not including night one), this method should be overwritten and indicate which part(s) of the day this ability can be triggered during
@@ -252,30 +267,17 @@ impl Player {
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
         let cr = self.role.day_ability(player_index, state)?;
-        let status_effect = self.get_statuses().iter().find(|se| {
-            se.behavior_types.as_ref().is_some_and(|behaviors| {
-                behaviors
-                    .iter()
-                    .any(|behavior| matches!(behavior, PlayerBehaviors::NightAbility))
-            })
-        });
-
-        if let Some(status_effect) = status_effect {
-            // for cr in res.iter_mut() {
-            //     cr.state_change_func = None;
-            //     cr.description = format!("(*{}*) ", status_effect) + cr.description.as_str();
-            // }
-            // FIX: This needs apply to all the crs in the chain, not just the first one (although
-            // this technically disables the chain so we might need to play around that)
-            let new_cr = drunkify(player_index, cr, status_effect.to_string());
-            return new_cr.into();
-        }
-
-        return cr.into();
+        suppressible(
+            player_index,
+            PlayerBehaviors::DayAbility,
+            cr,
+            self.ability_suppression(PlayerBehaviors::DayAbility),
+        )
+        .into()
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) enum PlayerBehaviors {
     Kill,
     Execute,
@@ -286,6 +288,10 @@ pub(crate) enum PlayerBehaviors {
     NightOneAbility,
     NightAbility,
     DayAbility,
+    /// Abilities that are triggered by event listeners (Virgin, Mayor, Scarlet Woman, ...)
+    EventListener,
+    /// Abilities that are always on (Soldier, Recluse, Spy misregistration, ...)
+    PassiveAbility,
 }
 
 impl PartialEq for Player {
@@ -310,49 +316,121 @@ impl Debug for Player {
     }
 }
 
-fn drunkify(
+/// Wraps a change request created by a player's ability so that whether the ability works is
+/// decided when the request is resolved, not when it was created. If the player is droisoned at
+/// that point, the state change is run on a copy of the state, and only the changes that use up
+/// the player's own ability (role data, event listeners it removed) are kept. Every change
+/// request the ability queues is wrapped the same way, and information it would show is replaced
+/// with false information. Requests queued by listeners are already wrapped for their own player.
+pub(crate) fn suppressible(
     player_index: PlayerIndex,
+    behavior: PlayerBehaviors,
     mut change_request: ChangeRequestBuilder,
-    status_string: String,
+    suppression: Option<String>,
 ) -> ChangeRequestBuilder {
     if let Some(state_change_func) = change_request.state_change_func {
-        let status_string_clone = status_string.clone();
         let wrapper_func = StateChangeFuncPtr::new(move |state, args| {
-            let mut state_copy = state.clone();
-            state_change_func(&mut state_copy, args)?;
-
-            let player_role = state_copy.get_player(player_index).role.clone();
-            state.get_player_mut(player_index).role = player_role;
-
             let state_queue_len = state.change_request_queue.len();
-            if state_copy.change_request_queue.len() > state_queue_len {
-                let mut extra_reqs = state_copy.change_request_queue.split_off(state_queue_len);
-                // WARN: May affect things that don't have to do with the drunk player
-                loop {
-                    let next_cr = match extra_reqs.pop_front() {
-                        Some(cr) => cr,
-                        None => break,
-                    };
-                    let status_string_clone = status_string_clone.clone();
-                    state.change_request_queue.push_back(drunkify(
+
+            match state
+                .get_player(player_index)?
+                .ability_suppression(behavior)
+            {
+                None => state_change_func.call(state, args)?,
+                Some(reason) => {
+                    let mut state_copy = state.clone();
+                    state_change_func.call(&mut state_copy, args)?;
+
+                    state.keep_ability_effects(player_index, &state_copy);
+
+                    // Listeners reacting to what the ability would have done don't get to act
+                    let extra_reqs = state_copy.change_request_queue.split_off(state_queue_len);
+                    state.change_request_queue.extend(
+                        extra_reqs
+                            .into_iter()
+                            .filter(|cr| cr.source_player_index.is_none()),
+                    );
+
+                    state.log_event(Event::AbilityMalfunction {
                         player_index,
-                        next_cr,
-                        status_string_clone,
-                    ));
+                        reason,
+                    });
+                }
+            }
+
+            let follow_ups = state.change_request_queue.split_off(state_queue_len);
+            for next_cr in follow_ups {
+                if next_cr.source_player_index.is_some() {
+                    state.change_request_queue.push_back(next_cr);
+                    continue;
+                }
+
+                let suppression = state
+                    .get_player(player_index)?
+                    .ability_suppression(behavior);
+                let droisoned = suppression.is_some();
+                let mut next_cr = suppressible(player_index, behavior, next_cr, suppression);
+                if droisoned && next_cr.information.is_some() {
+                    next_cr = false_information(player_index, next_cr, state.get_players().len());
                 }
+                state.change_request_queue.push_back(next_cr);
             }
             Ok(())
         });
 
         change_request.state_change_func = Some(wrapper_func);
     }
+    change_request.source_player_index = Some(player_index);
 
-    change_request.description =
-        format!("(*{}*) ", status_string) + change_request.description.as_str();
+    if let Some(status_string) = suppression {
+        change_request.description =
+            format!("(*{}*) ", status_string) + change_request.description.as_str();
+    }
 
     return change_request;
 }
 
+/// Replaces a change request that shows a droisoned player their information with one where the
+/// storyteller picks what to show them instead. The description still has the true answer. The
+/// answer given is logged next to the true one, and shown right after so nothing else in the
+/// chain comes between them
+pub(crate) fn false_information(
+    player_index: PlayerIndex,
+    change_request: ChangeRequestBuilder,
+    player_count: usize,
+) -> ChangeRequestBuilder {
+    let Some((true_info, format)) = change_request.information.clone() else {
+        return change_request;
+    };
+
+    // The false answer is just as much one role as the true one
+    let same_role = matches!(true_info, Information::PlayersAndRole(..));
+    let mut false_info = ChangeRequest::new_builder(
+        true_info.change_type(player_count),
+        format!(
+            "{}. Choose the false answer to give instead",
+            change_request.description
+        ),
+    )
+    .state_change_func(StateChangeFuncPtr::new(move |state, args| {
+        let given_info = true_info.answered_by(&args)?;
+        state.log_event(Event::FalseInformation {
+            player_index,
+            true_info: true_info.to_string(),
+            given_info: given_info.to_string(),
+        });
+        state
+            .change_request_queue
+            .push_front(ChangeRequest::show_information(given_info, format));
+        Ok(())
+    }));
+    if same_role {
+        false_info = false_info.constraint(Constraint::SameRole);
+    }
+    false_info.source_player_index = Some(player_index);
+    return false_info;
+}
+
 // impl Display for Player {
 //     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 //         write!(
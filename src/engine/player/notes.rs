@@ -0,0 +1,97 @@
+use std::fmt::Display;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::engine::state::Step;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum NoteKind {
+    FreeText,
+    ClaimedRole,
+    /// A "You are X" reminder shown to the player
+    YouAre,
+    Suspicion,
+}
+
+impl NoteKind {
+    pub(crate) fn all() -> [NoteKind; 4] {
+        [
+            NoteKind::FreeText,
+            NoteKind::ClaimedRole,
+            NoteKind::YouAre,
+            NoteKind::Suspicion,
+        ]
+    }
+}
+
+impl Display for NoteKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NoteKind::FreeText => f.write_str("Note"),
+            NoteKind::ClaimedRole => f.write_str("Claimed Role"),
+            NoteKind::YouAre => f.write_str("You Are"),
+            NoteKind::Suspicion => f.write_str("Suspicion"),
+        }
+    }
+}
+
+/// A storyteller note about a player, stamped with the point in the game it was written
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Note {
+    pub(crate) text: String,
+    pub(crate) kind: NoteKind,
+    pub(crate) day_num: usize,
+    pub(crate) step: Step,
+}
+
+impl Note {
+    pub(crate) fn new(text: String, kind: NoteKind, day_num: usize, step: Step) -> Self {
+        Self {
+            text,
+            kind,
+            day_num,
+            step,
+        }
+    }
+
+    pub(crate) fn timestamp(&self) -> String {
+        match self.step {
+            Step::Start | Step::Setup => "Setup".to_string(),
+            Step::Day(_) => format!("Day {}", self.day_num),
+            Step::NightOne | Step::Night => format!("Night {}", self.day_num),
+        }
+    }
+
+    /// Case insensitive search through the note's text and kind
+    pub(crate) fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.text.to_lowercase().contains(&query)
+            || self.kind.to_string().to_lowercase().contains(&query)
+    }
+}
+
+impl Display for Note {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}: {}", self.timestamp(), self.kind, self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_note_search() {
+        let note = Note::new(
+            "Told me they were the Chef".to_string(),
+            NoteKind::Suspicion,
+            2,
+            Step::Night,
+        );
+
+        assert_eq!(note.timestamp(), "Night 2");
+        assert!(note.matches("chef"));
+        assert!(note.matches("suspicion"));
+        assert!(!note.matches("imp"));
+    }
+}
@@ -1,19 +1,16 @@
 use enum_dispatch::enum_dispatch;
-use leptos::prelude::StorageAccess;
 use serde_derive::{Deserialize, Serialize};
 use std::fmt::{Debug, Display};
-use std::ops::Deref;
-use std::sync::Arc;
 
-use crate::engine::change_request::{ChangeArgs, ChangeResult};
 use crate::engine::player::roles::demons::Imp;
 use crate::engine::player::roles::minions::{Baron, Poisoner, ScarletWoman, Spy};
 use crate::engine::player::roles::outsiders::{Butler, Drunk, Recluse, Saint};
 use crate::{
     engine::{
         change_request::ChangeRequestBuilder,
+        error::GameError,
         player::{roles::townsfolk::*, *},
-        state::{PlayerIndex, State},
+        state::{PlayerIndex, State, status_effects::StatusType},
     },
     initialization::CharacterTypeCounts,
 };
@@ -26,6 +23,7 @@ pub(crate) enum RoleNames {
     Empath,
     Gossip,
     Innkeeper,
+    Pixie,
     Washerwoman,
     Librarian,
     Chef,
@@ -41,10 +39,13 @@ pub(crate) enum RoleNames {
     Saint,
     Butler,
     Recluse,
+    Mutant,
     Spy,
     Baron,
     ScarletWoman,
     Poisoner,
+    Cerenovus,
+    Harpy,
     Imp,
 }
 
@@ -93,8 +94,8 @@ struct RoleData {
 }
 
 impl Roles {
-    fn new(role_name: &RoleNames) -> Self {
-        match role_name {
+    fn new(role_name: &RoleNames) -> Result<Self, GameError> {
+        Ok(match role_name {
             RoleNames::Investigator => Self::Investigator(Investigator::default()),
             RoleNames::Empath => Self::Empath(Empath::default()),
             // RoleNames::Gossip => todo!(),
@@ -119,11 +120,38 @@ impl Roles {
             RoleNames::ScarletWoman => Self::ScarletWoman(ScarletWoman::default()),
             RoleNames::Poisoner => Self::Poisoner(Poisoner::default()),
             RoleNames::Imp => Self::Imp(Imp::default()),
-            _ => todo!(),
+            _ => return Err(GameError::UnsupportedRole(*role_name)),
+        })
+    }
+
+    /// Takes the ability data (ability used, last killed, ...) from a copy of this role that had
+    /// its ability resolved on a copy of the state. The role itself is never swapped out, so
+    /// a Drunk only has the role they think they are updated
+    pub(crate) fn keep_ability_data(&mut self, updated: Roles) {
+        if let Roles::Drunk(drunk) = self
+            && !matches!(updated, Roles::Drunk(_))
+        {
+            drunk.keep_ability_data(updated);
+            return;
+        }
+
+        if self.to_role_name() == updated.to_role_name() {
+            *self = updated;
+        }
+    }
+
+    /// The role this player is woken as at night. Same as to_role_name, except for the Drunk
+    pub(crate) fn wakes_as(&self) -> RoleNames {
+        if let Roles::Drunk(drunk) = self
+            && let Some(role) = drunk.believed_role()
+        {
+            return role;
         }
+
+        self.to_role_name()
     }
 
-    fn to_role_name(&self) -> RoleNames {
+    pub(crate) fn to_role_name(&self) -> RoleNames {
         match self {
             Roles::Investigator(_) => RoleNames::Investigator,
             Roles::Empath(_) => RoleNames::Empath,
@@ -167,6 +195,7 @@ impl Display for RoleNames {
             RoleNames::Empath => write!(f, "Empath"),
             RoleNames::Gossip => write!(f, "Gossip"),
             RoleNames::Innkeeper => write!(f, "Innkeeper"),
+            RoleNames::Pixie => write!(f, "Pixie"),
             RoleNames::Washerwoman => write!(f, "Washerwoman"),
             RoleNames::Librarian => write!(f, "Librarian"),
             RoleNames::Chef => write!(f, "Chef"),
@@ -182,10 +211,13 @@ impl Display for RoleNames {
             RoleNames::Saint => write!(f, "Saint"),
             RoleNames::Butler => write!(f, "Butler"),
             RoleNames::Recluse => write!(f, "Recluse"),
+            RoleNames::Mutant => write!(f, "Mutant"),
             RoleNames::Spy => write!(f, "Spy"),
             RoleNames::Baron => write!(f, "Baron"),
             RoleNames::ScarletWoman => write!(f, "Scarletwoman"),
             RoleNames::Poisoner => write!(f, "Poisoner"),
+            RoleNames::Cerenovus => write!(f, "Cerenovus"),
+            RoleNames::Harpy => write!(f, "Harpy"),
             RoleNames::Imp => write!(f, "Imp"),
         }
     }
@@ -219,6 +251,12 @@ pub(crate) trait Role: Display + Send + Sync {
         false
     }
 
+    /// Should be overwritten by roles that think they have an ability that doesn't actually work
+    /// (the Drunk). Abilities of these roles are treated the same as a poisoned player's
+    fn ability_malfunctions(&self) -> bool {
+        false
+    }
+
     fn initialize(&self, _player_index: PlayerIndex, _state: &mut State) {}
 
     /// If the role being in the game affects character type counts, overwrite this method. The
@@ -228,14 +266,14 @@ pub(crate) trait Role: Display + Send + Sync {
         None
     }
 
-    // TODO: These has blah blah blah ability may not be necessary
-    // Instead of this implement a function to get the order that night
+    // NOTE: Roles only declare whether they wake. When they wake is decided by the night order
+    // table (see engine::night_order), so that orders can't collide
 
-    /// If the role has an ability that acts during the setup phase, this method should be overwritten and return
-    /// Some(order) in which the ability acts. This is NOT the same as affecting the character
-    /// counts in the game. That is the initialization phase.
-    fn setup_order(&self) -> Option<usize> {
-        None
+    /// If the role has an ability that acts during the setup phase, this method should be
+    /// overwritten to return true. This is NOT the same as affecting the character counts in the
+    /// game. That is the initialization phase.
+    fn wakes_during_setup(&self) -> bool {
+        false
     }
     /// If the role has an ability that acts during the setup phase, this method should be overwritten and
     /// resolve the setup ability. This is NOT the same as affecting the character
@@ -248,10 +286,10 @@ pub(crate) trait Role: Display + Send + Sync {
         None
     }
 
-    /// If the role has an ability that acts during night one, this method should be overwritten and return
-    /// Some(order) in which the ability acts
-    fn night_one_order(&self) -> Option<usize> {
-        None
+    /// If the role has an ability that acts during night one, this method should be overwritten
+    /// to return true
+    fn wakes_night_one(&self) -> bool {
+        false
     }
     /// If the role has an ability that acts during night one, this method should be overwritten and resolve the night 1 ability
     fn night_one_ability(
@@ -262,10 +300,10 @@ pub(crate) trait Role: Display + Send + Sync {
         None
     }
 
-    /// If the role has an ability that acts during the night (not including night one), this method should be overwritten and return
-    /// Some(order) in which the ability acts
-    fn night_order(&self) -> Option<usize> {
-        None
+    /// If the role has an ability that acts during the night (not including night one), this
+    /// method should be overwritten to return true
+    fn wakes_other_nights(&self) -> bool {
+        false
     }
     /// If the role has an ability that acts during the night (not including night one), this method should be overwritten and resolve the night ability
     fn night_ability(
@@ -291,7 +329,13 @@ pub(crate) trait Role: Display + Send + Sync {
 }
 
 impl RoleNames {
-    pub(crate) fn convert(&self) -> Roles {
+    /// The id of the character in official scripts and night order sheets
+    pub(crate) fn id(&self) -> String {
+        // Same as the serde name of the role
+        format!("{:?}", self).to_lowercase()
+    }
+
+    pub(crate) fn convert(&self) -> Result<Roles, GameError> {
         // TODO: Make classes to roles and resolve them here
         Roles::new(self)
     }
@@ -309,6 +353,7 @@ impl RoleNames {
             | RoleNames::Empath
             | RoleNames::Gossip
             | RoleNames::Innkeeper
+            | RoleNames::Pixie
             | RoleNames::Washerwoman
             | RoleNames::Librarian
             | RoleNames::Chef
@@ -320,12 +365,17 @@ impl RoleNames {
             | RoleNames::Mayor
             | RoleNames::Monk
             | RoleNames::Ravenkeeper => CharacterType::Townsfolk,
-            RoleNames::Drunk | RoleNames::Saint | RoleNames::Butler | RoleNames::Recluse => {
-                CharacterType::Outsider
-            }
-            RoleNames::Spy | RoleNames::Baron | RoleNames::ScarletWoman | RoleNames::Poisoner => {
-                CharacterType::Minion
-            }
+            RoleNames::Drunk
+            | RoleNames::Saint
+            | RoleNames::Butler
+            | RoleNames::Recluse
+            | RoleNames::Mutant => CharacterType::Outsider,
+            RoleNames::Spy
+            | RoleNames::Baron
+            | RoleNames::ScarletWoman
+            | RoleNames::Poisoner
+            | RoleNames::Cerenovus
+            | RoleNames::Harpy => CharacterType::Minion,
             RoleNames::Imp => CharacterType::Demon,
         }
     }
@@ -333,6 +383,33 @@ impl RoleNames {
     pub(crate) fn is_win_condition(&self) -> bool {
         matches!(self.get_type(), CharacterType::Demon)
     }
+
+    /// The reminder tokens that come with this role, for the storyteller to place by hand
+    pub(crate) fn reminders(&self) -> Vec<StatusType> {
+        let custom = |text: &str| StatusType::Custom(text.to_string());
+        match *self {
+            RoleNames::Washerwoman => vec![
+                StatusType::WasherwomanTownsfolk,
+                StatusType::WasherwomanWrong,
+            ],
+            RoleNames::Librarian => vec![StatusType::LibrarianOutsider, StatusType::LibrarianWrong],
+            RoleNames::Investigator => vec![
+                StatusType::InvestigatorMinion,
+                StatusType::InvestigatorWrong,
+            ],
+            RoleNames::Fortuneteller => vec![StatusType::FortuneTellerRedHerring],
+            RoleNames::Undertaker => vec![custom("Executed")],
+            RoleNames::Monk => vec![StatusType::DemonProtected],
+            RoleNames::Ravenkeeper => vec![custom("Woken")],
+            RoleNames::Virgin | RoleNames::Slayer => vec![custom("No Ability")],
+            RoleNames::Drunk => vec![StatusType::Drunk],
+            RoleNames::Butler => vec![StatusType::ButlerMaster],
+            RoleNames::Poisoner => vec![StatusType::Poisoned],
+            RoleNames::ScarletWoman => vec![custom("Demon")],
+            RoleNames::Imp => vec![custom("Dead")],
+            _ => vec![],
+        }
+    }
 }
 
 // Role Modules
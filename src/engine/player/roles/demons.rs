@@ -1,10 +1,10 @@
 use crate::ChangeRequest;
-use crate::engine::change_request::{FilterFuncPtr, StateChangeFuncPtr, check_len};
+use crate::engine::change_request::{Constraint, StateChangeFuncPtr, check_len};
 use crate::engine::player::roles::Roles;
 use std::fmt::Display;
 
 use crate::engine::{
-    change_request::{ChangeError, ChangeRequestBuilder, ChangeType},
+    change_request::{ChangeRequestBuilder, ChangeType},
     player::{Alignment, CharacterType, roles::Role},
     state::{PlayerIndex, State},
 };
@@ -24,8 +24,8 @@ impl Role for Imp {
         CharacterType::Demon
     }
 
-    fn night_order(&self) -> Option<usize> {
-        Some(34)
+    fn wakes_other_nights(&self) -> bool {
+        true
     }
 
     fn is_win_condition(&self) -> bool {
@@ -37,7 +37,7 @@ impl Role for Imp {
         player_index: PlayerIndex,
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
-        let dead = state.get_player(player_index).dead;
+        let dead = state.get_player(player_index).ok()?.dead;
         if dead {
             return None;
         }
@@ -58,8 +58,8 @@ impl Role for Imp {
             let target_players = args.extract_player_indicies()?;
             check_len(&target_players, 1)?;
             let target_player_index = target_players[0];
-            state.kill(player_index, target_player_index);
-            if let Roles::Imp(imp_data) = &mut state.get_player_mut(player_index).role {
+            state.kill(player_index, target_player_index)?;
+            if let Roles::Imp(imp_data) = &mut state.get_player_mut(player_index)?.role {
                 imp_data.last_killed = Some(day_num);
             }
             state.change_request_queue.push_back(
@@ -69,14 +69,20 @@ impl Role for Imp {
                         if let Roles::Imp(Imp {
                             last_swapped: Some(day_num),
                             ..
-                        }) = &state.get_player(player_index).role
+                        }) = &state.get_player(player_index)?.role
                             && *day_num == state.day_num
                         {
                             return Ok(());
                         }
 
+                        // Without a living minion there's nobody to pass the demon on to
+                        let minion_alive = state.get_players().iter().any(|player| {
+                            !player.dead
+                                && player.role.get_true_character_type() == CharacterType::Minion
+                        });
                         if target_player_index == player_index
-                            && state.get_player(player_index).dead
+                            && state.get_player(player_index)?.dead
+                            && minion_alive
                         {
                             state
                                 .change_request_queue
@@ -100,27 +106,17 @@ impl Imp {
                 let target_players = args.extract_player_indicies()?;
                 check_len(&target_players, 1)?;
 
-                let target_player_index = target_players[0];
-                let target_player = state.get_player(target_player_index);
-                if target_player.role.get_true_character_type() != CharacterType::Minion {
-                    return Err(ChangeError::InvalidSelectedPlayer {
-                        reason: "Cannot select a non-minion to become the new imp".into(),
-                    });
-                }
                 let target_player_index = target_players[0];
                 let day_num = state.day_num;
-                let mut new_role = state.get_player(player_index).role.clone();
+                let mut new_role = state.get_player(player_index)?.role.clone();
                 if let Roles::Imp(imp_data) = &mut new_role {
                     imp_data.last_swapped = Some(day_num);
                 }
-                let target_player = state.get_player_mut(target_player_index);
-
-                target_player.role = new_role;
+                state.set_role(target_player_index, new_role)?;
                 Ok(())
             }))
-            .filter_func(FilterFuncPtr::new(move |_, player| {
-                player.role.get_true_character_type() == CharacterType::Minion
-            }));
+            .constraint(Constraint::CharacterType(CharacterType::Minion))
+            .constraint(Constraint::Alive);
     }
 }
 
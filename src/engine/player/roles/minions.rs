@@ -1,17 +1,14 @@
 #![allow(unused_variables)]
 use std::fmt::Display;
 
-use leptos::leptos_dom::logging::console_log;
-
 use crate::{
     engine::{
         change_request::{
-            ChangeError, ChangeRequest, ChangeRequestBuilder, ChangeType, StateChangeFuncPtr,
-            check_len,
+            ChangeRequest, ChangeRequestBuilder, ChangeType, StateChangeFuncPtr, check_len,
         },
         player::{
             Alignment, CharacterType,
-            roles::{Role, RoleNames, Roles, demons::Imp},
+            roles::{Role, Roles},
         },
         state::{
             EventListener, PlayerIndex, State, log,
@@ -46,8 +43,8 @@ impl Role for Spy {
         CharacterType::Any
     }
 
-    fn night_one_order(&self) -> Option<usize> {
-        Some(65)
+    fn wakes_night_one(&self) -> bool {
+        true
     }
 
     fn night_one_ability(
@@ -58,8 +55,8 @@ impl Role for Spy {
         self.ability()
     }
 
-    fn night_order(&self) -> Option<usize> {
-        Some(84)
+    fn wakes_other_nights(&self) -> bool {
+        true
     }
 
     fn night_ability(
@@ -67,7 +64,7 @@ impl Role for Spy {
         player_index: PlayerIndex,
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
-        let dead = state.get_player(player_index).dead;
+        let dead = state.get_player(player_index).ok()?.dead;
         if dead {
             return None;
         }
@@ -125,7 +122,7 @@ impl Poisoner {
             let target_players = args.extract_player_indicies()?;
             check_len(&target_players, 1)?;
 
-            let target_player = state.get_player_mut(target_players[0]);
+            let target_player = state.get_player_mut(target_players[0])?;
             let status = StatusEffect::new(
                 StatusType::Poisoned,
                 player_index,
@@ -148,8 +145,8 @@ impl Role for Poisoner {
         CharacterType::Minion
     }
 
-    fn night_one_order(&self) -> Option<usize> {
-        Some(26)
+    fn wakes_night_one(&self) -> bool {
+        true
     }
 
     fn night_one_ability(
@@ -160,8 +157,8 @@ impl Role for Poisoner {
         self.ability(player_index)
     }
 
-    fn night_order(&self) -> Option<usize> {
-        Some(12)
+    fn wakes_other_nights(&self) -> bool {
+        true
     }
 
     fn night_ability(
@@ -169,7 +166,7 @@ impl Role for Poisoner {
         player_index: PlayerIndex,
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
-        let dead = state.get_player(player_index).dead;
+        let dead = state.get_player(player_index).ok()?.dead;
         if dead {
             return None;
         }
@@ -199,7 +196,10 @@ impl Role for ScarletWoman {
         let scarlet_listener = EventListener::new(
             player_index,
             |event_listener_state, state, death_event: log::Death| {
-                let dead_player = state.get_player(death_event.player_index);
+                let Ok(dead_player) = state.get_player(death_event.player_index) else {
+                    return state;
+                };
+                let dead_role = dead_player.role.to_string();
                 if dead_player.role.get_true_character_type() != CharacterType::Demon
                     || state
                         .get_players()
@@ -217,7 +217,7 @@ impl Role for ScarletWoman {
                     ChangeRequest::new_builder(ChangeType::NoStoryteller, String::new())
                         .state_change_func(StateChangeFuncPtr::new(move |state, _| {
                             let day_num = state.day_num;
-                            let dead_player = &mut state.get_player_mut(death_event.player_index);
+                            let dead_player = state.get_player_mut(death_event.player_index)?;
                             if let Roles::Imp(imp_data) = &mut dead_player.role {
                                 imp_data.last_swapped = Some(day_num);
                             }
@@ -225,19 +225,16 @@ impl Role for ScarletWoman {
                             let dead_role = dead_player.role.clone();
                             state.cleanup_event_listeners(source_player_index);
 
-                            let scarlet_player = state.get_player_mut(source_player_index);
-                            scarlet_player.role = dead_role;
+                            state.set_role(source_player_index, dead_role)?;
 
                             Ok(())
                         })),
                 );
-                let dead_player_string =
-                    state.get_player(death_event.player_index).role.to_string();
                 state
                     .change_request_queue
                     .push_back(ChangeRequest::new_builder(
                         ChangeType::Display,
-                        format!("The Scarletwoman becomes the {}", dead_player_string),
+                        format!("The Scarletwoman becomes the {}", dead_role),
                     ));
 
                 state
@@ -247,8 +244,8 @@ impl Role for ScarletWoman {
         state.death_listeners.push(scarlet_listener);
     }
 
-    // fn night_order(&self) -> Option<usize> {
-    //     Some(28)
+    // fn wakes_other_nights(&self) -> bool {
+    //     true
     // }
 
     // fn night_ability(&self, _player_index: PlayerIndex, state: &State) -> ChangeResult {
@@ -1,13 +1,13 @@
 use std::fmt::Display;
 
-use crate::engine::change_request::{ChangeArgs, ChangeError, ChangeRequest, StateChangeFuncPtr};
-use crate::engine::player::roles::Roles;
+use crate::engine::change_request::{ChangeRequest, Constraint, StateChangeFuncPtr};
+use crate::engine::player::roles::{RoleNames, Roles};
 use crate::engine::state::status_effects::CleanupPhase;
 use crate::engine::{
     change_request::{ChangeRequestBuilder, ChangeType, check_len},
     player::{Alignment, CharacterType, roles::Role},
     state::{
-        PlayerIndex, State,
+        EventListener, PlayerIndex, State, log,
         status_effects::{StatusEffect, StatusType},
     },
 };
@@ -27,14 +27,7 @@ impl Butler {
             let target_players = args.extract_player_indicies()?;
             check_len(&target_players, 1)?;
 
-            // Check that the butler is not picking themselves
-            if target_players[0] == player_index {
-                return Err(ChangeError::InvalidSelectedPlayer {
-                    reason: "Butler Should not be able to pick themselves".into(),
-                });
-            }
-
-            let target_player = state.get_player_mut(target_players[0]);
+            let target_player = state.get_player_mut(target_players[0])?;
             let status = StatusEffect::new(
                 StatusType::ButlerMaster,
                 player_index,
@@ -43,6 +36,7 @@ impl Butler {
             target_player.add_status(status);
             Ok(())
         }))
+        .constraint(Constraint::NotSelf(player_index))
         .into()
     }
 }
@@ -56,8 +50,8 @@ impl Role for Butler {
         CharacterType::Outsider
     }
 
-    fn night_one_order(&self) -> Option<usize> {
-        Some(51)
+    fn wakes_night_one(&self) -> bool {
+        true
     }
 
     fn night_one_ability(
@@ -68,8 +62,8 @@ impl Role for Butler {
         self.ability(player_index)
     }
 
-    fn night_order(&self) -> Option<usize> {
-        Some(83)
+    fn wakes_other_nights(&self) -> bool {
+        true
     }
 
     fn night_ability(
@@ -77,7 +71,7 @@ impl Role for Butler {
         player_index: PlayerIndex,
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
-        let dead = state.get_player(player_index).dead;
+        let dead = state.get_player(player_index).ok()?.dead;
         if dead {
             return None;
         }
@@ -97,6 +91,19 @@ pub(crate) struct Drunk {
     role: Option<Box<Roles>>,
 }
 
+impl Drunk {
+    /// The role the Drunk thinks they are, once it has been chosen
+    pub(crate) fn believed_role(&self) -> Option<RoleNames> {
+        self.role.as_ref().map(|role| role.to_role_name())
+    }
+
+    pub(crate) fn keep_ability_data(&mut self, updated: Roles) {
+        if let Some(role) = &mut self.role {
+            role.keep_ability_data(updated);
+        }
+    }
+}
+
 impl Role for Drunk {
     fn get_default_alignment(&self) -> Alignment {
         Alignment::Good
@@ -106,9 +113,12 @@ impl Role for Drunk {
         CharacterType::Outsider
     }
 
-    // TODO: Should change based on what role is assigned
-    fn setup_order(&self) -> Option<usize> {
-        Some(1)
+    fn ability_malfunctions(&self) -> bool {
+        self.role.is_some()
+    }
+
+    fn wakes_during_setup(&self) -> bool {
+        true
     }
 
     fn setup_ability(
@@ -118,8 +128,7 @@ impl Role for Drunk {
     ) -> Option<ChangeRequestBuilder> {
         // If the drunk has a role assigned, call its setup ability instead
         if let Some(role) = &self.role {
-            let res = role.setup_ability(player_index, state);
-            return res.map(|cr| cr.clear_state_change_func());
+            return role.setup_ability(player_index, state);
         };
 
         // Otherwise assign a role to the drunk
@@ -132,30 +141,30 @@ impl Role for Drunk {
 
             check_len(&roles, 1)?;
 
-            if roles[0].get_type() != CharacterType::Townsfolk {
-                return Err(ChangeError::InvalidSelectedRole {
-                    reason: "Drunk has to be a townsfolk role".into(),
-                });
-            }
-
-            let drunk = state.get_player_mut(player_index);
-            drunk.role = Roles::Drunk(Drunk {
-                role: Some(Box::new(roles[0].convert())),
-            });
+            let role = roles[0].convert()?;
+            state.set_role(
+                player_index,
+                Roles::Drunk(Drunk {
+                    role: Some(Box::new(role.clone())),
+                }),
+            )?;
 
-            let drunk = state.get_player(player_index);
-            if let Some(ability) = drunk.setup_ability(player_index, state) {
+            // The chain this is queued in will take care of marking it as drunk
+            if let Some(ability) = role.setup_ability(player_index, state) {
                 state.change_request_queue.push_back(ability);
             }
 
             Ok(())
         }))
+        .constraint(Constraint::RoleType(CharacterType::Townsfolk))
+        .constraint(Constraint::RoleNotInPlay)
         .into()
     }
 
-    fn night_one_order(&self) -> Option<usize> {
-        let role = self.role.clone()?;
-        role.night_one_order()
+    fn wakes_night_one(&self) -> bool {
+        self.role
+            .as_ref()
+            .is_some_and(|role| role.wakes_night_one())
     }
 
     fn night_one_ability(
@@ -164,21 +173,13 @@ impl Role for Drunk {
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
         let role = self.role.clone()?;
-
-        let res = role.night_one_ability(player_index, state);
-        return match res {
-            Some(mut cr) => {
-                cr.state_change_func = None;
-                cr.description = format!("(*Drunk*) {}", cr.description);
-                Some(cr)
-            }
-            None => None,
-        };
+        role.night_one_ability(player_index, state)
     }
 
-    fn night_order(&self) -> Option<usize> {
-        let role = self.role.clone()?;
-        role.night_order()
+    fn wakes_other_nights(&self) -> bool {
+        self.role
+            .as_ref()
+            .is_some_and(|role| role.wakes_other_nights())
     }
 
     fn night_ability(
@@ -187,15 +188,22 @@ impl Role for Drunk {
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
         let role = self.role.clone()?;
+        role.night_ability(player_index, state)
+    }
 
-        let res = role.night_ability(player_index, state);
-        return match res {
-            Some(cr) => Some(
-                cr.clear_state_change_func()
-                    .change_description(|desc| format!("(*Drunk*) {}", desc)),
-            ),
-            None => None,
-        };
+    fn has_day_ability(&self) -> bool {
+        self.role
+            .as_ref()
+            .is_some_and(|role| role.has_day_ability())
+    }
+
+    fn day_ability(
+        &self,
+        player_index: PlayerIndex,
+        state: &State,
+    ) -> Option<ChangeRequestBuilder> {
+        let role = self.role.clone()?;
+        role.day_ability(player_index, state)
     }
 }
 
@@ -245,6 +253,28 @@ impl Role for Saint {
     fn get_true_character_type(&self) -> CharacterType {
         CharacterType::Outsider
     }
+
+    fn initialize(&self, player_index: PlayerIndex, state: &mut State) {
+        let saint_listener = EventListener::new(
+            player_index,
+            |event_listener_state, state, execution_event: log::Execution| {
+                if execution_event.0 != event_listener_state.source_player_index {
+                    return state;
+                }
+
+                state
+                    .change_request_queue
+                    .push_back(ChangeRequest::new_builder(
+                        ChangeType::Display,
+                        "The Saint was executed, Evil wins".into(),
+                    ));
+
+                state
+            },
+        );
+
+        state.execution_listeners.push(saint_listener);
+    }
 }
 
 impl Display for Saint {
@@ -1,19 +1,17 @@
 use std::fmt::Display;
 
-use leptos::leptos_dom::logging::{console_error, console_log};
-
 use crate::engine::{
     change_request::{
-        ChangeError, ChangeRequest, ChangeRequestBuilder, ChangeResult, ChangeType, FilterFuncPtr,
+        ChangeRequest, ChangeRequestBuilder, ChangeType, Constraint, Information,
         StateChangeFuncPtr, check_len,
     },
     player::{
-        Alignment, CharacterType, PlayerBehaviors,
+        Alignment, CharacterType,
         roles::{Role, Roles},
     },
     state::{
-        EventListener, PlayerIndex, State,
-        log::{AttemptedKill, Event, Nomination},
+        DayStep, EventListener, PlayerIndex, State, Step,
+        log::{AttemptedKill, Event, Nomination, PhaseStart},
         status_effects::{CleanupPhase, StatusEffect, StatusType},
     },
 };
@@ -24,9 +22,9 @@ fn washerwoman_librarian_investigator(
     right_status: StatusType,
     wrong_status: StatusType,
 ) -> Option<ChangeRequestBuilder> {
-    let right_status = move || StatusEffect::new(right_status, player_index, None);
+    let right_status = move || StatusEffect::new(right_status.clone(), player_index, None);
 
-    let wrong_status = move || StatusEffect::new(wrong_status, player_index, None);
+    let wrong_status = move || StatusEffect::new(wrong_status.clone(), player_index, None);
 
     return ChangeRequest::new_builder(
         ChangeType::ChoosePlayers(1),
@@ -36,25 +34,26 @@ fn washerwoman_librarian_investigator(
         let target_player_indices = args.extract_player_indicies()?;
         check_len(&target_player_indices, 1)?;
 
-        let target_player = state.get_player_mut(target_player_indices[0]);
+        let target_player = state.get_player_mut(target_player_indices[0])?;
         target_player.add_status(right_status());
 
         state
             .change_request_queue
             .push_back(washerwoman_librarian_investigator_wrong(
                 player_index,
-                right_status,
-                wrong_status,
+                target_player_indices[0],
+                wrong_status.clone(),
             ));
 
         Ok(())
     }))
+    .constraint(Constraint::NotSelf(player_index))
     .into();
 }
 
 fn washerwoman_librarian_investigator_wrong(
     player_index: PlayerIndex,
-    right_status: impl Fn() -> StatusEffect + Send + Sync + 'static,
+    right_player_index: PlayerIndex,
     wrong_status: impl Fn() -> StatusEffect + Send + Sync + 'static,
 ) -> ChangeRequestBuilder {
     return ChangeRequest::new_builder(
@@ -65,31 +64,56 @@ fn washerwoman_librarian_investigator_wrong(
         let target_player_indices = args.extract_player_indicies()?;
         check_len(&target_player_indices, 1)?;
 
-        let target_player_index = target_player_indices[0];
-
-        if target_player_index == player_index {
-            return Err(ChangeError::InvalidSelectedPlayer {
-                reason: "TODO".to_string(),
-            });
-        }
-
-        let target_player = state.get_player(target_player_index);
-        if target_player
-            .get_statuses()
-            .iter()
-            .any(|se| *se == right_status())
-        {
-            return Err(ChangeError::InvalidSelectedPlayer {
-                reason: "TODO".to_string(),
-            });
-        }
-
         // Assign the chosen player the wrong status effect
-        let target_player = state.get_player_mut(target_player_indices[0]);
+        let target_player = state.get_player_mut(target_player_indices[0])?;
         target_player.add_status(wrong_status());
 
         Ok(())
-    }));
+    }))
+    .constraint(Constraint::NotSelf(player_index))
+    .constraint(Constraint::NotChosen(vec![right_player_index]));
+}
+
+/// Shows the player the two players they placed tokens on in setup, and the role of the one
+/// with the right token. The players are in seat order so the order doesn't give it away
+fn washerwoman_librarian_investigator_info(
+    player_index: PlayerIndex,
+    state: &State,
+    right_status: StatusType,
+    wrong_status: StatusType,
+) -> Option<ChangeRequestBuilder> {
+    let token_holder = |status_type: &StatusType| {
+        state.get_players().iter().position(|player| {
+            player.get_statuses().iter().any(|status_effect| {
+                status_effect.source_player_index == player_index
+                    && status_effect.status_type == *status_type
+            })
+        })
+    };
+    let (Some(right_player_index), Some(wrong_player_index)) =
+        (token_holder(&right_status), token_holder(&wrong_status))
+    else {
+        // The storyteller took the tokens off, so only they know what to show
+        let player = state.get_player(player_index).ok()?;
+        return ChangeRequest::new_builder(
+            ChangeType::Display,
+            format!("Show the {} the correct roles", player.role),
+        )
+        .into();
+    };
+    let role = state
+        .get_player(right_player_index)
+        .ok()?
+        .role
+        .to_role_name();
+
+    let mut player_indices = vec![right_player_index, wrong_player_index];
+    player_indices.sort();
+    return ChangeRequest::show_information(
+        Information::PlayersAndRole(player_indices, role),
+        |information| format!("Show them that {}", information),
+    )
+    .into();
 }
 
 #[derive(Default, Debug, Clone)]
@@ -104,8 +128,8 @@ impl Role for Washerwoman {
         CharacterType::Townsfolk
     }
 
-    fn setup_order(&self) -> Option<usize> {
-        Some(45)
+    fn wakes_during_setup(&self) -> bool {
+        true
     }
 
     fn setup_ability(
@@ -121,8 +145,8 @@ impl Role for Washerwoman {
         )
     }
 
-    fn night_one_order(&self) -> Option<usize> {
-        Some(45)
+    fn wakes_night_one(&self) -> bool {
+        true
     }
 
     fn night_one_ability(
@@ -130,12 +154,12 @@ impl Role for Washerwoman {
         player_index: crate::engine::state::PlayerIndex,
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
-        let player = state.get_player(player_index);
-        ChangeRequest::new_builder(
-            ChangeType::Display,
-            format!("Show the {} the correct roles", player.role),
+        washerwoman_librarian_investigator_info(
+            player_index,
+            state,
+            StatusType::WasherwomanTownsfolk,
+            StatusType::WasherwomanWrong,
         )
-        .into()
     }
 }
 
@@ -157,8 +181,8 @@ impl Role for Librarian {
         CharacterType::Townsfolk
     }
 
-    fn setup_order(&self) -> Option<usize> {
-        Some(46)
+    fn wakes_during_setup(&self) -> bool {
+        true
     }
 
     fn setup_ability(
@@ -189,8 +213,8 @@ impl Role for Librarian {
         None
     }
 
-    fn night_one_order(&self) -> Option<usize> {
-        Some(46)
+    fn wakes_night_one(&self) -> bool {
+        true
     }
 
     fn night_one_ability(
@@ -198,8 +222,6 @@ impl Role for Librarian {
         player_index: crate::engine::state::PlayerIndex,
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
-        let player = state.get_player(player_index);
-
         let outsider_count = state
             .get_players()
             .iter()
@@ -211,14 +233,20 @@ impl Role for Librarian {
             })
             .count();
 
-        ChangeRequest::new_builder(ChangeType::Display, {
-            if outsider_count == 0 {
-                "Show the Librarian there are no outsiders in play".to_string()
-            } else {
-                format!("Show the {} the correct roles", player.role)
-            }
-        })
-        .into()
+        if outsider_count == 0 {
+            return ChangeRequest::new_builder(
+                ChangeType::Display,
+                "Show the Librarian there are no outsiders in play".into(),
+            )
+            .into();
+        }
+
+        washerwoman_librarian_investigator_info(
+            player_index,
+            state,
+            StatusType::LibrarianOutsider,
+            StatusType::LibrarianWrong,
+        )
     }
 }
 
@@ -240,8 +268,8 @@ impl Role for Investigator {
         CharacterType::Townsfolk
     }
 
-    fn setup_order(&self) -> Option<usize> {
-        Some(47)
+    fn wakes_during_setup(&self) -> bool {
+        true
     }
 
     fn setup_ability(
@@ -257,8 +285,8 @@ impl Role for Investigator {
         )
     }
 
-    fn night_one_order(&self) -> Option<usize> {
-        Some(47)
+    fn wakes_night_one(&self) -> bool {
+        true
     }
 
     fn night_one_ability(
@@ -266,13 +294,12 @@ impl Role for Investigator {
         player_index: crate::engine::state::PlayerIndex,
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
-        let player = state.get_player(player_index);
-
-        ChangeRequest::new_builder(
-            ChangeType::Display,
-            format!("Show the {} the correct roles", player.role),
+        washerwoman_librarian_investigator_info(
+            player_index,
+            state,
+            StatusType::InvestigatorMinion,
+            StatusType::InvestigatorWrong,
         )
-        .into()
     }
 }
 
@@ -294,8 +321,8 @@ impl Role for Chef {
         CharacterType::Townsfolk
     }
 
-    fn night_one_order(&self) -> Option<usize> {
-        Some(48)
+    fn wakes_night_one(&self) -> bool {
+        true
     }
 
     fn night_one_ability(
@@ -312,18 +339,19 @@ impl Role for Chef {
             .iter()
             .enumerate()
             .filter(|(pi, player)| {
-                let right_player = state.get_player(state.right_player(*pi));
-                player.alignment == Alignment::Evil && right_player.alignment == Alignment::Evil
+                player.alignment == Alignment::Evil
+                    && state
+                        .get_player(state.right_player(*pi))
+                        .is_ok_and(|right_player| right_player.alignment == Alignment::Evil)
             })
             .count();
 
-        ChangeRequest::new_builder(
-            ChangeType::Display,
+        ChangeRequest::show_information(Information::Number(pair_count), |pair_count| {
             format!(
                 "Show the chef that there are {} pairs of evil players",
                 pair_count
-            ),
-        )
+            )
+        })
         .into()
     }
 }
@@ -341,19 +369,18 @@ impl Empath {
     fn ability(&self, player_index: PlayerIndex, state: &State) -> Option<ChangeRequestBuilder> {
         // Check how many players next to the empath are evil
         let mut count = 0;
-        let left_player = state.get_player(state.left_player(player_index));
+        let left_player = state.get_player(state.left_player(player_index)).ok()?;
         if left_player.alignment == Alignment::Evil {
             count += 1;
         }
-        let right_player = state.get_player(state.right_player(player_index));
+        let right_player = state.get_player(state.right_player(player_index)).ok()?;
         if right_player.alignment == Alignment::Evil {
             count += 1;
         }
 
-        ChangeRequest::new_builder(
-            ChangeType::Display,
-            format!("Empath has {} evil neighbors", count),
-        )
+        ChangeRequest::show_information(Information::Number(count), |count| {
+            format!("Empath has {} evil neighbors", count)
+        })
         .into()
     }
 }
@@ -367,12 +394,12 @@ impl Role for Empath {
         CharacterType::Townsfolk
     }
 
-    fn night_one_order(&self) -> Option<usize> {
-        Some(49)
+    fn wakes_night_one(&self) -> bool {
+        true
     }
 
-    fn night_order(&self) -> Option<usize> {
-        Some(68)
+    fn wakes_other_nights(&self) -> bool {
+        true
     }
 
     fn night_one_ability(
@@ -388,7 +415,7 @@ impl Role for Empath {
         player_index: PlayerIndex,
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
-        let dead = state.get_player(player_index).dead;
+        let dead = state.get_player(player_index).ok()?.dead;
         if dead {
             return None;
         }
@@ -407,7 +434,7 @@ pub(crate) struct Fortuneteller();
 
 impl Fortuneteller {
     fn ability(&self, player_index: PlayerIndex, state: &State) -> Option<ChangeRequestBuilder> {
-        let dead = state.get_player(player_index).dead;
+        let dead = state.get_player(player_index).ok()?.dead;
         if dead {
             return None;
         }
@@ -421,38 +448,37 @@ impl Fortuneteller {
 
             check_len(&target_player_indicies, 2)?;
 
-            // Make sure there are no duplicate players
-            if target_player_indicies[0] == target_player_indicies[1] {
-                return Err(ChangeError::InvalidSelectedPlayer {
-                    reason: "Please select unique players".into(),
-                });
-            }
-
             // Calculate whether any of the chosen players are either a red herring or a demon
             let demon_found = target_player_indicies.iter().any(|i| {
-                let player = state.get_player(*i);
+                let Ok(player) = state.get_player(*i) else {
+                    return false;
+                };
                 matches!(
                     player.get_character_type(),
                     CharacterType::Demon | CharacterType::Any
                 ) || player.get_statuses().iter().any(|se| {
                     se.source_player_index == player_index
+                        && se.affects_behaviour()
                         && matches!(se.status_type, StatusType::FortuneTellerRedHerring)
                 })
             });
             state
                 .change_request_queue
-                .push_back(ChangeRequest::new_builder(
-                    ChangeType::Display,
-                    format!(
-                        "Show the Fortuneteller a {}",
-                        match demon_found {
-                            true => "Thumbs Up",
-                            false => "Thumbs Down",
-                        }
-                    ),
+                .push_back(ChangeRequest::show_information(
+                    Information::YesNo(demon_found),
+                    |demon_found| {
+                        format!(
+                            "Show the Fortuneteller a {}",
+                            match demon_found {
+                                Information::YesNo(true) => "Thumbs Up",
+                                _ => "Thumbs Down",
+                            }
+                        )
+                    },
                 ));
             Ok(())
         }))
+        .constraint(Constraint::Distinct)
         .into()
     }
 }
@@ -466,8 +492,8 @@ impl Role for Fortuneteller {
         CharacterType::Townsfolk
     }
 
-    fn setup_order(&self) -> Option<usize> {
-        Some(50)
+    fn wakes_during_setup(&self) -> bool {
+        true
     }
 
     fn setup_ability(
@@ -486,24 +512,19 @@ impl Role for Fortuneteller {
 
             check_len(&target_player_indices, 1)?;
 
-            if target_player_indices[0] == player_index {
-                return Err(ChangeError::InvalidSelectedPlayer {
-                    reason: "Cannot select the fortune teller as their own red-herring".into(),
-                });
-            }
-
             let target_player_index = target_player_indices[0];
-            let target_player = state.get_player_mut(target_player_index);
+            let target_player = state.get_player_mut(target_player_index)?;
             let status = StatusEffect::new(StatusType::FortuneTellerRedHerring, player_index, None);
             target_player.add_status(status);
 
             Ok(())
         }))
+        .constraint(Constraint::NotSelf(player_index))
         .into()
     }
 
-    fn night_one_order(&self) -> Option<usize> {
-        Some(50)
+    fn wakes_night_one(&self) -> bool {
+        true
     }
 
     fn night_one_ability(
@@ -514,8 +535,8 @@ impl Role for Fortuneteller {
         self.ability(player_index, state)
     }
 
-    fn night_order(&self) -> Option<usize> {
-        Some(69)
+    fn wakes_other_nights(&self) -> bool {
+        true
     }
 
     fn night_ability(
@@ -545,8 +566,8 @@ impl Role for Undertaker {
         CharacterType::Townsfolk
     }
 
-    fn night_order(&self) -> Option<usize> {
-        Some(70)
+    fn wakes_other_nights(&self) -> bool {
+        true
     }
 
     fn night_ability(
@@ -554,13 +575,13 @@ impl Role for Undertaker {
         player_index: PlayerIndex,
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
-        let dead = state.get_player(player_index).dead;
+        let dead = state.get_player(player_index).ok()?.dead;
 
         if dead {
             return None;
         }
 
-        let execution_event = state.log.search_previous_phase(|e| match *e {
+        let execution_event = state.log.search_previous_day(|e| match *e {
             Event::Execution(_) => Some(e),
             _ => None,
         });
@@ -570,15 +591,18 @@ impl Role for Undertaker {
             Ok(_) | Err(_) => return None,
         };
 
-        let executed_role = state.get_player(executed_player_index).role.clone();
+        let executed_role = state
+            .get_player(executed_player_index)
+            .ok()?
+            .role
+            .to_role_name();
 
-        ChangeRequest::new_builder(
-            ChangeType::Display,
+        ChangeRequest::show_information(Information::Roles(vec![executed_role]), |role| {
             format!(
                 "Show the undertaker that the {} was executed yesterday",
-                executed_role
-            ),
-        )
+                role
+            )
+        })
         .into()
     }
 }
@@ -601,8 +625,8 @@ impl Role for Monk {
         CharacterType::Townsfolk
     }
 
-    fn night_order(&self) -> Option<usize> {
-        Some(19)
+    fn wakes_other_nights(&self) -> bool {
+        true
     }
 
     fn night_ability(
@@ -610,7 +634,7 @@ impl Role for Monk {
         player_index: PlayerIndex,
         state: &State,
     ) -> Option<ChangeRequestBuilder> {
-        let dead = state.get_player(player_index).dead;
+        let dead = state.get_player(player_index).ok()?.dead;
         if dead {
             return None;
         }
@@ -625,14 +649,7 @@ impl Role for Monk {
 
             check_len(&target_player_indices, 1)?;
 
-            // Make sure the monk can't protect themselves
-            if target_player_indices[0] == player_index {
-                return Err(ChangeError::InvalidSelectedPlayer {
-                    reason: "Monk cannot protect themselves".into(),
-                });
-            }
-
-            let target_player = state.get_player_mut(target_player_indices[0]);
+            let target_player = state.get_player_mut(target_player_indices[0])?;
             let status = StatusEffect::new(
                 StatusType::DemonProtected,
                 player_index,
@@ -642,7 +659,7 @@ impl Role for Monk {
 
             Ok(())
         }))
-        .filter_func(FilterFuncPtr::new(move |pi, _| pi != player_index))
+        .constraint(Constraint::NotSelf(player_index))
         .into()
     }
 }
@@ -667,11 +684,9 @@ impl Role for Ravenkeeper {
         CharacterType::Townsfolk
     }
 
-    fn night_order(&self) -> Option<usize> {
-        // if player == dead and ability not used, then order
-        // Otherwise no order
-        // Or might be easier to do in ability
-        Some(67)
+    fn wakes_other_nights(&self) -> bool {
+        // Only actually woken if they died tonight, which is checked in the ability
+        true
     }
 
     fn night_ability(
@@ -702,20 +717,20 @@ impl Role for Ravenkeeper {
             let target_player_indices = args.extract_player_indicies()?;
             check_len(&target_player_indices, 1)?;
 
-            state.get_player_mut(player_index).role =
+            state.get_player_mut(player_index)?.role =
                 Roles::Ravenkeeper(Ravenkeeper { ability_used: true });
 
-            let target_player = state.get_player(target_player_indices[0]);
+            let target_role = state
+                .get_player(target_player_indices[0])?
+                .role
+                .to_role_name();
 
             // Create a new change request using the role of the target player
             state
                 .change_request_queue
-                .push_back(ChangeRequest::new_builder(
-                    ChangeType::Display,
-                    format!(
-                        "Show the Ravenkeeper that they selected the {}",
-                        target_player.role
-                    ),
+                .push_back(ChangeRequest::show_information(
+                    Information::Roles(vec![target_role]),
+                    |role| format!("Show the Ravenkeeper that they selected the {}", role),
                 ));
 
             Ok(())
@@ -755,12 +770,11 @@ impl Role for Virgin {
                 state.change_request_queue.push_back(
                     ChangeRequest::new_builder(ChangeType::NoStoryteller, String::new())
                         .state_change_func(StateChangeFuncPtr::new(move |state, _| {
-                            // FIX: Doesn't account for drunkness or poisoned (bad account for drunkness)
                             let nominator =
-                                state.get_player_mut(nomination_event.nominator_player_index);
+                                state.get_player_mut(nomination_event.nominator_player_index)?;
                             if nominator.role.get_true_character_type() == CharacterType::Townsfolk
                             {
-                                state.execute_player(nomination_event.nominator_player_index);
+                                state.execute_player(nomination_event.nominator_player_index)?;
                             }
                             state.cleanup_event_listeners(source_player_index);
                             Ok(())
@@ -819,13 +833,13 @@ impl Role for Slayer {
             let target_player_indices = args.extract_player_indicies()?;
             check_len(&target_player_indices, 1)?;
 
-            let slayer = state.get_player_mut(player_index);
+            let slayer = state.get_player_mut(player_index)?;
             slayer.role = Roles::Slayer(Self { ability_used: true });
 
-            let target_player = state.get_player_mut(target_player_indices[0]);
+            let target_player = state.get_player_mut(target_player_indices[0])?;
 
             if target_player.get_character_type() == CharacterType::Demon {
-                state.kill(player_index, target_player_indices[0]);
+                state.kill(player_index, target_player_indices[0])?;
             }
 
             Ok(())
@@ -853,13 +867,13 @@ impl Role for Soldier {
     }
 
     fn initialize(&self, player_index: PlayerIndex, state: &mut State) {
-        state
-            .get_player_mut(player_index)
-            .add_status(StatusEffect::new(
+        if let Ok(player) = state.get_player_mut(player_index) {
+            player.add_status(StatusEffect::new(
                 StatusType::DemonProtected,
                 player_index,
                 None,
             ));
+        }
     }
 
     // Overwrite kill method for Soldier so they can't be killed by a demon
@@ -901,7 +915,6 @@ impl Role for Mayor {
         let mayor_listener = EventListener::new(
             player_index,
             move |event_listener_state, state, attempted_kill_event: AttemptedKill| {
-                console_error("I was called");
                 if attempted_kill_event.target_player_index
                     != event_listener_state.source_player_index
                 {
@@ -924,7 +937,7 @@ impl Role for Mayor {
 
                             // Stop infinite loop of mayor bouncing kills
                             if target_player_index == player_index {
-                                state.get_player_mut(player_index).dead = true;
+                                state.get_player_mut(player_index)?.dead = true;
                                 state.handle_death(player_index);
                                 return Ok(());
                             }
@@ -932,7 +945,7 @@ impl Role for Mayor {
                             state.kill(
                                 attempted_kill_event.attacking_player_index,
                                 target_player_index,
-                            );
+                            )?;
 
                             Ok(())
                         },
@@ -943,6 +956,34 @@ impl Role for Mayor {
         );
 
         state.attempted_kill_listeners.push(mayor_listener);
+
+        // If only 3 players live & no execution occurs, your team wins
+        let mayor_win_listener =
+            EventListener::new(player_index, move |_, state, phase_start: PhaseStart| {
+                if phase_start.0 != Step::Day(DayStep::Dusk) || state.living_player_count() != 3 {
+                    return state;
+                }
+
+                let executed_today = state
+                    .log
+                    .search_previous_day(|event| match event {
+                        Event::Execution(_) => Some(event),
+                        _ => None,
+                    })
+                    .is_ok();
+                if executed_today {
+                    return state;
+                }
+
+                state
+                    .change_request_queue
+                    .push_back(ChangeRequest::new_builder(
+                        ChangeType::Display,
+                        "Only 3 players are alive and nobody was executed. Good wins".into(),
+                    ));
+                state
+            });
+        state.phase_listeners.push(mayor_win_listener);
     }
 }
 
@@ -954,14 +995,17 @@ impl Display for Mayor {
 
 #[cfg(test)]
 mod test {
-    use crate::{engine::player::roles::RoleNames, scripts::trouble_brewing};
+    use crate::{
+        engine::{change_request::ChangeArgs, player::roles::RoleNames, state::GameMode},
+        scripts::trouble_brewing,
+    };
 
     use super::*;
 
     fn setup_test_state(roles: Vec<RoleNames>) -> State {
         let player_names = roles
             .iter()
-            .map(|role| role.convert().to_string())
+            .map(|role| role.convert().unwrap().to_string())
             .collect();
         State::new(roles, player_names, trouble_brewing()).unwrap()
     }
@@ -976,7 +1020,7 @@ mod test {
             RoleNames::Imp,
         ];
         let mut state = setup_test_state(roles);
-        let undertaker_role = Roles::new(&RoleNames::Undertaker);
+        let undertaker_role = Roles::new(&RoleNames::Undertaker).unwrap();
         let undertaker_index = state
             .get_players()
             .iter()
@@ -992,6 +1036,215 @@ mod test {
         state.next_step();
         state.next_step();
 
-        todo!()
+        let spy_index = state
+            .get_players()
+            .iter()
+            .position(|player| player.role.to_string() == "Spy")
+            .expect("Spy not found");
+        state.execute_player(spy_index).unwrap();
+
+        let cr = undertaker_role
+            .night_ability(undertaker_index, &state)
+            .expect("Undertaker should learn who was executed");
+        assert_eq!(
+            cr.description,
+            "Show the undertaker that the Spy was executed yesterday"
+        );
+    }
+
+    #[test]
+    fn test_poisoned_slayer() {
+        let roles = vec![
+            RoleNames::Slayer,
+            RoleNames::Virgin,
+            RoleNames::Soldier,
+            RoleNames::Poisoner,
+            RoleNames::Imp,
+        ];
+        let mut state = setup_test_state(roles);
+        state.next_step();
+        let find = |state: &State, role: &str| {
+            state
+                .get_players()
+                .iter()
+                .position(|player| player.role.to_string() == role)
+                .unwrap()
+        };
+        let slayer_index = find(&state, "Slayer");
+        let imp_index = find(&state, "Imp");
+        let poisoner_index = find(&state, "Poisoner");
+        state
+            .get_player_mut(slayer_index)
+            .unwrap()
+            .add_status(StatusEffect::new(
+                StatusType::Poisoned,
+                poisoner_index,
+                CleanupPhase::Dusk.into(),
+            ));
+
+        let cr = state
+            .day_ability(slayer_index)
+            .expect("Slayer should still be able to use their ability");
+        assert!(cr.description.starts_with("(*Poisoned*)"));
+        cr.state_change_func
+            .unwrap()
+            .call(&mut state, ChangeArgs::PlayerIndices(vec![imp_index]))
+            .unwrap();
+
+        assert!(!state.get_player(imp_index).unwrap().dead);
+        assert!(!state.get_player(slayer_index).unwrap().has_day_ability());
+        assert!(
+            state
+                .log
+                .search_current_phase(|event| match event {
+                    Event::AbilityMalfunction { player_index, .. }
+                        if *player_index == slayer_index =>
+                        Some(event),
+                    _ => None,
+                })
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_manual_reminders() {
+        let roles = vec![
+            RoleNames::Slayer,
+            RoleNames::Virgin,
+            RoleNames::Soldier,
+            RoleNames::Poisoner,
+            RoleNames::Imp,
+        ];
+        let mut state = setup_test_state(roles);
+        state.next_step();
+        let slayer_index = state
+            .get_players()
+            .iter()
+            .position(|player| player.role.to_string() == "Slayer")
+            .unwrap();
+
+        // A reminder that is only for the storyteller doesn't change the ability
+        state
+            .add_reminder(
+                slayer_index,
+                StatusType::Poisoned,
+                Some(RoleNames::Poisoner),
+                None,
+                false,
+            )
+            .unwrap();
+        let cr = state.day_ability(slayer_index).unwrap();
+        assert!(!cr.description.starts_with("(*Poisoned*)"));
+
+        state.remove_reminder(slayer_index, 0).unwrap();
+        assert!(
+            state
+                .get_player(slayer_index)
+                .unwrap()
+                .status_effects
+                .is_empty()
+        );
+
+        state
+            .add_reminder(
+                slayer_index,
+                StatusType::Poisoned,
+                Some(RoleNames::Poisoner),
+                Some(CleanupPhase::Dusk),
+                true,
+            )
+            .unwrap();
+        let cr = state.day_ability(slayer_index).unwrap();
+        assert!(cr.description.starts_with("(*Poisoned*)"));
+    }
+
+    #[test]
+    fn test_manual_mode_changes() {
+        let roles = vec![
+            RoleNames::Virgin,
+            RoleNames::Soldier,
+            RoleNames::Chef,
+            RoleNames::Poisoner,
+            RoleNames::Imp,
+        ];
+        let mut state = setup_test_state(roles);
+        state.next_step();
+        state.set_mode(GameMode::Manual);
+        let soldier_index = state
+            .get_players()
+            .iter()
+            .position(|player| player.role.to_string() == "Soldier")
+            .unwrap();
+
+        state.change_role(soldier_index, RoleNames::Slayer).unwrap();
+        assert!(state.get_player(soldier_index).unwrap().has_day_ability());
+
+        state.set_alignment(soldier_index, Alignment::Evil).unwrap();
+        assert_eq!(
+            state.get_player(soldier_index).unwrap().alignment,
+            Alignment::Evil
+        );
+
+        state.set_dead(soldier_index, true).unwrap();
+        assert!(state.get_player(soldier_index).unwrap().dead);
+        state.set_dead(soldier_index, false).unwrap();
+        assert!(!state.get_player(soldier_index).unwrap().dead);
+
+        let storyteller_events = state
+            .log
+            .day_phases
+            .last()
+            .unwrap()
+            .log
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    Event::ModeChanged(_)
+                        | Event::RoleChanged { .. }
+                        | Event::AlignmentChanged { .. }
+                        | Event::Death(_)
+                        | Event::Revived(_)
+                )
+            })
+            .count();
+        assert_eq!(storyteller_events, 5);
+    }
+
+    #[test]
+    fn test_mayor_win() {
+        let roles = vec![
+            RoleNames::Mayor,
+            RoleNames::Virgin,
+            RoleNames::Soldier,
+            RoleNames::Spy,
+            RoleNames::Imp,
+        ];
+        let mut state = setup_test_state(roles);
+        let index_of = |state: &State, role: &str| {
+            state
+                .get_players()
+                .iter()
+                .position(|player| player.role.to_string() == role)
+                .unwrap()
+        };
+        let virgin_index = index_of(&state, "Virgin");
+        let soldier_index = index_of(&state, "Soldier");
+        state.set_dead(virgin_index, true).unwrap();
+        state.set_dead(soldier_index, true).unwrap();
+
+        while !matches!(state.step, Step::Day(_)) {
+            state.next_step();
+        }
+        state.skip_to_day_step(DayStep::Execution).unwrap();
+        assert!(state.change_request_queue.is_empty());
+
+        state.next_step();
+        assert_eq!(state.step, Step::Day(DayStep::Dusk));
+        let cr = state
+            .change_request_queue
+            .pop_front()
+            .expect("The Mayor's team should win at dusk");
+        assert_eq!(cr.change_type, ChangeType::Display);
     }
 }
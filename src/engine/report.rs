@@ -0,0 +1,324 @@
+use std::fmt::Display;
+
+use serde_derive::Serialize;
+
+use crate::engine::{
+    night_order::percent_encode,
+    player::{Player, notes::Note, roles::RoleNames},
+    state::{PlayerIndex, State, log::Event, status_effects::StatusType},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ReportFormat {
+    Markdown,
+    PlainText,
+    Json,
+}
+
+impl ReportFormat {
+    pub(crate) fn all() -> [ReportFormat; 3] {
+        [
+            ReportFormat::Markdown,
+            ReportFormat::PlainText,
+            ReportFormat::Json,
+        ]
+    }
+
+    pub(crate) fn file_name(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "game_report.md",
+            ReportFormat::PlainText => "game_report.txt",
+            ReportFormat::Json => "game_report.json",
+        }
+    }
+
+    fn mime_type(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "text/markdown",
+            ReportFormat::PlainText => "text/plain",
+            ReportFormat::Json => "application/json",
+        }
+    }
+}
+
+impl Display for ReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            ReportFormat::Markdown => "Markdown",
+            ReportFormat::PlainText => "Plain Text",
+            ReportFormat::Json => "JSON",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct ReportPlayer {
+    pub(crate) name: String,
+    pub(crate) starting_role: String,
+    pub(crate) final_role: String,
+    pub(crate) alignment: String,
+    pub(crate) survived: bool,
+    pub(crate) claims: Option<String>,
+    /// The storyteller's notes about the player, so they're kept with the saved report
+    pub(crate) notes: Vec<Note>,
+}
+
+/// Something that happened, and when
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct ReportEntry {
+    pub(crate) phase: String,
+    pub(crate) text: String,
+}
+
+/// Recap of a finished game, built from the log and the final state
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct GameReport {
+    pub(crate) winner: Option<String>,
+    /// Everyone in seat order, with the character they started with
+    pub(crate) players: Vec<ReportPlayer>,
+    pub(crate) drunk_and_poisoned: Vec<ReportEntry>,
+    pub(crate) info: Vec<ReportEntry>,
+    pub(crate) nominations: Vec<ReportEntry>,
+    pub(crate) executions: Vec<ReportEntry>,
+    pub(crate) deaths: Vec<ReportEntry>,
+}
+
+impl GameReport {
+    pub(crate) fn new(state: &State) -> Self {
+        let players = state
+            .get_players()
+            .iter()
+            .enumerate()
+            .map(|(player_index, player)| ReportPlayer {
+                name: player.name.clone(),
+                starting_role: starting_role(state, player_index, player).to_string(),
+                final_role: player.role.to_string(),
+                alignment: player.alignment.to_string(),
+                survived: !player.dead,
+                claims: state.claim_summary(player_index),
+                notes: player.notes.clone(),
+            })
+            .collect();
+
+        let mut report = Self {
+            winner: state.winner.map(|winner| winner.to_string()),
+            players,
+            drunk_and_poisoned: vec![],
+            info: vec![],
+            nominations: vec![],
+            executions: vec![],
+            deaths: vec![],
+        };
+
+        for day_phase in state.log.day_phases.iter() {
+            for event in day_phase.log.iter() {
+                let section = match event {
+                    Event::StatusApplied { status_effect, .. }
+                    | Event::ReminderAdded { status_effect, .. }
+                        if matches!(
+                            status_effect.status_type,
+                            StatusType::Drunk | StatusType::Poisoned
+                        ) =>
+                    {
+                        &mut report.drunk_and_poisoned
+                    }
+                    Event::InfoLearned { .. } => &mut report.info,
+                    Event::Nomination { .. } | Event::Voting { .. } => &mut report.nominations,
+                    Event::Execution(_) => &mut report.executions,
+                    Event::Death(_) => &mut report.deaths,
+                    _ => continue,
+                };
+                section.push(ReportEntry {
+                    phase: day_phase.title(),
+                    text: state.describe_event(event.clone()),
+                });
+            }
+        }
+
+        return report;
+    }
+
+    fn sections(&self) -> [(&'static str, &Vec<ReportEntry>); 5] {
+        [
+            ("Drunk & Poisoned", &self.drunk_and_poisoned),
+            ("Info Given", &self.info),
+            ("Nominations & Votes", &self.nominations),
+            ("Executions", &self.executions),
+            ("Deaths", &self.deaths),
+        ]
+    }
+
+    fn winner_line(&self) -> String {
+        match &self.winner {
+            Some(winner) => format!("{} wins", winner),
+            None => "No winner was declared".to_string(),
+        }
+    }
+
+    fn player_line(player: &ReportPlayer) -> String {
+        let mut line = format!(
+            "{}: {} ({})",
+            player.name, player.starting_role, player.alignment
+        );
+        if player.final_role != player.starting_role {
+            line += &format!(", ended as {}", player.final_role);
+        }
+        if !player.survived {
+            line += ", dead";
+        }
+        if let Some(claims) = &player.claims {
+            line += &format!(", {}", claims);
+        }
+        return line;
+    }
+
+    pub(crate) fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Game Report\n\n**{}**\n\n## Setup\n\n",
+            self.winner_line()
+        );
+        for player in self.players.iter() {
+            out += &format!("- {}\n", Self::player_line(player));
+            for note in player.notes.iter() {
+                out += &format!("  - {}\n", note);
+            }
+        }
+        for (title, entries) in self.sections() {
+            out += &format!("\n## {}\n\n", title);
+            if entries.is_empty() {
+                out += "_None_\n";
+            }
+            for entry in entries {
+                out += &format!("- **{}**: {}\n", entry.phase, entry.text);
+            }
+        }
+        return out;
+    }
+
+    pub(crate) fn to_plain_text(&self) -> String {
+        let mut out = format!("GAME REPORT\n{}\n\nSETUP\n", self.winner_line());
+        for player in self.players.iter() {
+            out += &format!("  {}\n", Self::player_line(player));
+            for note in player.notes.iter() {
+                out += &format!("    {}\n", note);
+            }
+        }
+        for (title, entries) in self.sections() {
+            out += &format!("\n{}\n", title.to_uppercase());
+            if entries.is_empty() {
+                out += "  None\n";
+            }
+            for entry in entries {
+                out += &format!("  {}: {}\n", entry.phase, entry.text);
+            }
+        }
+        return out;
+    }
+
+    pub(crate) fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    pub(crate) fn export(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::PlainText => self.to_plain_text(),
+            ReportFormat::Json => self.to_json(),
+        }
+    }
+
+    pub(crate) fn to_data_url(&self, format: ReportFormat) -> String {
+        format!(
+            "data:{};charset=utf-8,{}",
+            format.mime_type(),
+            percent_encode(&self.export(format))
+        )
+    }
+}
+
+/// The role a player had before anything changed it
+fn starting_role(state: &State, player_index: PlayerIndex, player: &Player) -> RoleNames {
+    let first_change = state
+        .log
+        .day_phases
+        .iter()
+        .flat_map(|day_phase| day_phase.log.iter())
+        .find_map(|event| match event {
+            Event::RoleChanged {
+                player_index: changed_index,
+                old_role,
+                ..
+            } if *changed_index == player_index => Some(*old_role),
+            _ => None,
+        });
+    return first_change.unwrap_or(player.role.to_role_name());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{
+        player::{Alignment, notes::NoteKind},
+        scenario::Scenario,
+        state::{DayStep, Step, log::Event},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_game_report() {
+        let mut scenario = Scenario::new(&[
+            ("Alice", RoleNames::Chef),
+            ("Bob", RoleNames::Virgin),
+            ("Carol", RoleNames::Soldier),
+            ("Dan", RoleNames::Spy),
+            ("Eve", RoleNames::Imp),
+        ]);
+        let soldier_index = scenario.index("Carol");
+
+        scenario.advance_to(Step::Day(DayStep::Dawn));
+        let state = &mut scenario.state;
+        state.change_role(soldier_index, RoleNames::Mayor).unwrap();
+        state.record_info(soldier_index, "You learn a 1".to_string());
+        state
+            .add_note(
+                soldier_index,
+                NoteKind::Suspicion,
+                "Claimed Chef privately".to_string(),
+            )
+            .unwrap();
+        state.skip_to_day_step(DayStep::Execution).unwrap();
+        state.execute_player(soldier_index).unwrap();
+        state.declare_winner(Alignment::Evil);
+
+        let report = GameReport::new(state);
+        assert_eq!(report.winner, Some("Evil".to_string()));
+        assert_eq!(report.players[soldier_index].starting_role, "Soldier");
+        assert_eq!(report.info.len(), 1);
+        assert_eq!(report.executions.len(), 1);
+        assert!(
+            state
+                .log
+                .search_current_phase(|event| match event {
+                    Event::GameOver(_) => Some(event),
+                    _ => None,
+                })
+                .is_ok()
+        );
+
+        assert!(report.to_markdown().contains("## Executions"));
+        assert!(report.to_plain_text().contains("EXECUTIONS"));
+        assert!(report.to_json().contains("\"starting_role\": \"Soldier\""));
+        assert_eq!(report.players[soldier_index].notes.len(), 1);
+        assert!(
+            report
+                .to_markdown()
+                .contains("Suspicion: Claimed Chef privately")
+        );
+        assert!(
+            report
+                .to_json()
+                .contains("\"text\": \"Claimed Chef privately\"")
+        );
+    }
+}
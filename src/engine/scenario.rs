@@ -0,0 +1,575 @@
+//! Small DSL for writing role interaction tests. Players sit in fixed seats and are referred to
+//! by name, and every step panics with a readable message if the game doesn't go as expected:
+//!
+//! ```ignore
+//! Scenario::new(&[("Alice", RoleNames::Monk), ("Bob", RoleNames::Imp), ...])
+//!     .advance_to(Step::Night)
+//!     .wake("Alice")
+//!     .answer(&["Carol"])
+//!     .assert_status("Carol", StatusType::DemonProtected);
+//! ```
+#![allow(clippy::needless_return)]
+
+use crate::{
+    engine::{
+        change_request::{ChangeArgs, ChangeRequest, ChangeType},
+        player::{Player, roles::RoleNames},
+        state::{PlayerIndex, State, Step, log::Event, status_effects::StatusType},
+    },
+    scripts::trouble_brewing,
+};
+
+/// More steps than this without reaching the target step means it can't be reached
+const MAX_STEPS: usize = 100;
+
+pub(crate) struct Scenario {
+    pub(crate) state: State,
+}
+
+impl Scenario {
+    /// Seats the players clockwise in the given order with the given roles
+    pub(crate) fn new(seats: &[(&str, RoleNames)]) -> Self {
+        let roles = seats.iter().map(|(_, role)| *role).collect();
+        let player_names = seats.iter().map(|(name, _)| name.to_string()).collect();
+        let state = State::from_seating(roles, player_names, trouble_brewing())
+            .expect("Scenario seating should be valid");
+        return Self { state };
+    }
+
+    pub(crate) fn index(&self, name: &str) -> PlayerIndex {
+        return self
+            .state
+            .get_players()
+            .iter()
+            .position(|player| player.name == name)
+            .unwrap_or_else(|| panic!("Nobody named {} is in the game", name));
+    }
+
+    pub(crate) fn player(&self, name: &str) -> &Player {
+        return self.state.get_player(self.index(name)).unwrap();
+    }
+
+    fn indices(&self, names: &[&str]) -> Vec<PlayerIndex> {
+        return names.iter().map(|name| self.index(name)).collect();
+    }
+
+    /// Moves on step by step until the given step starts. Anything still queued is left for the
+    /// test to answer
+    pub(crate) fn advance_to(&mut self, step: Step) -> &mut Self {
+        for _ in 0..MAX_STEPS {
+            if self.state.step == step {
+                return self;
+            }
+            self.state.next_step();
+            self.settle();
+        }
+        panic!("Never reached {:?}, stuck at {:?}", step, self.state.step);
+    }
+
+    pub(crate) fn next_step(&mut self) -> &mut Self {
+        self.state.next_step();
+        return self.settle();
+    }
+
+    /// Wakes a player for their ability in the current step, regardless of the night order
+    pub(crate) fn wake(&mut self, name: &str) -> &mut Self {
+        self.state.resolve(self.index(name));
+        return self.settle();
+    }
+
+    pub(crate) fn use_day_ability(&mut self, name: &str) -> &mut Self {
+        let cr = self
+            .state
+            .day_ability(self.index(name))
+            .unwrap_or_else(|| panic!("{} has no day ability to use", name));
+        self.state.change_request_queue.push_back(cr);
+        return self.settle();
+    }
+
+    /// Answers the next change request that needs the storyteller by choosing players
+    pub(crate) fn answer(&mut self, names: &[&str]) -> &mut Self {
+        let args = ChangeArgs::PlayerIndices(self.indices(names));
+        return self.answer_with(args);
+    }
+
+    /// Answers the next change request that needs the storyteller by choosing roles
+    pub(crate) fn answer_roles(&mut self, roles: &[RoleNames]) -> &mut Self {
+        return self.answer_with(ChangeArgs::Roles(roles.to_vec()));
+    }
+
+    /// Answers the next change request by giving each player a role
+    pub(crate) fn answer_pairs(&mut self, pairs: &[(&str, RoleNames)]) -> &mut Self {
+        let pairs = pairs
+            .iter()
+            .map(|(name, role)| (self.index(name), *role))
+            .collect();
+        return self.answer_with(ChangeArgs::PlayersAndRoles(pairs));
+    }
+
+    pub(crate) fn answer_yes_no(&mut self, answer: bool) -> &mut Self {
+        return self.answer_with(ChangeArgs::YesNo(answer));
+    }
+
+    pub(crate) fn answer_number(&mut self, number: usize) -> &mut Self {
+        return self.answer_with(ChangeArgs::Number(number));
+    }
+
+    /// Skips the next change request, which has to be optional
+    pub(crate) fn skip(&mut self) -> &mut Self {
+        return self.answer_with(ChangeArgs::Blank);
+    }
+
+    fn answer_with(&mut self, args: ChangeArgs) -> &mut Self {
+        let cr = self.next_request();
+        if cr.get_change_type() == ChangeType::Display {
+            panic!(
+                "Expected a choice but the storyteller is showing: {}",
+                cr.get_description()
+            );
+        }
+
+        if cr.get_state_change_func().is_none() {
+            panic!("\"{}\" has nothing to answer", cr.get_description());
+        }
+        if let Err(err) = cr.apply(&mut self.state, args.clone()) {
+            panic!(
+                "\"{}\" rejected {:?}: {:?}",
+                cr.get_description(),
+                args,
+                err
+            );
+        }
+        return self.settle();
+    }
+
+    /// Expects the next change request to refuse the players, and leaves it waiting for a real
+    /// answer
+    pub(crate) fn rejects(&mut self, names: &[&str]) -> &mut Self {
+        self.settle();
+        let cr = self
+            .state
+            .change_request_queue
+            .front()
+            .cloned()
+            .unwrap_or_else(|| panic!("Nothing is waiting for the storyteller"))
+            .build();
+        let args = ChangeArgs::PlayerIndices(self.indices(names));
+        if cr.validate(&self.state, &args).is_ok() {
+            panic!("\"{}\" accepted {:?}", cr.get_description(), names);
+        }
+        return self;
+    }
+
+    /// Expects the next change request to show something containing the text, and dismisses it
+    pub(crate) fn shown(&mut self, text: &str) -> &mut Self {
+        let cr = self.next_request();
+        let description = cr.get_description();
+        assert_eq!(
+            cr.get_change_type(),
+            ChangeType::Display,
+            "Expected to show \"{}\" but got a choice: {}",
+            text,
+            description
+        );
+        assert!(
+            description.contains(text),
+            "Expected to show \"{}\" but showed: {}",
+            text,
+            description
+        );
+        return self.settle();
+    }
+
+    pub(crate) fn nominate(&mut self, nominator: &str, target: &str) -> &mut Self {
+        if let Err(err) = self
+            .state
+            .nominate_player(self.index(nominator), self.index(target))
+        {
+            panic!("{} couldn't nominate {}: {}", nominator, target, err);
+        }
+        return self.settle();
+    }
+
+    pub(crate) fn execute(&mut self, name: &str) -> &mut Self {
+        if let Err(err) = self.state.execute_player(self.index(name)) {
+            panic!("{} couldn't be executed: {}", name, err);
+        }
+        return self.settle();
+    }
+
+    /// Runs the change requests at the front of the queue that don't need the storyteller
+    fn settle(&mut self) -> &mut Self {
+        while let Some(cr) = self.state.change_request_queue.front()
+            && cr.change_type == ChangeType::NoStoryteller
+        {
+            let cr = self.state.next_change_request().unwrap().build();
+            cr.apply(&mut self.state, ChangeArgs::Blank)
+                .unwrap_or_else(|err| panic!("Automatic change failed: {:?}", err));
+        }
+        return self;
+    }
+
+    fn next_request(&mut self) -> ChangeRequest {
+        self.settle();
+        return self
+            .state
+            .next_change_request()
+            .unwrap_or_else(|| panic!("Nothing is waiting for the storyteller"))
+            .build();
+    }
+
+    pub(crate) fn assert_dead(&mut self, name: &str) -> &mut Self {
+        assert!(self.player(name).dead, "{} should be dead", name);
+        return self;
+    }
+
+    pub(crate) fn assert_alive(&mut self, name: &str) -> &mut Self {
+        assert!(!self.player(name).dead, "{} should be alive", name);
+        return self;
+    }
+
+    pub(crate) fn assert_role(&mut self, name: &str, role: RoleNames) -> &mut Self {
+        assert_eq!(
+            self.player(name).role.to_role_name(),
+            role,
+            "{}'s role",
+            name
+        );
+        return self;
+    }
+
+    pub(crate) fn assert_status(&mut self, name: &str, status_type: StatusType) -> &mut Self {
+        assert!(
+            self.has_status(name, &status_type),
+            "{} should be {}",
+            name,
+            status_type
+        );
+        return self;
+    }
+
+    pub(crate) fn assert_no_status(&mut self, name: &str, status_type: StatusType) -> &mut Self {
+        assert!(
+            !self.has_status(name, &status_type),
+            "{} shouldn't be {}",
+            name,
+            status_type
+        );
+        return self;
+    }
+
+    fn has_status(&self, name: &str, status_type: &StatusType) -> bool {
+        return self
+            .player(name)
+            .status_effects
+            .iter()
+            .any(|status_effect| status_effect.status_type == *status_type);
+    }
+
+    /// Checks that an event was logged at any point in the game
+    pub(crate) fn assert_logged(&mut self, event: Event) -> &mut Self {
+        let logged = self
+            .state
+            .log
+            .day_phases
+            .iter()
+            .any(|day_phase| day_phase.log.contains(&event));
+        assert!(logged, "{:?} was never logged", event);
+        return self;
+    }
+
+    /// Checks that nothing is left for the storyteller to do
+    pub(crate) fn assert_settled(&mut self) -> &mut Self {
+        self.settle();
+        if let Some(cr) = self.state.next_change_request() {
+            panic!("Still waiting for the storyteller: {}", cr.description);
+        }
+        return self;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{
+        error::GameError,
+        state::{DayStep, status_effects::StatusEffect},
+    };
+
+    use super::*;
+
+    fn town(extra: &[(&'static str, RoleNames)]) -> Scenario {
+        let mut seats = vec![
+            ("Alice", RoleNames::Monk),
+            ("Bob", RoleNames::Soldier),
+            ("Carol", RoleNames::Mayor),
+            ("Dan", RoleNames::Slayer),
+            ("Eve", RoleNames::Spy),
+            ("Fred", RoleNames::Imp),
+        ];
+        seats.extend_from_slice(extra);
+        return Scenario::new(&seats);
+    }
+
+    #[test]
+    fn test_mayor_bounces_kill() {
+        town(&[])
+            .advance_to(Step::Night)
+            .wake("Alice")
+            .answer(&["Bob"])
+            .assert_status("Bob", StatusType::DemonProtected)
+            .wake("Fred")
+            .answer(&["Carol"])
+            .answer(&["Dan"])
+            .assert_settled()
+            .assert_alive("Carol")
+            .assert_dead("Dan");
+    }
+
+    #[test]
+    fn test_poisoned_imp_does_not_trigger_mayor() {
+        let mut scenario = town(&[]);
+        let (eve, fred) = (scenario.index("Eve"), scenario.index("Fred"));
+        scenario
+            .state
+            .get_player_mut(fred)
+            .unwrap()
+            .add_status(StatusEffect::new(StatusType::Poisoned, eve, None));
+        scenario
+            .advance_to(Step::Night)
+            .wake("Fred")
+            .answer(&["Carol"])
+            .assert_settled()
+            .assert_alive("Carol");
+    }
+
+    #[test]
+    fn test_monk_protects_from_imp() {
+        town(&[])
+            .advance_to(Step::Night)
+            .wake("Alice")
+            .answer(&["Dan"])
+            .wake("Fred")
+            .answer(&["Dan"])
+            .assert_alive("Dan")
+            .next_step()
+            .assert_no_status("Dan", StatusType::DemonProtected);
+    }
+
+    #[test]
+    fn test_monk_cannot_protect_self() {
+        town(&[])
+            .advance_to(Step::Night)
+            .wake("Alice")
+            .rejects(&["Alice"])
+            .rejects(&["Bob", "Carol"])
+            .answer(&["Bob"])
+            .assert_status("Bob", StatusType::DemonProtected);
+    }
+
+    #[test]
+    fn test_soldier_survives_imp() {
+        let mut scenario = town(&[]);
+        scenario
+            .advance_to(Step::Night)
+            .wake("Fred")
+            .answer(&["Bob"])
+            .assert_alive("Bob");
+        let bob = scenario.index("Bob");
+        scenario.assert_logged(Event::AttemptedKill {
+            attacking_player_index: scenario.index("Fred"),
+            target_player_index: bob,
+        });
+    }
+
+    #[test]
+    fn test_imp_star_pass() {
+        town(&[])
+            .advance_to(Step::Night)
+            .wake("Fred")
+            .answer(&["Fred"])
+            .answer(&["Eve"])
+            .assert_dead("Fred")
+            .assert_role("Eve", RoleNames::Imp);
+    }
+
+    #[test]
+    fn test_slayer_kills_imp() {
+        town(&[])
+            .advance_to(Step::Day(DayStep::Discussion))
+            .use_day_ability("Dan")
+            .answer(&["Fred"])
+            .assert_dead("Fred");
+    }
+
+    #[test]
+    fn test_virgin_executes_townsfolk_nominator() {
+        let mut scenario = town(&[("Gina", RoleNames::Virgin)]);
+        scenario
+            .advance_to(Step::Day(DayStep::Nominations))
+            .nominate("Alice", "Gina")
+            .assert_dead("Alice")
+            .assert_alive("Gina");
+        assert_eq!(scenario.state.step, Step::Day(DayStep::Dusk));
+        scenario.assert_logged(Event::Execution(scenario.index("Alice")));
+    }
+
+    #[test]
+    fn test_poisoned_virgin_loses_ability() {
+        let mut scenario = town(&[("Gina", RoleNames::Virgin), ("Hank", RoleNames::Poisoner)]);
+        scenario
+            .advance_to(Step::NightOne)
+            .wake("Hank")
+            .answer(&["Gina"])
+            .advance_to(Step::Day(DayStep::Nominations))
+            .nominate("Alice", "Gina")
+            .assert_alive("Alice")
+            .assert_alive("Gina");
+        let gina = scenario.index("Gina");
+        assert!(!scenario.state.has_event_listeners(gina));
+    }
+
+    #[test]
+    fn test_revived_virgin_keeps_ability() {
+        let mut scenario = town(&[("Gina", RoleNames::Virgin)]);
+        let gina = scenario.index("Gina");
+        scenario.state.set_dead(gina, true).unwrap();
+        scenario.state.set_dead(gina, false).unwrap();
+        scenario
+            .advance_to(Step::Day(DayStep::Nominations))
+            .nominate("Alice", "Gina")
+            .assert_dead("Alice");
+    }
+
+    #[test]
+    fn test_change_role_removes_old_statuses() {
+        let mut scenario = town(&[("Gina", RoleNames::Chef), ("Hank", RoleNames::Poisoner)]);
+        scenario
+            .advance_to(Step::NightOne)
+            .wake("Hank")
+            .answer(&["Gina"])
+            .assert_status("Gina", StatusType::Poisoned);
+        let hank = scenario.index("Hank");
+        scenario
+            .state
+            .change_role(hank, RoleNames::Soldier)
+            .unwrap();
+        scenario.assert_no_status("Gina", StatusType::Poisoned);
+
+        let fred = scenario.index("Fred");
+        scenario.state.change_role(hank, RoleNames::Imp).unwrap();
+        scenario
+            .state
+            .change_role(fred, RoleNames::Soldier)
+            .unwrap();
+        scenario.state.set_dead(hank, true).unwrap();
+        assert_eq!(scenario.state.game_over(), Ok(true));
+    }
+
+    #[test]
+    fn test_leaving_saint_is_not_executed() {
+        let mut scenario = town(&[("Gina", RoleNames::Saint)]);
+        let gina = scenario.index("Gina");
+        scenario.advance_to(Step::Day(DayStep::Nominations));
+        scenario.state.player_leaves(gina).unwrap();
+        scenario
+            .assert_dead("Gina")
+            .assert_logged(Event::Death(gina))
+            .assert_settled();
+        assert!(
+            !scenario
+                .state
+                .log
+                .day_phases
+                .iter()
+                .any(|day_phase| day_phase.log.contains(&Event::Execution(gina)))
+        );
+    }
+
+    #[test]
+    fn test_exile_does_not_end_the_day() {
+        let mut scenario = town(&[("Gina", RoleNames::Saint)]);
+        let gina = scenario.index("Gina");
+        scenario.advance_to(Step::Day(DayStep::Nominations));
+        assert_eq!(
+            scenario.state.exile_player(gina),
+            Err(GameError::NotTraveller(gina))
+        );
+        scenario.state.set_traveller(gina, true).unwrap();
+        scenario.state.exile_player(gina).unwrap();
+        scenario
+            .assert_dead("Gina")
+            .assert_logged(Event::Exile(gina))
+            .assert_settled();
+        assert_eq!(scenario.state.step, Step::Day(DayStep::Nominations));
+    }
+
+    #[test]
+    fn test_poisoned_chef_gets_false_information() {
+        let mut scenario = town(&[("Gina", RoleNames::Chef), ("Hank", RoleNames::Poisoner)]);
+        scenario
+            .advance_to(Step::NightOne)
+            .wake("Hank")
+            .answer(&["Gina"])
+            .wake("Gina")
+            .answer_number(0)
+            .shown("there are 0 pairs");
+        scenario.assert_logged(Event::FalseInformation {
+            player_index: scenario.index("Gina"),
+            true_info: "1".into(),
+            given_info: "0".into(),
+        });
+    }
+
+    #[test]
+    fn test_poisoned_fortune_teller_gets_false_information() {
+        let mut scenario = town(&[
+            ("Gina", RoleNames::Fortuneteller),
+            ("Hank", RoleNames::Poisoner),
+        ]);
+        scenario
+            .advance_to(Step::Setup)
+            .wake("Gina")
+            .answer(&["Bob"])
+            .advance_to(Step::NightOne)
+            .wake("Hank")
+            .answer(&["Gina"])
+            .wake("Gina")
+            .answer(&["Alice", "Fred"])
+            .answer_yes_no(false)
+            .shown("Thumbs Down")
+            .assert_settled();
+        scenario.assert_logged(Event::FalseInformation {
+            player_index: scenario.index("Gina"),
+            true_info: "Yes".into(),
+            given_info: "No".into(),
+        });
+    }
+
+    #[test]
+    fn test_undertaker_learns_execution() {
+        town(&[("Gina", RoleNames::Undertaker)])
+            .advance_to(Step::Day(DayStep::Execution))
+            .execute("Eve")
+            .advance_to(Step::Night)
+            .wake("Gina")
+            .shown("Spy was executed");
+    }
+
+    #[test]
+    fn test_washerwoman_shown_setup_tokens() {
+        Scenario::new(&[
+            ("Alice", RoleNames::Washerwoman),
+            ("Bob", RoleNames::Chef),
+            ("Carol", RoleNames::Butler),
+            ("Dan", RoleNames::Poisoner),
+            ("Eve", RoleNames::Imp),
+        ])
+        .advance_to(Step::Setup)
+        .wake("Alice")
+        .answer(&["Bob"])
+        .answer(&["Dan"])
+        .advance_to(Step::NightOne)
+        .wake("Alice")
+        .shown("seat 2 or seat 4 is the Chef");
+    }
+}
@@ -0,0 +1,379 @@
+//! Plays lots of random games against the engine to shake out panics. A bot storyteller answers
+//! every change request with random arguments that pass its constraints, and makes random
+//! nominations, executions and day ability uses. Every game is seeded, so a failure can be
+//! replayed with BOTC_SIMULATION_SEED=<seed> cargo test random_games
+#![allow(clippy::needless_return)]
+
+use std::{
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::{Arc, Mutex},
+};
+
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::IndexedRandom, seq::SliceRandom};
+
+use crate::{
+    engine::{
+        change_request::{ChangeArgs, ChangeRequest, ChangeType, Constraint},
+        player::{
+            CharacterType,
+            roles::{Role, RoleNames},
+        },
+        state::{DayStep, PlayerIndex, State, Step},
+    },
+    initialization::{CharacterTypeCounts, Script},
+    scripts::trouble_brewing,
+};
+
+const DEFAULT_GAMES: u64 = 3000;
+/// Games that haven't ended by then are stopped
+const MAX_DAYS: usize = 12;
+/// More change requests than this in a row means an ability is stuck in a loop
+const MAX_CHAINED_REQUESTS: usize = 200;
+
+/// A game that panicked, with everything needed to reproduce it
+#[derive(Debug)]
+pub(crate) struct Failure {
+    pub(crate) seed: u64,
+    pub(crate) message: String,
+    pub(crate) inputs: Vec<String>,
+}
+
+struct Simulation {
+    rng: StdRng,
+    state: State,
+    script: Script,
+    /// Shared so it survives the game panicking
+    inputs: Arc<Mutex<Vec<String>>>,
+}
+
+/// Plays one game from a seed. Returns the failure if the engine panicked
+pub(crate) fn run_game(seed: u64) -> Result<(), Failure> {
+    let inputs = Arc::new(Mutex::new(vec![]));
+    let game_inputs = inputs.clone();
+    let result = catch_unwind(AssertUnwindSafe(move || {
+        Simulation::new(seed, game_inputs).play();
+    }));
+
+    let Err(panic) = result else {
+        return Ok(());
+    };
+    let message = panic
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    let inputs = inputs
+        .lock()
+        .map(|inputs| inputs.clone())
+        .unwrap_or_default();
+    return Err(Failure {
+        seed,
+        message,
+        inputs,
+    });
+}
+
+/// A legal set of roles from the script for the number of players, including setup changes like
+/// the Baron's extra outsiders
+pub(crate) fn random_role_set(
+    rng: &mut impl Rng,
+    script: &Script,
+    player_count: usize,
+) -> Vec<RoleNames> {
+    let of_type = |character_type: CharacterType| {
+        script
+            .roles
+            .iter()
+            .filter(|role| {
+                role.convert()
+                    .is_ok_and(|role| role.get_true_character_type() == character_type)
+            })
+            .copied()
+            .collect::<Vec<RoleNames>>()
+    };
+    let mut counts = CharacterTypeCounts::new(player_count).unwrap();
+
+    let mut roles: Vec<RoleNames> = of_type(CharacterType::Demon)
+        .choose_multiple(rng, counts.demons as usize)
+        .copied()
+        .collect();
+    let minions: Vec<RoleNames> = of_type(CharacterType::Minion)
+        .choose_multiple(rng, counts.minions as usize)
+        .copied()
+        .collect();
+    for minion in minions.iter() {
+        if let Some(effect) = minion
+            .convert()
+            .ok()
+            .and_then(|role| role.initialization_effect())
+        {
+            counts += effect;
+        }
+    }
+    roles.extend(minions);
+    for (character_type, count) in [
+        (CharacterType::Outsider, counts.outsiders),
+        (CharacterType::Townsfolk, counts.townsfolk),
+    ] {
+        roles.extend(
+            of_type(character_type)
+                .choose_multiple(rng, count.max(0) as usize)
+                .copied(),
+        );
+    }
+
+    roles.shuffle(rng);
+    return roles;
+}
+
+impl Simulation {
+    fn new(seed: u64, inputs: Arc<Mutex<Vec<String>>>) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let script = trouble_brewing();
+        let player_count = rng.random_range(5..=15);
+        let roles = random_role_set(&mut rng, &script, player_count);
+        let player_names = (0..roles.len()).map(|seat| format!("P{}", seat)).collect();
+        inputs.lock().unwrap().push(format!("Seating: {:?}", roles));
+
+        let state = State::from_seating(roles, player_names, script.clone()).unwrap();
+        Self {
+            rng,
+            state,
+            script,
+            inputs,
+        }
+    }
+
+    fn record(&self, input: String) {
+        self.inputs.lock().unwrap().push(input);
+    }
+
+    fn play(&mut self) {
+        while self.state.day_num <= MAX_DAYS {
+            if self
+                .state
+                .game_over()
+                .expect("Simulated games always have a demon")
+            {
+                let winner = self
+                    .state
+                    .suggested_winner()
+                    .expect("A game that's over should have a winner");
+                self.state.declare_winner(winner);
+                self.record(format!("{} wins", winner));
+                return;
+            }
+
+            match self.state.step {
+                Step::Day(day_step) => self.play_day_step(day_step),
+                Step::Start | Step::Setup | Step::NightOne | Step::Night => self.play_night_step(),
+            }
+        }
+    }
+
+    fn play_night_step(&mut self) {
+        match self.state.advance_active_player() {
+            Some(player_index) => {
+                self.record(format!("Wake P{}", player_index));
+                self.state.resolve(player_index);
+            }
+            None => {
+                self.record("Next step".to_string());
+                self.state.next_step();
+            }
+        }
+        self.resolve_queue();
+    }
+
+    fn play_day_step(&mut self, day_step: DayStep) {
+        if self.rng.random_bool(0.3) {
+            let active = self.state.get_day_active();
+            if let Some(player_index) = active.choose(&mut self.rng).copied()
+                && let Some(cr) = self.state.day_ability(player_index)
+            {
+                self.record(format!("Day ability of P{}", player_index));
+                self.state.change_request_queue.push_back(cr);
+                self.resolve_queue();
+            }
+        }
+
+        let living = self.living_players();
+        match day_step {
+            DayStep::Nominations if self.rng.random_bool(0.5) && !living.is_empty() => {
+                let nominator = *living.choose(&mut self.rng).unwrap();
+                let target = self.rng.random_range(0..self.state.get_players().len());
+                self.record(format!("P{} nominates P{}", nominator, target));
+                if let Err(err) = self.state.nominate_player(nominator, target) {
+                    self.record(format!("Rejected: {}", err));
+                }
+                self.resolve_queue();
+            }
+            DayStep::Execution if self.rng.random_bool(0.5) && !living.is_empty() => {
+                let target = *living.choose(&mut self.rng).unwrap();
+                self.record(format!("Execute P{}", target));
+                if let Err(err) = self.state.execute_player(target) {
+                    self.record(format!("Rejected: {}", err));
+                }
+                self.resolve_queue();
+                return;
+            }
+            _ => (),
+        }
+
+        self.record("Next step".to_string());
+        self.state.next_step();
+        self.resolve_queue();
+    }
+
+    fn living_players(&self) -> Vec<PlayerIndex> {
+        self.state
+            .get_players()
+            .iter()
+            .enumerate()
+            .filter(|(_, player)| !player.dead)
+            .map(|(player_index, _)| player_index)
+            .collect()
+    }
+
+    /// Answers every queued change request the way a random storyteller would
+    fn resolve_queue(&mut self) {
+        for _ in 0..MAX_CHAINED_REQUESTS {
+            let Some(cr) = self.state.next_change_request() else {
+                return;
+            };
+            let cr = cr.build();
+            let args = self.random_args(&cr);
+            self.record(format!("{:?} -> {:?}", cr.get_description(), args));
+
+            // The answer respects the change request's constraints, so the engine has to take it
+            if let Some(args) = args
+                && let Err(err) = cr.apply(&mut self.state, args)
+            {
+                panic!(
+                    "{:?} rejected an answer within its constraints: {}",
+                    cr, err
+                );
+            }
+        }
+        panic!("More than {MAX_CHAINED_REQUESTS} change requests in a row");
+    }
+
+    fn random_args(&mut self, cr: &ChangeRequest) -> Option<ChangeArgs> {
+        if cr.is_optional() && self.rng.random_bool(0.2) {
+            return Some(ChangeArgs::Blank);
+        }
+
+        match cr.get_change_type() {
+            ChangeType::ChoosePlayers(_) => {
+                let count = self.random_count(cr);
+                Some(ChangeArgs::PlayerIndices(self.random_players(cr, count)))
+            }
+            ChangeType::ChooseRoles(_) => {
+                let count = self.random_count(cr);
+                Some(ChangeArgs::Roles(self.random_roles(cr, count)))
+            }
+            ChangeType::ChoosePlayersAndRoles(_) => {
+                let count = self.random_count(cr);
+                let players = self.random_players(cr, count);
+                let roles = match cr.get_constraints().contains(&Constraint::SameRole) {
+                    true => {
+                        let role = self.random_roles(cr, 1);
+                        role.repeat(players.len())
+                    }
+                    false => (0..players.len())
+                        .filter_map(|_| self.random_roles(cr, 1).pop())
+                        .collect::<Vec<_>>(),
+                };
+                Some(ChangeArgs::PlayersAndRoles(
+                    players.into_iter().zip(roles).collect(),
+                ))
+            }
+            ChangeType::YesNo => Some(ChangeArgs::YesNo(self.rng.random_bool(0.5))),
+            ChangeType::ChooseNumber { min, max } => {
+                Some(ChangeArgs::Number(self.rng.random_range(min..=max)))
+            }
+            ChangeType::NoStoryteller | ChangeType::Voting => Some(ChangeArgs::Blank),
+            ChangeType::Display => None,
+        }
+    }
+
+    fn random_players(&mut self, cr: &ChangeRequest, count: usize) -> Vec<PlayerIndex> {
+        let candidates: Vec<PlayerIndex> = self
+            .state
+            .get_players()
+            .iter()
+            .enumerate()
+            .filter(|(player_index, player)| {
+                cr.player_disabled_reason(*player_index, player).is_none()
+            })
+            .map(|(player_index, _)| player_index)
+            .collect();
+        return candidates
+            .choose_multiple(&mut self.rng, count)
+            .copied()
+            .collect();
+    }
+
+    fn random_roles(&mut self, cr: &ChangeRequest, count: usize) -> Vec<RoleNames> {
+        let candidates: Vec<RoleNames> = self
+            .script
+            .roles
+            .iter()
+            .filter(|role| cr.role_disabled_reason(**role, &self.state).is_none())
+            .copied()
+            .collect();
+        return candidates
+            .choose_multiple(&mut self.rng, count)
+            .copied()
+            .collect();
+    }
+
+    fn random_count(&mut self, cr: &ChangeRequest) -> usize {
+        let (min, max) = cr.count_range().unwrap_or_default();
+        return self.rng.random_range(min..=max);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_games() {
+        let seeds = match std::env::var("BOTC_SIMULATION_SEED") {
+            Ok(seed) => {
+                let seed = seed
+                    .parse()
+                    .expect("BOTC_SIMULATION_SEED should be a number");
+                seed..seed + 1
+            }
+            Err(_) => {
+                let games = std::env::var("BOTC_SIMULATION_GAMES")
+                    .ok()
+                    .and_then(|games| games.parse().ok())
+                    .unwrap_or(DEFAULT_GAMES);
+                0..games
+            }
+        };
+
+        let failures: Vec<Failure> = seeds.filter_map(|seed| run_game(seed).err()).collect();
+        let report = failures
+            .iter()
+            .map(|failure| {
+                format!(
+                    "Seed {} panicked: {}\nInputs:\n  {}",
+                    failure.seed,
+                    failure.message,
+                    failure.inputs.join("\n  ")
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        assert!(
+            failures.is_empty(),
+            "{} games panicked\n\n{}",
+            failures.len(),
+            report
+        );
+    }
+}
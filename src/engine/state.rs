@@ -1,25 +1,46 @@
 #![allow(dead_code, clippy::needless_return)]
+pub(crate) mod claims;
+pub(crate) mod dawn;
 pub(crate) mod log;
+pub(crate) mod madness;
+pub(crate) mod night_plan;
+pub(crate) mod public;
+pub(crate) mod seating;
+pub(crate) mod timeline;
+pub(crate) mod timer;
+pub(crate) mod transaction;
 
-use crate::console_error;
 use leptos::leptos_dom::logging::console_log;
 use log::Log;
-use std::{collections::VecDeque, fmt::Debug, sync::Arc};
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Display},
+    sync::Arc,
+};
 pub(crate) mod status_effects;
 
 use rand::{self, seq::SliceRandom};
 use reactive_stores::*;
+use serde_derive::{Deserialize, Serialize};
 
 use crate::{
     engine::{
         change_request::ChangeRequestBuilder,
+        error::{GameError, SetupError},
         player::{
-            Player,
+            Alignment, Player, PlayerBehaviors, false_information,
+            notes::{Note, NoteKind},
             roles::{Role, RoleNames},
+            suppressible,
         },
         state::{
+            claims::Claim,
+            dawn::DawnReport,
             log::Event,
-            status_effects::{CleanupPhase, StatusType},
+            night_plan::NightPlan,
+            status_effects::{CleanupPhase, StatusEffect, StatusType},
+            timer::{DayTimer, TimerSettings, format_secs},
+            transaction::AbilityTransaction,
         },
     },
     initialization::Script,
@@ -55,15 +76,12 @@ pub(crate) type PlayerIndex = usize;
 //     }
 // }
 
-#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 pub enum Step {
     #[default]
     Start,
     Setup,
-    // Day
-    // DayDiscussion,
-    // DayExecution,
-    Day,
+    Day(DayStep),
     // Night
     NightOne,
     Night,
@@ -76,15 +94,87 @@ pub enum Step {
     // DisplayPlayers,
 }
 
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::Start => f.write_str("Start"),
+            Step::Setup => f.write_str("Setup"),
+            Step::Day(day_step) => write!(f, "{}", day_step),
+            Step::NightOne => f.write_str("Night One"),
+            Step::Night => f.write_str("Night"),
+        }
+    }
+}
+
+/// The parts of a day, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum DayStep {
+    /// Night deaths are announced
+    #[default]
+    Dawn,
+    Discussion,
+    Nominations,
+    /// Nominations are closed and the player on the block is executed
+    Execution,
+    /// End of day abilities resolve
+    Dusk,
+}
+
+impl DayStep {
+    /// The part of the day after this one, or None if night comes next
+    pub(crate) fn next(self) -> Option<DayStep> {
+        match self {
+            DayStep::Dawn => Some(DayStep::Discussion),
+            DayStep::Discussion => Some(DayStep::Nominations),
+            DayStep::Nominations => Some(DayStep::Execution),
+            DayStep::Execution => Some(DayStep::Dusk),
+            DayStep::Dusk => None,
+        }
+    }
+}
+
+impl Display for DayStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            DayStep::Dawn => "Dawn",
+            DayStep::Discussion => "Discussion",
+            DayStep::Nominations => "Nominations",
+            DayStep::Execution => "Execution",
+            DayStep::Dusk => "Dusk",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// How much of the game logic the engine runs by itself
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum GameMode {
+    /// Abilities and event listeners change the state automatically
+    #[default]
+    Automatic,
+    /// The engine still walks the night order and logs events, but the storyteller makes every
+    /// change to the state by hand
+    Manual,
+}
+
+impl Display for GameMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameMode::Automatic => f.write_str("Automatic"),
+            GameMode::Manual => f.write_str("Manual"),
+        }
+    }
+}
+
+pub(crate) type ListenerFunc<EventType> = dyn for<'a> Fn(&mut EventListenerState, &'a mut State, EventType) -> &'a mut State
+    + 'static
+    + Send
+    + Sync;
+
 #[derive(Clone)]
 pub(crate) struct EventListener<EventType> {
     state: EventListenerState,
-    listener: Arc<
-        dyn for<'a> Fn(&mut EventListenerState, &'a mut State, EventType) -> &'a mut State
-            + 'static
-            + Send
-            + Sync,
-    >,
+    listener: Arc<ListenerFunc<EventType>>,
 }
 
 impl<EventType> Debug for EventListener<EventType> {
@@ -129,6 +219,24 @@ pub(crate) struct State {
     pub(crate) log: Log,
     script: Script,
     pub(crate) step: Step,
+    pub(crate) mode: GameMode,
+    /// Role claims made by each player, indexed by PlayerIndex
+    claims: Vec<Vec<Claim>>,
+    pub(crate) night_plan: NightPlan,
+    /// Whether each seat is on the same couch as the seat after it
+    pub(crate) couch_links: Vec<bool>,
+    /// Timer for the current part of the day, if the storyteller started one
+    pub(crate) timer: Option<DayTimer>,
+    pub(crate) timer_settings: TimerSettings,
+    /// Who died last night. Only set during the day
+    pub(crate) dawn_report: Option<DawnReport>,
+    pub(crate) winner: Option<Alignment>,
+    /// The ability currently resolving, which the storyteller can still cancel
+    pub(crate) ability_transaction: Option<AbilityTransaction>,
+    /// The description of the change request being applied right now, if any
+    pub(crate) applying_change_request: Option<String>,
+    /// Where to snapshot the grimoire after each logged event. Only the interface sets this
+    pub(crate) snapshot_store: Option<timeline::SnapshotStore>,
 
     // pub(crate) curr_args: Option<ChangeArgs>,
     // pub(crate) curr_description: Option<String>,
@@ -138,6 +246,9 @@ pub(crate) struct State {
     pub(crate) attempted_kill_listeners: Vec<EventListener<log::AttemptedKill>>,
     pub(crate) prevent_kill_default: bool,
     pub(crate) death_listeners: Vec<EventListener<log::Death>>,
+    pub(crate) execution_listeners: Vec<EventListener<log::Execution>>,
+    /// Called at the start of every step and part of the day
+    pub(crate) phase_listeners: Vec<EventListener<log::PhaseStart>>,
 }
 
 impl State {
@@ -145,30 +256,41 @@ impl State {
         mut roles: Vec<RoleNames>,
         player_names: Vec<String>,
         script: Script,
-    ) -> Result<Self, ()> {
-        let mut players: Vec<Player> = vec![];
-
+    ) -> Result<Self, GameError> {
         let mut rng = rand::rng();
         roles.shuffle(&mut rng);
 
+        return Self::from_seating(roles, player_names, script);
+    }
+
+    /// Creates a game where each player gets the role at the same position, without shuffling
+    pub(crate) fn from_seating(
+        roles: Vec<RoleNames>,
+        player_names: Vec<String>,
+        script: Script,
+    ) -> Result<Self, GameError> {
+        let mut players: Vec<Player> = vec![];
+
         if roles.len() != player_names.len() {
-            eprintln!("Number of players does not match number of roles");
-            // TODO: Figure out to do errors here
-            return Err(());
+            return Err(SetupError::RoleCountMismatch {
+                players: player_names.len(),
+                roles: roles.len(),
+            }
+            .into());
         }
 
         // TODO: Figure out how to store roles properly so we can create a game. Do we even need
         // the roles enum?
         // Idea: For now, just have a method to translate a member of the enum to a role trait
         for i in 0..roles.len() {
-            let player = Player::new(player_names[i].clone(), roles[i].convert());
+            let player = Player::new(player_names[i].clone(), roles[i].convert()?);
             players.push(player);
         }
 
         let win_cond_index = players
             .iter()
             .position(|player| player.role.is_win_condition())
-            .unwrap();
+            .ok_or(SetupError::NoDemon)?;
 
         let _demon_listener = EventListener::new(
             win_cond_index,
@@ -203,6 +325,7 @@ impl State {
         let win_cond_i = players.iter().position(|p| p.role.is_win_condition());
 
         let log = Log::new();
+        let player_count = players.len();
 
         let mut state = Self {
             players,
@@ -211,6 +334,17 @@ impl State {
             log,
             script,
             step: Step::default(),
+            mode: GameMode::default(),
+            claims: vec![vec![]; player_count],
+            night_plan: NightPlan::default(),
+            couch_links: vec![false; player_count],
+            timer: None,
+            timer_settings: TimerSettings::default(),
+            dawn_report: None,
+            winner: None,
+            ability_transaction: None,
+            applying_change_request: None,
+            snapshot_store: None,
 
             // curr_args: None,
             // curr_description: None,
@@ -221,6 +355,8 @@ impl State {
             prevent_kill_default: false,
             // TODO: Maybe add a listener for demon death?
             death_listeners: vec![],
+            execution_listeners: vec![],
+            phase_listeners: vec![],
         };
 
         for (player_index, player) in state.players.clone().iter().enumerate() {
@@ -231,23 +367,31 @@ impl State {
         return Ok(state);
     }
 
-    pub(crate) fn get_player_index(&self, player: &Player) -> PlayerIndex {
+    /// Where the player sits. A player that isn't seated is reported as sitting past the last seat
+    pub(crate) fn get_player_index(&self, player: &Player) -> Result<PlayerIndex, GameError> {
         self.players
             .iter()
             .position(|p| p == player)
-            .expect("Player should be in player array")
+            .ok_or(GameError::NoSuchPlayer(self.players.len()))
     }
 
     pub(crate) fn get_players(&self) -> &Vec<Player> {
         &self.players
     }
 
-    pub(crate) fn get_player(&self, player_index: PlayerIndex) -> &Player {
-        &self.players[player_index]
+    pub(crate) fn get_player(&self, player_index: PlayerIndex) -> Result<&Player, GameError> {
+        self.players
+            .get(player_index)
+            .ok_or(GameError::NoSuchPlayer(player_index))
     }
 
-    pub(crate) fn get_player_mut(&mut self, player_index: PlayerIndex) -> &mut Player {
-        &mut self.players[player_index]
+    pub(crate) fn get_player_mut(
+        &mut self,
+        player_index: PlayerIndex,
+    ) -> Result<&mut Player, GameError> {
+        self.players
+            .get_mut(player_index)
+            .ok_or(GameError::NoSuchPlayer(player_index))
     }
 
     pub(crate) fn living_player_count(&self) -> usize {
@@ -257,7 +401,7 @@ impl State {
     pub(crate) fn left_player(&self, player_index: PlayerIndex) -> PlayerIndex {
         let mut index: PlayerIndex = (player_index + self.players.len() - 1) % self.players.len();
         // eprintln!("{}", index);
-        while self.players[index].dead {
+        while self.players[index].dead || self.players[index].left {
             // eprintln!("{}", index);
             index = (index + self.players.len() - 1) % self.players.len();
         }
@@ -266,69 +410,127 @@ impl State {
     }
     pub(crate) fn right_player(&self, player_index: PlayerIndex) -> PlayerIndex {
         let mut index: PlayerIndex = (player_index + self.players.len() + 1) % self.players.len();
-        while self.players[index].dead {
+        while self.players[index].dead || self.players[index].left {
             index = (index + self.players.len() + 1) % self.players.len();
         }
 
         return index;
     }
 
-    pub(crate) fn set_win_condition(&mut self, player: &Player) {
-        self.win_cond_i = Some(self.get_player_index(player));
+    pub(crate) fn set_win_condition(&mut self, player: &Player) -> Result<(), GameError> {
+        self.win_cond_i = Some(self.get_player_index(player)?);
+        Ok(())
     }
 
-    pub(crate) fn game_over(&self) -> bool {
-        let index = match self.win_cond_i {
-            Some(i) => i,
-            None =>
-            // TODO: Need to implement this for athiest games, but this should be manually
-            // resolved by story teller most likely
-            {
-                todo!()
-            }
-        };
-        // Game ends if win condition player is dead
-        self.players[index].dead
+    /// The team that looks like it has won, going by the demon being dead or only two players
+    /// being left. The storyteller still has to declare it, since other abilities can end the
+    /// game too
+    pub(crate) fn suggested_winner(&self) -> Option<Alignment> {
+        let demon_dead = self
+            .win_cond_i
+            .is_some_and(|player_index| self.get_player(player_index).is_ok_and(|p| p.dead));
+        if demon_dead {
+            return Some(Alignment::Good);
+        }
+        if self.living_player_count() <= 2 {
+            return Some(Alignment::Evil);
+        }
+        return None;
+    }
+
+    pub(crate) fn declare_winner(&mut self, winner: Alignment) {
+        if self.winner.is_some() {
+            return;
+        }
+
+        self.winner = Some(winner);
+        self.log_event(Event::GameOver(winner));
+    }
+
+    /// Logs the event. If the timeline is listening, it gets the grimoire as it is now along with
+    /// the change request and ability that caused the event
+    pub(crate) fn log_event(&mut self, event: Event) {
+        let event_id = self.log.log_event(event);
+        if let (Some(event_id), Some(snapshot_store)) = (event_id, &self.snapshot_store) {
+            let acting_player = self
+                .ability_transaction
+                .as_ref()
+                .map(|transaction| transaction.player_index);
+            let snapshot = self.snapshot(self.applying_change_request.clone(), acting_player);
+            snapshot_store.push(event_id, snapshot);
+        }
+    }
+
+    /// Records information the storyteller showed a player, so it can be reviewed later
+    pub(crate) fn record_info(&mut self, player_index: PlayerIndex, info: String) {
+        self.log_event(Event::InfoLearned { player_index, info });
+    }
+
+    pub(crate) fn game_over(&self) -> Result<bool, GameError> {
+        // Athiest games are left to the storyteller
+        let index = self.win_cond_i.ok_or(GameError::NoWinCondition)?;
+        // Game ends if win condition player is dead, or only two players are left alive
+        Ok(self.get_player(index)?.dead || self.living_player_count() <= 2)
     }
 
     pub(crate) fn next_step(&mut self) {
         let next_step = match self.step {
             Step::Start => Step::Setup,
             Step::Setup => Step::NightOne,
-            // Step::DayDiscussion => Step::DayExecution,
-            // Step::DayExecution => {
-            //     self.cleanup_statuses(CleanupPhase::Dusk);
-            //     self.day_num += 1;
-            //     Step::Night
-            // }
-            Step::Day => {
-                self.cleanup_statuses(CleanupPhase::Dusk);
-                self.day_num += 1;
-                Step::Night
-            }
+            Step::Day(day_step) => match day_step.next() {
+                Some(next_day_step) => {
+                    // The day can't go on without the town knowing who died
+                    if day_step == DayStep::Dawn {
+                        self.announce_dawn();
+                    }
+                    Step::Day(next_day_step)
+                }
+                None => {
+                    self.cleanup_statuses(CleanupPhase::Dusk);
+                    self.day_num += 1;
+                    self.dawn_report = None;
+                    Step::Night
+                }
+            },
             Step::NightOne | Step::Night => {
                 self.cleanup_statuses(CleanupPhase::Dawn);
-                Step::Day
+                self.dawn_report = Some(self.build_dawn_report());
+                Step::Day(DayStep::Dawn)
             }
         };
 
-        self.log.next_phase();
+        // The log starts in the Setup phase
+        if self.step != Step::Start {
+            self.log.next_phase();
+        }
+        self.commit_ability();
 
         self.step = next_step;
-        // TODO: Log step change
+        self.timer = None;
+        // Nobody wakes during the day, so this empties the plan
+        self.build_night_plan();
+        self.notify_listeners(
+            |state| &mut state.phase_listeners,
+            log::PhaseStart(next_step),
+        );
     }
 
-    pub(crate) fn get_next_active_player(
-        &self,
-        previous_player: Option<PlayerIndex>,
-    ) -> Option<PlayerIndex> {
-        match self.step {
-            Step::Start => None,
-            Step::Setup => self.get_next_active_setup(previous_player),
-            Step::NightOne => self.get_next_active_night_one(previous_player),
-            Step::Night => self.get_next_active_night(previous_player),
-            _ => None,
+    /// Moves on to the given part of today, skipping anything in between. Does nothing if that
+    /// part of the day has already passed
+    pub(crate) fn skip_to_day_step(&mut self, day_step: DayStep) -> Result<(), GameError> {
+        if !matches!(self.step, Step::Day(_)) {
+            return Err(GameError::IllegalAction {
+                action: "skip ahead in the day",
+                step: self.step,
+            });
+        }
+
+        while let Step::Day(current) = self.step
+            && current < day_step
+        {
+            self.next_step();
         }
+        return Ok(());
     }
 
     /// Function to resolve a player's effect on the state
@@ -342,7 +544,19 @@ impl State {
     /// * Option<ChangeRequest> : A change request if the role does something, or none if it
     ///   doesn't
     pub(crate) fn resolve(&mut self, player_index: PlayerIndex) {
-        let player = self.get_player(player_index);
+        self.begin_ability(player_index);
+        self.in_ability(|state| state.queue_ability(player_index));
+    }
+
+    /// Queues the change request the player's ability starts with
+    fn queue_ability(&mut self, player_index: PlayerIndex) {
+        if self.resolve_storyteller_wake(player_index) {
+            return;
+        }
+
+        let Ok(player) = self.get_player(player_index) else {
+            return;
+        };
 
         let res = match self.step {
             Step::Setup => player.setup_ability(player_index, self),
@@ -350,116 +564,300 @@ impl State {
             Step::Night => player.night_ability(player_index, self),
             _ => None,
         };
+        let suppression = player.ability_suppression(self.step_behavior());
 
         if let Some(cr) = res {
+            // Change requests that only display something never get resolved, so their
+            // malfunction has to be logged here
+            if cr.state_change_func.is_none()
+                && let Some(reason) = suppression
+            {
+                self.log_event(Event::AbilityMalfunction {
+                    player_index,
+                    reason,
+                });
+                if cr.information.is_some() {
+                    let false_info = false_information(player_index, cr, self.players.len());
+                    self.change_request_queue.push_back(false_info);
+                    return;
+                }
+            }
             self.change_request_queue.push_back(cr);
         }
         // TODO: Log events that happen in the setup
     }
 
+    /// The player behavior that abilities resolved during the current step fall under
+    fn step_behavior(&self) -> PlayerBehaviors {
+        match self.step {
+            Step::Setup => PlayerBehaviors::SetupAbility,
+            Step::NightOne => PlayerBehaviors::NightOneAbility,
+            Step::Night => PlayerBehaviors::NightAbility,
+            Step::Start | Step::Day(_) => PlayerBehaviors::DayAbility,
+        }
+    }
+
+    /// Calls every listener for an event. Listeners whose source player is droisoned are run
+    /// on a copy of the state instead, and if they would have done anything, it is logged as a
+    /// malfunctioning ability. Only their ability being used up is kept. Change requests queued
+    /// by a listener are suppressible, so the rest of the chain is checked again when it resolves.
+    fn notify_listeners<EventType: Clone>(
+        &mut self,
+        listeners: fn(&mut State) -> &mut Vec<EventListener<EventType>>,
+        event: EventType,
+    ) {
+        // Listeners are part of the logic the storyteller handles in manual mode
+        if self.mode == GameMode::Manual {
+            return;
+        }
+
+        let mut event_listeners = std::mem::take(listeners(self));
+        for listener in event_listeners.iter_mut() {
+            let source_player_index = listener.state.source_player_index;
+            let state_queue_len = self.change_request_queue.len();
+            // A listener left behind by a seat that's gone has nobody to act for
+            let suppression = match self.get_player(source_player_index) {
+                Ok(player) => player.ability_suppression(PlayerBehaviors::EventListener),
+                Err(_) => continue,
+            };
+
+            match suppression {
+                None => {
+                    listener.call(self, event.clone());
+                    let follow_ups = self.change_request_queue.split_off(state_queue_len);
+                    for cr in follow_ups {
+                        self.change_request_queue.push_back(suppressible(
+                            source_player_index,
+                            PlayerBehaviors::EventListener,
+                            cr,
+                            None,
+                        ));
+                    }
+                }
+                Some(reason) => {
+                    let mut state_copy = self.clone();
+                    let mut listener_copy = listener.clone();
+                    listener_copy.call(&mut state_copy, event.clone());
+                    self.keep_ability_effects(source_player_index, &state_copy);
+                    let malfunctioned = state_copy.change_request_queue.len() > state_queue_len
+                        || state_copy.prevent_kill_default != self.prevent_kill_default;
+
+                    // Follow ups that change the state still get resolved, on a copy, so the
+                    // ability gets used up (e.g. a poisoned Virgin still loses her ability)
+                    let follow_ups: Vec<_> = state_copy
+                        .change_request_queue
+                        .split_off(state_queue_len)
+                        .into_iter()
+                        .filter(|cr| cr.state_change_func.is_some())
+                        .collect();
+                    if follow_ups.is_empty() && malfunctioned {
+                        self.log_event(Event::AbilityMalfunction {
+                            player_index: source_player_index,
+                            reason: reason.clone(),
+                        });
+                    }
+                    for cr in follow_ups {
+                        self.change_request_queue.push_back(suppressible(
+                            source_player_index,
+                            PlayerBehaviors::EventListener,
+                            cr,
+                            Some(reason.clone()),
+                        ));
+                    }
+                }
+            }
+        }
+
+        *listeners(self) = event_listeners;
+    }
+
     pub(crate) fn kill(
         &mut self,
         attacking_player_index: PlayerIndex,
         target_player_index: PlayerIndex,
-    ) {
+    ) -> Result<(), GameError> {
+        self.get_player(target_player_index)?;
         // Go through all kill listeners (can maybe set a change request up to go)
         self.prevent_kill_default = false;
-        let mut state = self;
-        state.log.log_event(Event::AttemptedKill {
+        self.log_event(Event::AttemptedKill {
             attacking_player_index,
             target_player_index,
         });
-        let mut attempted_kill_listeners = std::mem::take(&mut state.attempted_kill_listeners);
-        for listener in attempted_kill_listeners.iter_mut() {
-            if state.players[listener.state.source_player_index]
-                .status_effects
-                .iter_mut()
-                .any(|se| matches!(se.status_type, StatusType::Poisoned | StatusType::Drunk))
-            {
-                continue;
-            }
+        self.notify_listeners(
+            |state| &mut state.attempted_kill_listeners,
+            log::AttemptedKill {
+                attacking_player_index,
+                target_player_index,
+            },
+        );
 
-            state = listener.call(
-                state,
-                log::AttemptedKill {
-                    attacking_player_index,
-                    target_player_index,
-                },
-            );
-        }
-        // Go through all status effects
-        for status_effect in state.players[target_player_index].status_effects.iter() {
-            if status_effect.status_type == status_effects::StatusType::DemonProtected {
-                state.prevent_kill_default = true;
-            }
+        // Go through all status effects. Protection only works while the ability it comes from
+        // does
+        let protected = self
+            .get_player(target_player_index)?
+            .status_effects
+            .iter()
+            .filter(|se| {
+                se.behavior_types
+                    .as_ref()
+                    .is_some_and(|behaviors| behaviors.contains(&PlayerBehaviors::Kill))
+            })
+            .any(|se| {
+                self.get_player(se.source_player_index).is_ok_and(|source| {
+                    source
+                        .ability_suppression(PlayerBehaviors::PassiveAbility)
+                        .is_none()
+                })
+            });
+        if protected {
+            self.prevent_kill_default = true;
         }
 
-        state.attempted_kill_listeners = attempted_kill_listeners;
-        console_error(
-            format!(
-                "Kill attempted and prevent_default {:?}",
-                state.prevent_kill_default
-            )
-            .as_str(),
-        );
-        if state.prevent_kill_default {
-            return;
+        if self.prevent_kill_default {
+            return Ok(());
         }
 
         // TODO: Return early if a listener needs us to (need to get this information from the
         // listener)
 
-        // let cr = self.get_player_mut(target_player_index).kill(
-        //     attacking_player_index,
-        //     target_player_index,
-        //     &state_snapshot,
-        // );
         // FIX: Shouldn't always successfully kill
-        state.get_player_mut(target_player_index).dead = true;
+        self.get_player_mut(target_player_index)?.dead = true;
+        self.handle_death(target_player_index);
+        return Ok(());
+    }
 
-        let dead = state.get_player(target_player_index).dead;
-        if dead {
-            state.handle_death(target_player_index);
+    pub(crate) fn handle_death(&mut self, player_index: PlayerIndex) {
+        self.log_event(Event::Death(player_index));
+        self.notify_listeners(
+            |state| &mut state.death_listeners,
+            log::Death { player_index },
+        );
+
+        // In manual mode this happens when switching back to automatic
+        if self.mode == GameMode::Automatic {
+            self.cleanup_event_listeners(player_index);
+            self.cleanup_player_statuses(player_index);
         }
     }
 
-    pub(crate) fn handle_death(&mut self, player_index: PlayerIndex) {
-        let mut state = self;
-        state.log.log_event(Event::Death(player_index));
-        let mut death_listeners = std::mem::take(&mut state.death_listeners);
-        for listener in death_listeners.iter_mut() {
-            if state.players[listener.state.source_player_index]
-                .status_effects
-                .iter_mut()
-                .any(|se| matches!(se.status_type, StatusType::Poisoned | StatusType::Drunk))
-            {
-                continue;
+    /// Switches between automatic and manual mode. When going back to automatic, anything the
+    /// dead players left behind while in manual mode is cleaned up so that enforcement resumes
+    /// from the edited state
+    pub(crate) fn set_mode(&mut self, mode: GameMode) {
+        if self.mode == mode {
+            return;
+        }
+
+        self.mode = mode;
+        if mode == GameMode::Automatic {
+            let dead_players: Vec<PlayerIndex> = self
+                .players
+                .iter()
+                .enumerate()
+                .filter_map(|(player_index, player)| player.dead.then_some(player_index))
+                .collect();
+            for player_index in dead_players {
+                self.cleanup_event_listeners(player_index);
+                self.cleanup_player_statuses(player_index);
             }
-            state = listener.call(state, log::Death { player_index });
         }
+        self.log_event(Event::ModeChanged(mode));
+    }
 
-        state.death_listeners = death_listeners;
-        state.cleanup_event_listeners(player_index);
-        state.cleanup_player_statuses(player_index);
+    /// Marks a player as dead or alive by hand, without going through kill or execution
+    pub(crate) fn set_dead(
+        &mut self,
+        player_index: PlayerIndex,
+        dead: bool,
+    ) -> Result<(), GameError> {
+        if self.get_player(player_index)?.dead == dead {
+            return Ok(());
+        }
+
+        self.get_player_mut(player_index)?.dead = dead;
+        if dead {
+            self.handle_death(player_index);
+        } else {
+            // Dying cleaned up the player's ability, so it has to be set up again
+            if self.mode == GameMode::Automatic {
+                self.cleanup_event_listeners(player_index);
+                let role = self.get_player(player_index)?.role.clone();
+                role.initialize(player_index, self);
+            }
+            self.log_event(Event::Revived(player_index));
+        }
+        return Ok(());
     }
 
+    /// Changes a player's role by hand. The old role's listeners and the statuses it placed are
+    /// removed and the new role is initialized, but the player keeps their alignment
+    pub(crate) fn change_role(
+        &mut self,
+        player_index: PlayerIndex,
+        role: RoleNames,
+    ) -> Result<(), GameError> {
+        let old_role = self.get_player(player_index)?.role.to_role_name();
+        if old_role == role {
+            return Ok(());
+        }
+
+        self.cleanup_event_listeners(player_index);
+        self.cleanup_role_statuses(player_index);
+        let new_role = role.convert()?;
+        self.set_role(player_index, new_role.clone())?;
+        new_role.initialize(player_index, self);
+        self.update_win_condition();
+
+        self.log_event(Event::RoleChanged {
+            player_index,
+            old_role,
+            new_role: role,
+        });
+        return Ok(());
+    }
+
+    /// Changes a player's alignment by hand
+    pub(crate) fn set_alignment(
+        &mut self,
+        player_index: PlayerIndex,
+        alignment: Alignment,
+    ) -> Result<(), GameError> {
+        if self.get_player(player_index)?.alignment == alignment {
+            return Ok(());
+        }
+
+        self.get_player_mut(player_index)?.alignment = alignment;
+        self.log_event(Event::AlignmentChanged {
+            player_index,
+            alignment,
+        });
+        return Ok(());
+    }
+
+    /// The event in words. An event about a seat that doesn't exist says so instead
     pub(crate) fn describe_event(&self, event: Event) -> String {
-        match event {
+        return self
+            .try_describe_event(event)
+            .unwrap_or_else(|err| err.to_string());
+    }
+
+    fn try_describe_event(&self, event: Event) -> Result<String, GameError> {
+        let description = match event {
             Event::Nomination {
                 nominator_player_index,
                 target_player_index,
             } => {
                 format!(
                     "{} nominated {} for execution",
-                    self.get_player(nominator_player_index).name,
-                    self.get_player(target_player_index).name
+                    self.get_player(nominator_player_index)?.name,
+                    self.get_player(target_player_index)?.name
                 )
             }
             Event::Voting {
                 players_voted,
                 target_player_index,
             } => {
-                let player = self.get_player(target_player_index);
+                let player = self.get_player(target_player_index)?;
                 let descriptor = match players_voted {
                     0 => "Nobody",
                     1 => "Person",
@@ -471,15 +869,15 @@ impl State {
                 )
             }
             Event::Execution(player_index) => {
-                let player = self.get_player(player_index);
+                let player = self.get_player(player_index)?;
                 format!("{}({}) was executed", player.name, player.role)
             }
             Event::AttemptedKill {
                 attacking_player_index,
                 target_player_index,
             } => {
-                let attacking_player = self.get_player(attacking_player_index);
-                let target_player = self.get_player(target_player_index);
+                let attacking_player = self.get_player(attacking_player_index)?;
+                let target_player = self.get_player(target_player_index)?;
                 format!(
                     "{}({}) attemped to kill {}({})",
                     attacking_player.name,
@@ -489,7 +887,7 @@ impl State {
                 )
             }
             Event::Death(player_index) => {
-                let player = self.get_player(player_index);
+                let player = self.get_player(player_index)?;
                 format!("{}({}) died", player.name, player.role)
             }
             Event::StatusApplied {
@@ -497,8 +895,8 @@ impl State {
                 target_player_index,
                 status_effect,
             } => {
-                let source_player = self.get_player(source_player_index);
-                let target_player = self.get_player(target_player_index);
+                let source_player = self.get_player(source_player_index)?;
+                let target_player = self.get_player(target_player_index)?;
                 format!(
                     "{}({}) gave {}({}) {} effect",
                     source_player.name,
@@ -508,59 +906,345 @@ impl State {
                     status_effect
                 )
             }
-            Event::InfoLearned(info) =>
-            // TODO: Include player index
-            {
-                format!("{info} was learned")
+            Event::AbilityMalfunction {
+                player_index,
+                reason,
+            } => {
+                let player = self.get_player(player_index)?;
+                format!(
+                    "{}({}) used their ability while {} (it had no effect)",
+                    player.name, player.role, reason
+                )
             }
-        }
+            Event::MadnessBroken {
+                player_index,
+                role,
+                by,
+                day_num,
+            } => {
+                let player = self.get_player(player_index)?;
+                format!(
+                    "{}({}) broke madness about being the {} ({}) on day {}",
+                    player.name, player.role, role, by, day_num
+                )
+            }
+            Event::FalseInformation {
+                player_index,
+                true_info,
+                given_info,
+            } => {
+                let player = self.get_player(player_index)?;
+                format!(
+                    "{}({}) was told {} instead of {}",
+                    player.name, player.role, given_info, true_info
+                )
+            }
+            Event::ModeChanged(mode) => format!("Storyteller switched to {} mode", mode),
+            Event::Revived(player_index) => {
+                let player = self.get_player(player_index)?;
+                format!("Storyteller revived {}({})", player.name, player.role)
+            }
+            Event::RoleChanged {
+                player_index,
+                old_role,
+                new_role,
+            } => {
+                let player = self.get_player(player_index)?;
+                format!(
+                    "Storyteller changed {}'s role from {} to {}",
+                    player.name, old_role, new_role
+                )
+            }
+            Event::AlignmentChanged {
+                player_index,
+                alignment,
+            } => {
+                let player = self.get_player(player_index)?;
+                format!(
+                    "Storyteller changed {}({})'s alignment to {}",
+                    player.name, player.role, alignment
+                )
+            }
+            Event::NoteAdded { player_index, note } => {
+                let player = self.get_player(player_index)?;
+                format!("Storyteller noted on {}: {}", player.name, note)
+            }
+            Event::NoteEdited { player_index, note } => {
+                let player = self.get_player(player_index)?;
+                format!("Storyteller edited a note on {}: {}", player.name, note)
+            }
+            Event::NoteRemoved { player_index, note } => {
+                let player = self.get_player(player_index)?;
+                format!("Storyteller removed a note on {}: {}", player.name, note)
+            }
+            Event::RoleClaimed {
+                player_index,
+                claim,
+            } => {
+                let player = self.get_player(player_index)?;
+                format!("{}({}) claimed {}", player.name, player.role, claim)
+            }
+            Event::ClaimRemoved {
+                player_index,
+                claim,
+            } => {
+                let player = self.get_player(player_index)?;
+                format!(
+                    "Storyteller removed {}'s claim of {}",
+                    player.name, claim.role
+                )
+            }
+            Event::PlayerReplaced {
+                player_index,
+                old_name,
+                new_name,
+            } => {
+                let player = self.get_player(player_index)?;
+                format!(
+                    "{} took over {}'s seat as the {}",
+                    new_name, old_name, player.role
+                )
+            }
+            Event::PlayerLeft(player_index) => {
+                let player = self.get_player(player_index)?;
+                format!("{}({}) left the game", player.name, player.role)
+            }
+            Event::Exile(player_index) => {
+                let player = self.get_player(player_index)?;
+                format!("{}({}) was exiled", player.name, player.role)
+            }
+            Event::DawnAnnounced { deaths } => {
+                if deaths.is_empty() {
+                    return Ok("Dawn: nobody died last night".to_string());
+                }
+                let names = deaths
+                    .iter()
+                    .map(|player_index| Ok(self.get_player(*player_index)?.name.clone()))
+                    .collect::<Result<Vec<_>, GameError>>()?
+                    .join(", ");
+                format!("Dawn: {} died last night", names)
+            }
+            Event::TimerStarted {
+                kind,
+                duration_secs,
+            } => format!("{} timer started for {}", kind, format_secs(duration_secs)),
+            Event::TimerExtended { kind, secs } => {
+                format!("{} timer extended by {}", kind, format_secs(secs))
+            }
+            Event::TimerExpired(kind) => format!("{} timer ran out", kind),
+            Event::WakeSkipped {
+                player_index,
+                skipped,
+            } => {
+                let player = self.get_player(player_index)?;
+                let action = if skipped { "skipped" } else { "unskipped" };
+                format!(
+                    "Storyteller {} waking {}({})",
+                    action, player.name, player.role
+                )
+            }
+            Event::TravellerMarked {
+                player_index,
+                traveller,
+            } => {
+                let player = self.get_player(player_index)?;
+                let marked = if traveller { "as" } else { "as no longer" };
+                format!("Storyteller marked {} {} a traveller", player.name, marked)
+            }
+            Event::WakeMoved {
+                player_index,
+                from,
+                to,
+            } => {
+                let player = self.get_player(player_index)?;
+                format!(
+                    "Storyteller moved waking {}({}) from #{} to #{}",
+                    player.name,
+                    player.role,
+                    from + 1,
+                    to + 1
+                )
+            }
+            Event::WakeAdded {
+                player_index,
+                reason,
+            } => {
+                let player = self.get_player(player_index)?;
+                format!(
+                    "Storyteller added a wake for {}({}): {}",
+                    player.name, player.role, reason
+                )
+            }
+            Event::ReminderAdded {
+                target_player_index,
+                status_effect,
+            } => {
+                let target_player = self.get_player(target_player_index)?;
+                format!(
+                    "Storyteller placed a {} reminder on {}({})",
+                    status_effect, target_player.name, target_player.role
+                )
+            }
+            Event::ReminderRemoved {
+                target_player_index,
+                status_effect,
+            } => {
+                let target_player = self.get_player(target_player_index)?;
+                format!(
+                    "Storyteller removed the {} reminder from {}({})",
+                    status_effect, target_player.name, target_player.role
+                )
+            }
+            Event::InfoLearned { player_index, info } => {
+                let player = self.get_player(player_index)?;
+                format!("{}({}) was shown: {}", player.name, player.role, info)
+            }
+            Event::GameOver(winner) => format!("{} wins", winner),
+        };
+        return Ok(description);
     }
 
     pub(crate) fn nominate_player(
         &mut self,
         source_player_index: PlayerIndex,
         target_player_index: PlayerIndex,
-    ) {
-        // target_player.nominate(source_player_index, target_player_index, self);
-        let mut state = self;
-        let mut nomination_listeners = std::mem::take(&mut state.nomination_listeners);
-        for listener in nomination_listeners.iter_mut() {
-            if state.players[listener.state.source_player_index]
-                .status_effects
-                .iter_mut()
-                .any(|se| matches!(se.status_type, StatusType::Poisoned | StatusType::Drunk))
-            {
-                continue;
-            }
-            state = listener.call(
-                state,
-                log::Nomination {
-                    nominator_player_index: source_player_index,
-                    target_player_index,
-                },
-            );
+    ) -> Result<(), GameError> {
+        if self.step != Step::Day(DayStep::Nominations) {
+            return Err(GameError::IllegalAction {
+                action: "nominate",
+                step: self.step,
+            });
         }
-        state.nomination_listeners = nomination_listeners;
+        self.get_player(source_player_index)?;
+        self.get_player(target_player_index)?;
+
+        // target_player.nominate(source_player_index, target_player_index, self);
+        self.notify_listeners(
+            |state| &mut state.nomination_listeners,
+            log::Nomination {
+                nominator_player_index: source_player_index,
+                target_player_index,
+            },
+        );
 
-        state.log.log_event(Event::Nomination {
+        self.log_event(Event::Nomination {
             nominator_player_index: source_player_index,
             target_player_index,
         });
+        return Ok(());
     }
 
-    pub(crate) fn execute_player(&mut self, target_player_index: PlayerIndex) {
-        let target_player = self.get_player_mut(target_player_index);
+    pub(crate) fn execute_player(
+        &mut self,
+        target_player_index: PlayerIndex,
+    ) -> Result<(), GameError> {
+        if !matches!(self.step, Step::Day(_)) {
+            return Err(GameError::IllegalAction {
+                action: "execute a player",
+                step: self.step,
+            });
+        }
+        self.get_player(target_player_index)?;
+
+        self.handle_execution(target_player_index)?;
+
+        // After a player is executed, the day ends
+        return self.skip_to_day_step(DayStep::Dusk);
+    }
+
+    fn handle_execution(&mut self, target_player_index: PlayerIndex) -> Result<(), GameError> {
+        let target_player = self.get_player_mut(target_player_index)?;
 
         // FIX: Make this work properly again and prevent defaults
         // target_player.execute();
         target_player.dead = true;
-        // TODO: Call execute listeners
+        self.log_event(Event::Execution(target_player_index));
+        // Execution listeners need to be called before the death cleans them up
         // Resolve their change requests (right away if possible)
+        self.notify_listeners(
+            |state| &mut state.execution_listeners,
+            log::Execution(target_player_index),
+        );
         self.handle_death(target_player_index);
-        self.log.log_event(Event::Execution(target_player_index));
+        return Ok(());
+    }
+
+    /// Someone else takes over a seat. Everything about the seat stays the same, only the name
+    /// changes
+    pub(crate) fn replace_player(
+        &mut self,
+        player_index: PlayerIndex,
+        new_name: String,
+    ) -> Result<(), GameError> {
+        self.get_player(player_index)?;
+        let old_name = std::mem::replace(
+            &mut self.get_player_mut(player_index)?.name,
+            new_name.clone(),
+        );
+        self.log_event(Event::PlayerReplaced {
+            player_index,
+            old_name,
+            new_name,
+        });
+        return Ok(());
+    }
+
+    /// The person in a seat leaves the game without anyone taking over. They die without being
+    /// executed, and their seat is skipped for neighbours from then on
+    pub(crate) fn player_leaves(&mut self, player_index: PlayerIndex) -> Result<(), GameError> {
+        if self.get_player(player_index)?.left {
+            return Ok(());
+        }
+
+        self.get_player_mut(player_index)?.left = true;
+        self.log_event(Event::PlayerLeft(player_index));
+        self.night_plan.remove_player(player_index);
+        if !self.get_player(player_index)?.dead {
+            self.get_player_mut(player_index)?.dead = true;
+            self.handle_death(player_index);
+        }
+        return Ok(());
+    }
+
+    /// Marks whether the player is a traveller. Travellers don't have abilities yet, so this
+    /// only decides who can be exiled and what the town square shows
+    pub(crate) fn set_traveller(
+        &mut self,
+        player_index: PlayerIndex,
+        traveller: bool,
+    ) -> Result<(), GameError> {
+        if self.get_player(player_index)?.traveller == traveller {
+            return Ok(());
+        }
 
-        // After a player is executed, immediately go to night
-        self.next_step();
+        self.get_player_mut(player_index)?.traveller = traveller;
+        self.log_event(Event::TravellerMarked {
+            player_index,
+            traveller,
+        });
+        return Ok(());
+    }
+
+    /// The town exiles a traveller. Unlike an execution it doesn't end the day or trigger
+    /// anything that happens on execution
+    pub(crate) fn exile_player(&mut self, player_index: PlayerIndex) -> Result<(), GameError> {
+        if !matches!(self.step, Step::Day(_)) {
+            return Err(GameError::IllegalAction {
+                action: "exile a player",
+                step: self.step,
+            });
+        }
+        if !self.get_player(player_index)?.traveller {
+            return Err(GameError::NotTraveller(player_index));
+        }
+        if self.get_player(player_index)?.dead {
+            return Ok(());
+        }
+
+        self.get_player_mut(player_index)?.dead = true;
+        self.log_event(Event::Exile(player_index));
+        self.handle_death(player_index);
+        return Ok(());
     }
 
     pub(crate) fn get_day_active(&self) -> Vec<PlayerIndex> {
@@ -579,236 +1263,249 @@ impl State {
 
     pub(crate) fn day_ability(&self, player_index: PlayerIndex) -> Option<ChangeRequestBuilder> {
         self.get_player(player_index)
+            .ok()?
             .day_ability(player_index, self)
     }
 
-    pub(crate) fn get_next_active_night_one(
-        &self,
-        previous_player: Option<PlayerIndex>,
-    ) -> Option<PlayerIndex> {
-        let prev_player_order = {
-            match previous_player {
-                Some(player_index) => self.get_player(player_index).role.night_one_order(),
-                None => None,
+    /// Where the player acts during a step, if they act at all. The role only says whether it
+    /// wakes, the order itself comes from the script
+    pub(crate) fn wake_order(&self, player_index: PlayerIndex, step: Step) -> Option<usize> {
+        let role = &self.get_player(player_index).ok()?.role;
+        match step {
+            // The Drunk is set up as the Drunk, not as the role they think they are
+            Step::Setup => role
+                .wakes_during_setup()
+                .then(|| self.script.setup_order(role.to_role_name())),
+            Step::NightOne if role.wakes_night_one() => {
+                self.script.first_night_order(role.wakes_as())
             }
-        };
-        let mut next_player: Option<(PlayerIndex, usize)> = None;
-
-        let players = self.get_players();
-        for (player_index, player) in players.iter().enumerate() {
-            let order = player.role.night_one_order();
-            // Check that the player acts at night
-            let order = match order {
-                Some(order) => order,
-                None => continue,
-            };
-            if let Some(prev_player_order) = prev_player_order {
-                if order < prev_player_order {
-                    continue;
-                } else if order == prev_player_order + 1 {
-                    return Some(player_index);
-                }
-                // If there's a duplicate, return the next player with a higher player index than the
-                // current player that has the same role
-                else if order == prev_player_order {
-                    let previous_player_index = match previous_player {
-                        Some(i) => i,
-                        None => panic!("Next player has order of 0, should be impossible"), // Should never happen
-                    };
-                    if player_index <= previous_player_index {
-                        continue;
-                    }
-                    return Some(player_index);
-                }
+            Step::Night if role.wakes_other_nights() => {
+                self.script.other_night_order(role.wakes_as())
             }
-            let next_player_info = match next_player {
-                Some(info) => info,
-                None => {
-                    next_player = Some((player_index, order));
-                    continue;
-                }
-            };
-            if order > next_player_info.1 {
-                continue;
-            };
-            // Getting to this point means order is more than the previous_player but less than the
-            // current next_player
-            next_player = Some((player_index, order));
+            _ => None,
         }
+    }
+}
 
-        match next_player {
-            Some(player) => return Some(player.0),
-            None => return None,
+/// Status Effects can either be visual (just for the storyteller) and do nothing or they can
+/// overwrite player behaviors
+impl State {
+    /// Places a reminder token on a player by hand. The token's source is whichever player has
+    /// the role it belongs to, so it is cleaned up with the rest of that player's statuses
+    pub(crate) fn add_reminder(
+        &mut self,
+        target_player_index: PlayerIndex,
+        status_type: StatusType,
+        role: Option<RoleNames>,
+        cleanup_phase: Option<CleanupPhase>,
+        affects_behaviour: bool,
+    ) -> Result<(), GameError> {
+        self.get_player(target_player_index)?;
+        let source_player_index = role
+            .and_then(|role| {
+                self.players
+                    .iter()
+                    .position(|player| player.role.to_role_name() == role)
+            })
+            .unwrap_or(target_player_index);
+
+        let status_effect = StatusEffect::new_reminder(
+            status_type,
+            source_player_index,
+            cleanup_phase,
+            affects_behaviour,
+        );
+        self.get_player_mut(target_player_index)?
+            .add_status(status_effect.clone());
+        self.log_event(Event::ReminderAdded {
+            target_player_index,
+            status_effect,
+        });
+        return Ok(());
+    }
+
+    /// Removes the status at status_index from a player by hand. Works on any status, not just
+    /// the ones placed by the storyteller
+    pub(crate) fn remove_reminder(
+        &mut self,
+        target_player_index: PlayerIndex,
+        status_index: usize,
+    ) -> Result<(), GameError> {
+        self.get_player(target_player_index)?;
+        let statuses = &mut self.get_player_mut(target_player_index)?.status_effects;
+        if status_index >= statuses.len() {
+            return Err(GameError::NoSuchEntry {
+                player_index: target_player_index,
+                kind: "reminder",
+                index: status_index,
+            });
         }
+
+        let status_effect = statuses.remove(status_index);
+        self.log_event(Event::ReminderRemoved {
+            target_player_index,
+            status_effect,
+        });
+        return Ok(());
     }
 
-    pub(crate) fn get_next_active_night(
-        &self,
-        previous_player: Option<PlayerIndex>,
-    ) -> Option<PlayerIndex> {
-        let prev_player_order = {
-            match previous_player {
-                Some(player_index) => self.get_player(player_index).role.night_order(),
-                None => None,
-            }
+    /// Writes a storyteller note on a player, stamped with the current day and step
+    pub(crate) fn add_note(
+        &mut self,
+        player_index: PlayerIndex,
+        kind: NoteKind,
+        text: String,
+    ) -> Result<(), GameError> {
+        self.get_player(player_index)?;
+        let note = Note::new(text, kind, self.day_num, self.step);
+        self.get_player_mut(player_index)?.notes.push(note.clone());
+        self.log_event(Event::NoteAdded { player_index, note });
+        return Ok(());
+    }
+
+    /// Replaces the text of a note. The note keeps the time it was originally written
+    pub(crate) fn edit_note(
+        &mut self,
+        player_index: PlayerIndex,
+        note_index: usize,
+        text: String,
+    ) -> Result<(), GameError> {
+        self.get_player(player_index)?;
+        let Some(note) = self.get_player_mut(player_index)?.notes.get_mut(note_index) else {
+            return Err(GameError::NoSuchEntry {
+                player_index,
+                kind: "note",
+                index: note_index,
+            });
         };
-        let mut next_player: Option<(PlayerIndex, usize)> = None;
-
-        let players = self.get_players();
-        for (player_index, player) in players.iter().enumerate() {
-            let order = player.role.night_order();
-            // Check that the player acts at night
-            let order = match order {
-                Some(order) => order,
-                None => continue,
-            };
-            if let Some(prev_player_order) = prev_player_order {
-                if order < prev_player_order {
-                    continue;
-                }
-                // If there's a duplicate, return the next player with a higher player index than the
-                // current player that has the same role
-                else if order == prev_player_order {
-                    let previous_player_index = match previous_player {
-                        Some(i) => i,
-                        None => panic!("Next player has order of 0, should be impossible"), // Should never happen
-                    };
-                    if player_index <= previous_player_index {
-                        continue;
-                    }
-                    return Some(player_index);
-                } else if order == prev_player_order + 1 {
-                    return Some(player_index);
-                }
-            }
+        note.text = text;
 
-            // (player index, order)
-            let next_player_info = match next_player {
-                Some(info) => info,
-                None => {
-                    next_player = Some((player_index, order));
-                    continue;
-                }
-            };
-            if order >= next_player_info.1 {
-                continue;
-            };
-            // Getting to this point means order is more than the previous_player but less than the
-            // current next_player
-            next_player = Some((player_index, order));
-        }
+        let note = note.clone();
+        self.log_event(Event::NoteEdited { player_index, note });
+        return Ok(());
+    }
 
-        match next_player {
-            Some(player) => return Some(player.0),
-            None => return None,
+    pub(crate) fn remove_note(
+        &mut self,
+        player_index: PlayerIndex,
+        note_index: usize,
+    ) -> Result<(), GameError> {
+        self.get_player(player_index)?;
+        let notes = &mut self.get_player_mut(player_index)?.notes;
+        if note_index >= notes.len() {
+            return Err(GameError::NoSuchEntry {
+                player_index,
+                kind: "note",
+                index: note_index,
+            });
         }
+
+        let note = notes.remove(note_index);
+        self.log_event(Event::NoteRemoved { player_index, note });
+        return Ok(());
     }
 
-    fn get_role_order_night1(role: RoleNames) -> usize {
-        match role {
-            // Role::DUSK => 0,
-            // Role::Lordoftyphon => 1,
-            // Role::Kazali => 2,
-            // Role::Apprentice => 3,
-            // Role::Barista => 4,
-            // Role::Bureaucrat => 5,
-            // Role::Thief => 6,
-            // Role::Boffin => 7,
-            // Role::Philosopher => 8,
-            // Role::Alchemist => 9,
-            // Role::Poppygrower => 10,
-            // Role::Yaggababble => 11
-            // Role::Magician => 12,
-            // Role::MINION => 13, // TODO: Need to implement this shit
-            // Role::Snitch => 14,
-            // Role::Lunatic => 15,
-            // Role::Summoner => 16,
-            // Role::DEMON => 17, // TODO: Need to implement this shit
-            // Role::King => 18,
-            // Role::Sailor => 19,
-            // Role::Marionette => 20,
-            // Role::Engineer => 21,
-            // Role::Preacher => 22,
-            // Role::Lilmonsta => 23,
-            // Role::Lleech => 24,
-            // Role::Xaan => 25,
-            RoleNames::Poisoner => 26,
-            // Role::Widow => 27,
-            // Role::Courtier => 28,
-            // Role::Wizard => 29,
-            // Role::Snakecharmer => 30,
-            // Role::Godfather => 31,
-            // Role::Organgrinder => 32,
-            // Role::Devilsadvocate => 33,
-            // Role::Eviltwin => 34,
-            // Role::Witch => 35,
-            // Role::Cerenovus => 36,
-            // Role::Fearmonger => 37,
-            // Role::Harpy => 38,
-            // Role::Mezepheles => 39,
-            // Role::Pukka => 40,
-            // Role::Pixie => 41,
-            // Role::Huntsman => 42,
-            // Role::Damsel => 43,
-            // Role::Amnesiac => 44,
-            RoleNames::Washerwoman => 45,
-            RoleNames::Librarian => 46,
-            RoleNames::Investigator => 47,
-            RoleNames::Chef => 48,
-            RoleNames::Empath => 49,
-            RoleNames::Fortuneteller => 50,
-            RoleNames::Butler => 51,
-            // Role::Grandmother => 52,
-            // Role::Clockmaker => 53,
-            // Role::Dreamer => 54,
-            // Role::Seamstress => 55,
-            // Role::Steward => 56,
-            // Role::Knight => 57,
-            // Role::Noble => 58,
-            // Role::Balloonist => 59,
-            // Role::Shugenja => 60,
-            // Role::Villageidiot => 61,
-            // Role::Bountyhunter => 62,
-            // Role::Nightwatchman => 63,
-            // Role::Cultleader => 64,
-            RoleNames::Spy => 65,
-            // Role::Ogre => 66,
-            // Role::Highpriestess => 67,
-            // Role::General => 68,
-            // Role::Chambermaid => 69,
-            // Role::Mathematician => 70,
-            // Role::DAWN => 71, TODO: Figure out wtf this means
-            // Role::Leviathan => 72,
-            // Role::Vizier => 73
-            _ => 0,
-        }
+    /// Finds every note on every player matching the query
+    pub(crate) fn search_notes(&self, query: &str) -> Vec<(PlayerIndex, &Note)> {
+        self.players
+            .iter()
+            .enumerate()
+            .flat_map(|(player_index, player)| {
+                player
+                    .notes
+                    .iter()
+                    .filter(|note| note.matches(query))
+                    .map(move |note| (player_index, note))
+            })
+            .collect()
     }
-}
 
-/// Status Effects can either be visual (just for the storyteller) and do nothing or they can
-/// overwrite player behaviors
-impl State {
     pub(crate) fn cleanup_player_statuses(&mut self, source_player_index: PlayerIndex) {
         for player in self.players.iter_mut() {
             player.remove_players_statuses(source_player_index);
         }
     }
 
+    /// Removes the statuses a player's role placed, but not the ones the storyteller placed by
+    /// hand
+    pub(crate) fn cleanup_role_statuses(&mut self, source_player_index: PlayerIndex) {
+        for player in self.players.iter_mut() {
+            player.status_effects.retain(|status_effect| {
+                status_effect.manual || status_effect.source_player_index != source_player_index
+            });
+        }
+    }
+
+    /// Points the win condition at the living player whose death ends the game, or a dead one if
+    /// nobody alive has it
+    fn update_win_condition(&mut self) {
+        let win_conditions = || {
+            self.players
+                .iter()
+                .enumerate()
+                .filter(|(_, player)| player.role.is_win_condition())
+        };
+        self.win_cond_i = win_conditions()
+            .find(|(_, player)| !player.dead)
+            .or_else(|| win_conditions().next())
+            .map(|(player_index, _)| player_index);
+    }
+
     pub(crate) fn cleanup_statuses(&mut self, cleanup_phase: CleanupPhase) {
         for player in self.players.iter_mut() {
             player.cleanup_statuses(cleanup_phase);
         }
     }
 
+    /// Keeps what a droisoned player's ability used up when it was run on a copy of the state:
+    /// their role data, and their event listeners if the ability removed them
+    pub(crate) fn keep_ability_effects(&mut self, player_index: PlayerIndex, copy: &State) {
+        if let Ok(used) = copy.get_player(player_index)
+            && let Ok(player) = self.get_player_mut(player_index)
+        {
+            player.role.keep_ability_data(used.role.clone());
+        }
+
+        if self.has_event_listeners(player_index) && !copy.has_event_listeners(player_index) {
+            self.cleanup_event_listeners(player_index);
+        }
+    }
+
+    pub(crate) fn has_event_listeners(&self, player_index: PlayerIndex) -> bool {
+        let from_player = |source_player_index: PlayerIndex| source_player_index == player_index;
+        return self
+            .nomination_listeners
+            .iter()
+            .any(|listener| from_player(listener.state.source_player_index))
+            || self
+                .attempted_kill_listeners
+                .iter()
+                .any(|listener| from_player(listener.state.source_player_index))
+            || self
+                .death_listeners
+                .iter()
+                .any(|listener| from_player(listener.state.source_player_index))
+            || self
+                .execution_listeners
+                .iter()
+                .any(|listener| from_player(listener.state.source_player_index))
+            || self
+                .phase_listeners
+                .iter()
+                .any(|listener| from_player(listener.state.source_player_index));
+    }
+
     pub(crate) fn cleanup_event_listeners(&mut self, player_index: PlayerIndex) {
-        console_log(format!("Cleanup for the {}", self.get_player_mut(player_index).role).as_str());
-        console_log(format!("Event Listeners are: {:#?}", self.death_listeners).as_str());
         self.nomination_listeners
             .retain(|listener| listener.state.source_player_index != player_index);
         self.attempted_kill_listeners
             .retain(|listener| listener.state.source_player_index != player_index);
         self.death_listeners
             .retain(|listener| listener.state.source_player_index != player_index);
+        self.execution_listeners
+            .retain(|listener| listener.state.source_player_index != player_index);
+        self.phase_listeners
+            .retain(|listener| listener.state.source_player_index != player_index);
     }
 }
 
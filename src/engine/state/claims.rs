@@ -0,0 +1,150 @@
+use std::fmt::Display;
+
+use crate::engine::{
+    error::GameError,
+    player::roles::RoleNames,
+    state::{PlayerIndex, State, Step, log::Event},
+};
+
+/// A role a player said they were, either to the whole town or in a private chat
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Claim {
+    pub(crate) role: RoleNames,
+    pub(crate) public: bool,
+    pub(crate) day_num: usize,
+    pub(crate) step: Step,
+}
+
+impl Display for Claim {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let visibility = if self.public { "publicly" } else { "privately" };
+        let time = match self.step {
+            Step::Start | Step::Setup => "before the game".to_string(),
+            Step::Day(_) => format!("on day {}", self.day_num),
+            Step::NightOne | Step::Night => format!("on night {}", self.day_num),
+        };
+        write!(f, "{} {} {}", self.role, visibility, time)
+    }
+}
+
+impl State {
+    pub(crate) fn add_claim(
+        &mut self,
+        player_index: PlayerIndex,
+        role: RoleNames,
+        public: bool,
+    ) -> Result<(), GameError> {
+        let claim = Claim {
+            role,
+            public,
+            day_num: self.day_num,
+            step: self.step,
+        };
+        self.claims_mut(player_index)?.push(claim);
+        self.log_event(Event::RoleClaimed {
+            player_index,
+            claim,
+        });
+        return Ok(());
+    }
+
+    pub(crate) fn remove_claim(
+        &mut self,
+        player_index: PlayerIndex,
+        claim_index: usize,
+    ) -> Result<(), GameError> {
+        let claims = self.claims_mut(player_index)?;
+        if claim_index >= claims.len() {
+            return Err(GameError::NoSuchEntry {
+                player_index,
+                kind: "claim",
+                index: claim_index,
+            });
+        }
+
+        let claim = claims.remove(claim_index);
+        self.log_event(Event::ClaimRemoved {
+            player_index,
+            claim,
+        });
+        return Ok(());
+    }
+
+    /// Every claim the player has made, oldest first
+    pub(crate) fn get_claims(&self, player_index: PlayerIndex) -> Result<&Vec<Claim>, GameError> {
+        return self
+            .claims
+            .get(player_index)
+            .ok_or(GameError::NoSuchPlayer(player_index));
+    }
+
+    fn claims_mut(&mut self, player_index: PlayerIndex) -> Result<&mut Vec<Claim>, GameError> {
+        return self
+            .claims
+            .get_mut(player_index)
+            .ok_or(GameError::NoSuchPlayer(player_index));
+    }
+
+    /// The most recent claim the player has made
+    pub(crate) fn current_claim(&self, player_index: PlayerIndex) -> Option<&Claim> {
+        self.claims.get(player_index)?.last()
+    }
+
+    /// The most recent claim the player has made to the whole town
+    pub(crate) fn current_public_claim(&self, player_index: PlayerIndex) -> Option<&Claim> {
+        self.claims
+            .get(player_index)?
+            .iter()
+            .rev()
+            .find(|claim| claim.public)
+    }
+
+    /// Players whose current public claim is the given role
+    pub(crate) fn players_claiming(&self, role: RoleNames) -> Vec<PlayerIndex> {
+        (0..self.claims.len())
+            .filter(|player_index| {
+                self.current_public_claim(*player_index)
+                    .is_some_and(|claim| claim.role == role)
+            })
+            .collect()
+    }
+
+    /// Describes what the player claimed compared to what they actually were, e.g. "claimed
+    /// Chef, was Imp"
+    pub(crate) fn claim_summary(&self, player_index: PlayerIndex) -> Option<String> {
+        let claim = self.current_claim(player_index)?;
+        let role = self.get_player(player_index).ok()?.role.to_role_name();
+        Some(format!("claimed {}, was {}", claim.role, role))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{player::roles::RoleNames::*, scenario::Scenario};
+
+    use super::*;
+
+    #[test]
+    fn test_claims() {
+        let mut scenario = Scenario::new(&[
+            ("Alice", Chef),
+            ("Bob", Virgin),
+            ("Carol", Soldier),
+            ("Dan", Spy),
+            ("Eve", Imp),
+        ]);
+        let eve = scenario.index("Eve");
+        let state = &mut scenario.state;
+
+        state.add_claim(eve, Chef, false).unwrap();
+        state.add_claim(eve, Soldier, true).unwrap();
+        state.add_claim(eve, Chef, false).unwrap();
+
+        assert_eq!(state.get_claims(eve).unwrap().len(), 3);
+        assert_eq!(state.get_claims(9), Err(GameError::NoSuchPlayer(9)));
+        assert_eq!(state.current_claim(9), None);
+        assert_eq!(state.current_public_claim(eve).unwrap().role, Soldier);
+        assert_eq!(state.players_claiming(Soldier), vec![eve]);
+        assert_eq!(state.claim_summary(eve).unwrap(), "claimed Chef, was Imp");
+    }
+}
@@ -0,0 +1,128 @@
+use crate::engine::state::{DayStep, PlayerIndex, State, Step, log::Event};
+
+/// What happened during the night that the town finds out about at dawn
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct DawnReport {
+    /// Players who died during the night, in seat order
+    pub(crate) deaths: Vec<PlayerIndex>,
+    /// Players someone tried to kill who survived. Only the storyteller sees these
+    pub(crate) failed_kills: Vec<PlayerIndex>,
+    pub(crate) announced: bool,
+}
+
+impl State {
+    /// Collects the deaths and failed kills from the night that just ended. Has to be called
+    /// before the log moves on to the day
+    pub(crate) fn build_dawn_report(&self) -> DawnReport {
+        let Some(night) = self.log.day_phases.last() else {
+            return DawnReport::default();
+        };
+
+        let mut deaths = vec![];
+        let mut failed_kills = vec![];
+        for event in night.log.iter() {
+            match event {
+                Event::Death(player_index) => deaths.push(*player_index),
+                Event::AttemptedKill {
+                    target_player_index,
+                    ..
+                } => failed_kills.push(*target_player_index),
+                _ => (),
+            }
+        }
+
+        // Storyteller corrections during the night could have brought someone back
+        deaths.retain(|player_index| self.get_player(*player_index).is_ok_and(|p| p.dead));
+        failed_kills.retain(|player_index| !self.get_player(*player_index).is_ok_and(|p| p.dead));
+        for list in [&mut deaths, &mut failed_kills] {
+            list.sort();
+            list.dedup();
+        }
+
+        return DawnReport {
+            deaths,
+            failed_kills,
+            announced: false,
+        };
+    }
+
+    /// Tells the town who died in the night. Does nothing if it was already announced
+    pub(crate) fn announce_dawn(&mut self) {
+        let Some(dawn_report) = self.dawn_report.as_mut() else {
+            return;
+        };
+        if dawn_report.announced {
+            return;
+        }
+
+        dawn_report.announced = true;
+        let deaths = dawn_report.deaths.clone();
+        self.log_event(Event::DawnAnnounced { deaths });
+    }
+
+    /// Whether a player's death hasn't been announced to the town yet
+    pub(crate) fn death_hidden(&self, player_index: PlayerIndex) -> bool {
+        if !self.get_player(player_index).is_ok_and(|p| p.dead) {
+            return false;
+        }
+
+        match self.step {
+            Step::NightOne | Step::Night => self
+                .log
+                .day_phases
+                .last()
+                .is_some_and(|night| night.log.contains(&Event::Death(player_index))),
+            Step::Day(DayStep::Dawn) => self.dawn_report.as_ref().is_some_and(|dawn_report| {
+                !dawn_report.announced && dawn_report.deaths.contains(&player_index)
+            }),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{player::roles::RoleNames::*, scenario::Scenario};
+
+    use super::*;
+
+    #[test]
+    fn test_dawn_report() {
+        let mut scenario = Scenario::new(&[
+            ("Alice", Chef),
+            ("Bob", Virgin),
+            ("Carol", Soldier),
+            ("Dan", Spy),
+            ("Eve", Imp),
+        ]);
+        let (bob, dan) = (scenario.index("Bob"), scenario.index("Dan"));
+        let public_dead = |scenario: &Scenario, player_index: PlayerIndex| {
+            scenario.state.public_state().players[player_index].dead
+        };
+
+        scenario.advance_to(Step::NightOne);
+        scenario.state.set_dead(dan, true).unwrap();
+        scenario.state.set_dead(bob, true).unwrap();
+        assert!(!public_dead(&scenario, bob));
+
+        scenario.next_step();
+        assert_eq!(scenario.state.step, Step::Day(DayStep::Dawn));
+        let dawn_report = scenario.state.dawn_report.clone().unwrap();
+        assert_eq!(dawn_report.deaths, vec![bob, dan]);
+        assert!(!public_dead(&scenario, dan));
+
+        scenario.state.announce_dawn();
+        assert!(public_dead(&scenario, bob));
+        assert!(public_dead(&scenario, dan));
+        assert!(
+            scenario
+                .state
+                .log
+                .search_current_phase(|event| match event {
+                    Event::DawnAnnounced { .. } => Some(event),
+                    _ => None,
+                })
+                .is_ok()
+        );
+    }
+}
@@ -1,15 +1,29 @@
-use std::fmt::Display;
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
-use crate::engine::state::Step;
+use crate::engine::{
+    player::{Alignment, notes::Note, roles::RoleNames},
+    state::{DayStep, GameMode, Step, claims::Claim, timer::TimerKind},
+};
 
 use super::{PlayerIndex, status_effects::StatusEffect};
 // -- Logging --
 
+/// Tells logged events apart, even identical ones. Never reused, so an event that was rolled
+/// back and logged again is a different event
+pub(crate) type EventId = usize;
+
+static NEXT_EVENT_ID: AtomicUsize = AtomicUsize::new(0);
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct DayPhaseLog {
     pub(crate) day_phase: Step,
     pub(crate) log: Vec<Event>,
     pub(crate) day_num: usize,
+    /// The id of each event in log
+    event_ids: Vec<EventId>,
 }
 
 // #[derive(Clone)]
@@ -30,9 +44,10 @@ pub struct Log {
 }
 
 impl Log {
+    /// Starts with the Setup phase, so events logged before the first night aren't dropped
     pub fn new() -> Self {
         Self {
-            day_phases: vec![],
+            day_phases: vec![DayPhaseLog::new(Step::Setup, 0)],
             day_num: 0,
         }
     }
@@ -67,50 +82,61 @@ impl Log {
         return Err(SearchError::EventNotFound);
     }
 
+    /// Returns the latest event of this type from the most recent day that has ended or is
+    /// going on, across all of its parts
+    pub fn search_previous_day<F>(&self, search_func: F) -> Result<&Event, SearchError>
+    where
+        F: Fn(&Event) -> Option<&Event>,
+    {
+        let mut day = self.latest_day().peekable();
+        if day.peek().is_none() {
+            return Err(SearchError::InvalidDayNum);
+        }
+        return day
+            .find_map(|day_phase| day_phase.search(&search_func))
+            .ok_or(SearchError::EventNotFound);
+    }
+
+    /// Every part of the most recent day, latest first
+    pub(crate) fn latest_day(&self) -> impl Iterator<Item = &DayPhaseLog> {
+        self.day_phases
+            .iter()
+            .rev()
+            .skip_while(|day_phase| !matches!(day_phase.day_phase, Step::Day(_)))
+            .take_while(|day_phase| matches!(day_phase.day_phase, Step::Day(_)))
+    }
+
     pub fn next_phase(&mut self) {
         // Check the latest day_phase
         match self.get_latest_phase() {
             None => {
-                self.day_phases.push(DayPhaseLog {
-                    day_phase: Step::Setup,
-                    log: vec![],
-                    day_num: self.day_num,
-                });
+                self.day_phases
+                    .push(DayPhaseLog::new(Step::Setup, self.day_num));
             }
             Some(phase) => match phase.day_phase {
                 Step::Setup => {
                     // Create night one in log
-                    let night_1 = DayPhaseLog {
-                        day_phase: Step::NightOne,
-                        log: vec![],
-                        day_num: 1,
-                    };
+                    let night_1 = DayPhaseLog::new(Step::NightOne, 1);
                     self.day_num = 1;
                     self.day_phases.push(night_1);
                 }
-                Step::Day => {
-                    self.day_phases.push(DayPhaseLog {
-                        day_phase: Step::Night,
-                        log: vec![],
-                        day_num: self.day_num,
-                    });
+                Step::Day(day_step) => {
+                    let day_phase = match day_step.next() {
+                        Some(next_day_step) => Step::Day(next_day_step),
+                        None => Step::Night,
+                    };
+                    self.day_phases
+                        .push(DayPhaseLog::new(day_phase, self.day_num));
                 }
                 Step::NightOne => {
-                    // Only time we should increment day num
-                    self.day_phases.push(DayPhaseLog {
-                        day_phase: Step::Day,
-                        log: vec![],
-                        day_num: self.day_num,
-                    });
+                    self.day_phases
+                        .push(DayPhaseLog::new(Step::Day(DayStep::Dawn), self.day_num));
                 }
                 Step::Night => {
                     // Only time we should increment day num
                     self.day_num += 1;
-                    self.day_phases.push(DayPhaseLog {
-                        day_phase: Step::Day,
-                        log: vec![],
-                        day_num: self.day_num,
-                    });
+                    self.day_phases
+                        .push(DayPhaseLog::new(Step::Day(DayStep::Dawn), self.day_num));
                 }
                 Step::Start => panic!("Log should never have Start Phase"),
             },
@@ -119,28 +145,78 @@ impl Log {
 
     fn get_previous_phase(&self) -> Option<&DayPhaseLog> {
         let len = self.day_phases.len();
-        self.day_phases.get(len - 2)
+        self.day_phases.get(len.checked_sub(2)?)
     }
 
     fn get_mut_previous_phase(&mut self) -> Option<&mut DayPhaseLog> {
         let len = self.day_phases.len();
-        self.day_phases.get_mut(len - 2)
+        self.day_phases.get_mut(len.checked_sub(2)?)
     }
 
     fn get_latest_phase(&self) -> Option<&DayPhaseLog> {
         self.day_phases.last()
     }
 
-    fn get_mut_latest_phase(&mut self) -> &mut DayPhaseLog {
-        // WARN: This should never be empty anyway, but do fix this implementation to not panic if
-        // it is
-        assert!(!self.day_phases.is_empty());
-        self.day_phases.last_mut().unwrap()
+    fn get_mut_latest_phase(&mut self) -> Option<&mut DayPhaseLog> {
+        self.day_phases.last_mut()
+    }
+
+    /// Logs the event in the latest phase and returns its id
+    pub fn log_event(&mut self, event: Event) -> Option<EventId> {
+        return self
+            .get_mut_latest_phase()
+            .map(|latest_phase| latest_phase.log(event));
+    }
+
+    /// Every event with its id, oldest first
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (&DayPhaseLog, EventId, &Event)> {
+        self.day_phases.iter().flat_map(|day_phase| {
+            day_phase
+                .log
+                .iter()
+                .zip(day_phase.event_ids.iter())
+                .map(move |(event, event_id)| (day_phase, *event_id, event))
+        })
     }
 
-    pub fn log_event(&mut self, event: Event) {
-        let latest_phase = self.get_mut_latest_phase();
-        latest_phase.log(event);
+    /// The id of the newest event, None if nothing has been logged yet
+    pub(crate) fn last_event_id(&self) -> Option<EventId> {
+        self.day_phases
+            .iter()
+            .rev()
+            .find_map(|day_phase| day_phase.event_ids.last().copied())
+    }
+
+    /// The ids of every event logged after the given one
+    pub(crate) fn event_ids_after(&self, event_id: Option<EventId>) -> Vec<EventId> {
+        self.entries()
+            .map(|(_, id, _)| id)
+            .filter(|id| Some(*id) > event_id)
+            .collect()
+    }
+
+    /// Takes the events out of the log
+    pub(crate) fn remove_events(&mut self, event_ids: &[EventId]) {
+        for day_phase in self.day_phases.iter_mut() {
+            let mut keep = day_phase
+                .event_ids
+                .iter()
+                .map(|event_id| !event_ids.contains(event_id));
+            day_phase.log.retain(|_| keep.next().unwrap());
+            day_phase
+                .event_ids
+                .retain(|event_id| !event_ids.contains(event_id));
+        }
+    }
+
+    /// Updates every player index in the log after players have changed seats. seat_map maps
+    /// old indices to new ones
+    pub(crate) fn remap_players(&mut self, seat_map: &[PlayerIndex]) {
+        for day_phase in self.day_phases.iter_mut() {
+            for event in day_phase.log.iter_mut() {
+                event.remap_players(seat_map);
+            }
+        }
     }
 
     // fn search(&self, day_num: usize, event_type: EventType) -> Result<Event, SearchError> {
@@ -149,8 +225,29 @@ impl Log {
 }
 
 impl DayPhaseLog {
-    fn log(&mut self, event: Event) {
+    /// e.g. "Night 2" or "Day 1 Nominations"
+    pub(crate) fn title(&self) -> String {
+        match self.day_phase {
+            Step::Start | Step::Setup => "Setup".to_string(),
+            Step::NightOne | Step::Night => format!("Night {}", self.day_num),
+            Step::Day(day_step) => format!("Day {} {}", self.day_num, day_step),
+        }
+    }
+
+    fn new(day_phase: Step, day_num: usize) -> Self {
+        Self {
+            day_phase,
+            log: vec![],
+            day_num,
+            event_ids: vec![],
+        }
+    }
+
+    fn log(&mut self, event: Event) -> EventId {
+        let event_id = NEXT_EVENT_ID.fetch_add(1, Ordering::Relaxed);
         self.log.push(event);
+        self.event_ids.push(event_id);
+        return event_id;
     }
 
     fn search<F>(&self, func: F) -> Option<&Event>
@@ -193,7 +290,186 @@ pub enum Event {
         target_player_index: PlayerIndex,
         status_effect: StatusEffect,
     },
-    InfoLearned(String),
+    AbilityMalfunction {
+        player_index: PlayerIndex,
+        reason: String,
+    },
+    /// A player went back on the role they had to be mad about
+    MadnessBroken {
+        player_index: PlayerIndex,
+        role: RoleNames,
+        by: RoleNames,
+        day_num: usize,
+    },
+    /// A droisoned player was given a different answer than their ability would have
+    FalseInformation {
+        player_index: PlayerIndex,
+        true_info: String,
+        given_info: String,
+    },
+    // Storyteller Events
+    ModeChanged(GameMode),
+    Revived(PlayerIndex),
+    RoleChanged {
+        player_index: PlayerIndex,
+        old_role: RoleNames,
+        new_role: RoleNames,
+    },
+    AlignmentChanged {
+        player_index: PlayerIndex,
+        alignment: Alignment,
+    },
+    ReminderAdded {
+        target_player_index: PlayerIndex,
+        status_effect: StatusEffect,
+    },
+    ReminderRemoved {
+        target_player_index: PlayerIndex,
+        status_effect: StatusEffect,
+    },
+    RoleClaimed {
+        player_index: PlayerIndex,
+        claim: Claim,
+    },
+    ClaimRemoved {
+        player_index: PlayerIndex,
+        claim: Claim,
+    },
+    PlayerReplaced {
+        player_index: PlayerIndex,
+        old_name: String,
+        new_name: String,
+    },
+    PlayerLeft(PlayerIndex),
+    TravellerMarked {
+        player_index: PlayerIndex,
+        traveller: bool,
+    },
+    /// A traveller was exiled by the town
+    Exile(PlayerIndex),
+    /// The storyteller told the town who died in the night
+    DawnAnnounced {
+        deaths: Vec<PlayerIndex>,
+    },
+    TimerStarted {
+        kind: TimerKind,
+        duration_secs: u64,
+    },
+    TimerExtended {
+        kind: TimerKind,
+        secs: u64,
+    },
+    TimerExpired(TimerKind),
+    WakeSkipped {
+        player_index: PlayerIndex,
+        skipped: bool,
+    },
+    WakeAdded {
+        player_index: PlayerIndex,
+        reason: String,
+    },
+    /// A wake was moved to another spot in tonight's plan
+    WakeMoved {
+        player_index: PlayerIndex,
+        from: usize,
+        to: usize,
+    },
+    NoteAdded {
+        player_index: PlayerIndex,
+        note: Note,
+    },
+    NoteEdited {
+        player_index: PlayerIndex,
+        note: Note,
+    },
+    NoteRemoved {
+        player_index: PlayerIndex,
+        note: Note,
+    },
+    /// Information the storyteller showed a player
+    InfoLearned {
+        player_index: PlayerIndex,
+        info: String,
+    },
+    GameOver(Alignment),
+}
+
+impl Event {
+    fn remap_players(&mut self, seat_map: &[PlayerIndex]) {
+        match self {
+            Event::Nomination {
+                nominator_player_index,
+                target_player_index,
+            } => {
+                *nominator_player_index = seat_map[*nominator_player_index];
+                *target_player_index = seat_map[*target_player_index];
+            }
+            Event::AttemptedKill {
+                attacking_player_index: source_player_index,
+                target_player_index,
+            }
+            | Event::StatusApplied {
+                source_player_index,
+                target_player_index,
+                ..
+            } => {
+                *source_player_index = seat_map[*source_player_index];
+                *target_player_index = seat_map[*target_player_index];
+            }
+            Event::Voting {
+                target_player_index: player_index,
+                ..
+            }
+            | Event::Execution(player_index)
+            | Event::Death(player_index)
+            | Event::Revived(player_index)
+            | Event::PlayerLeft(player_index)
+            | Event::TravellerMarked { player_index, .. }
+            | Event::Exile(player_index)
+            | Event::PlayerReplaced { player_index, .. }
+            | Event::AbilityMalfunction { player_index, .. }
+            | Event::FalseInformation { player_index, .. }
+            | Event::MadnessBroken { player_index, .. }
+            | Event::RoleChanged { player_index, .. }
+            | Event::AlignmentChanged { player_index, .. }
+            | Event::RoleClaimed { player_index, .. }
+            | Event::ClaimRemoved { player_index, .. }
+            | Event::WakeSkipped { player_index, .. }
+            | Event::WakeAdded { player_index, .. }
+            | Event::WakeMoved { player_index, .. }
+            | Event::NoteAdded { player_index, .. }
+            | Event::NoteEdited { player_index, .. }
+            | Event::NoteRemoved { player_index, .. }
+            | Event::InfoLearned { player_index, .. } => {
+                *player_index = seat_map[*player_index];
+            }
+            Event::ReminderAdded {
+                target_player_index,
+                status_effect,
+            }
+            | Event::ReminderRemoved {
+                target_player_index,
+                status_effect,
+            } => {
+                *target_player_index = seat_map[*target_player_index];
+                status_effect.source_player_index = seat_map[status_effect.source_player_index];
+            }
+            Event::DawnAnnounced { deaths } => {
+                for player_index in deaths.iter_mut() {
+                    *player_index = seat_map[*player_index];
+                }
+            }
+            Event::ModeChanged(_)
+            | Event::GameOver(_)
+            | Event::TimerStarted { .. }
+            | Event::TimerExtended { .. }
+            | Event::TimerExpired(_) => (),
+        }
+
+        if let Event::StatusApplied { status_effect, .. } = self {
+            status_effect.source_player_index = seat_map[status_effect.source_player_index];
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -207,7 +483,9 @@ pub struct Voting {
     pub target_player_index: PlayerIndex,
 }
 #[derive(Clone, Debug, PartialEq)]
-pub struct Execution(PlayerIndex);
+pub struct Execution(pub PlayerIndex);
+#[derive(Clone, Debug, PartialEq)]
+pub struct PhaseStart(pub Step);
 #[derive(Clone, Debug, PartialEq)]
 pub struct AttemptedKill {
     pub attacking_player_index: PlayerIndex,
@@ -272,7 +550,6 @@ mod tests {
     fn test_search_previous_phase() {
         let mut log = Log::new();
         log.next_phase();
-        log.next_phase();
         let execution_event = Event::Execution(2);
         log.log_event(execution_event.clone());
         log.next_phase();
@@ -286,4 +563,38 @@ mod tests {
             execution_event == *event.expect("No execution event was found in the previous phase")
         );
     }
+
+    #[test]
+    fn test_log_before_first_night() {
+        let mut log = Log::new();
+        log.log_event(Event::Execution(1));
+        assert_eq!(log.day_phases[0].day_phase, Step::Setup);
+        assert_eq!(log.day_phases[0].log, vec![Event::Execution(1)]);
+    }
+
+    #[test]
+    fn test_search_previous_day() {
+        let mut log = Log::new();
+        // Night one, then day one up to nominations
+        for _ in 0..4 {
+            log.next_phase();
+        }
+        assert_eq!(
+            log.day_phases.last().unwrap().day_phase,
+            Step::Day(DayStep::Nominations)
+        );
+        let execution_event = Event::Execution(2);
+        log.log_event(execution_event.clone());
+        // Execution, dusk, then night
+        for _ in 0..3 {
+            log.next_phase();
+        }
+        assert_eq!(log.day_phases.last().unwrap().day_phase, Step::Night);
+
+        let event = log.search_previous_day(|ev| match *ev {
+            Event::Execution(_) => Some(ev),
+            _ => None,
+        });
+        assert!(execution_event == *event.expect("No execution event was found yesterday"));
+    }
 }
@@ -0,0 +1,237 @@
+#![allow(clippy::needless_return)]
+
+use crate::engine::{
+    change_request::{
+        ChangeRequest, ChangeRequestBuilder, ChangeType, Constraint, StateChangeFuncPtr,
+    },
+    error::GameError,
+    player::roles::RoleNames,
+    state::{
+        PlayerIndex, State, Step,
+        log::Event,
+        status_effects::{CleanupPhase, StatusEffect, StatusType},
+    },
+};
+
+impl State {
+    /// Makes a player mad that they are a role, because of the ability of the role `by`. The
+    /// cleanup phase is how long the madness lasts, e.g. Dusk for the Cerenovus' "tomorrow"
+    pub(crate) fn make_mad(
+        &mut self,
+        player_index: PlayerIndex,
+        role: RoleNames,
+        by: RoleNames,
+        cleanup_phase: Option<CleanupPhase>,
+    ) -> Result<(), GameError> {
+        self.get_player(player_index)?;
+        let source_player_index = self
+            .players
+            .iter()
+            .position(|player| player.role.to_role_name() == by)
+            .unwrap_or(player_index);
+
+        let status_effect = StatusEffect::new(
+            StatusType::Mad { role, by },
+            source_player_index,
+            cleanup_phase,
+        );
+        self.get_player_mut(player_index)?
+            .add_status(status_effect.clone());
+        self.log_event(Event::StatusApplied {
+            source_player_index,
+            target_player_index: player_index,
+            status_effect,
+        });
+        return Ok(());
+    }
+
+    /// The roles a player has to be mad about, with the role that made them mad
+    pub(crate) fn madness(&self, player_index: PlayerIndex) -> Vec<(RoleNames, RoleNames)> {
+        let Ok(player) = self.get_player(player_index) else {
+            return vec![];
+        };
+        return player
+            .status_effects
+            .iter()
+            .filter_map(|status_effect| match status_effect.status_type {
+                StatusType::Mad { role, by } => Some((role, by)),
+                _ => None,
+            })
+            .collect();
+    }
+
+    /// The storyteller decided a player wasn't mad enough about being a role. Logs it and
+    /// queues whatever the role that made them mad does about it
+    pub(crate) fn break_madness(
+        &mut self,
+        player_index: PlayerIndex,
+        role: RoleNames,
+    ) -> Result<(), GameError> {
+        if !matches!(self.step, Step::Day(_)) {
+            return Err(GameError::IllegalAction {
+                action: "break madness",
+                step: self.step,
+            });
+        }
+        self.get_player(player_index)?;
+        let (_, by) = self
+            .madness(player_index)
+            .into_iter()
+            .find(|(mad_role, _)| *mad_role == role)
+            .ok_or(GameError::NotMad { player_index, role })?;
+
+        self.log_event(Event::MadnessBroken {
+            player_index,
+            role,
+            by,
+            day_num: self.day_num,
+        });
+        if let Some(consequence) = self.madness_consequence(player_index, role, by) {
+            self.change_request_queue.push_back(consequence);
+        }
+        return Ok(());
+    }
+
+    fn madness_consequence(
+        &self,
+        player_index: PlayerIndex,
+        role: RoleNames,
+        by: RoleNames,
+    ) -> Option<ChangeRequestBuilder> {
+        let name = &self.get_player(player_index).ok()?.name;
+        match by {
+            RoleNames::Cerenovus | RoleNames::Mutant => ChangeRequest::new_builder(
+                ChangeType::YesNo,
+                format!(
+                    "{} broke madness about being the {}. Execute them?",
+                    name, role
+                ),
+            )
+            .state_change_func(StateChangeFuncPtr::new(move |state, args| {
+                if args.extract_yes_no()? {
+                    state.execute_player(player_index)?;
+                }
+                Ok(())
+            }))
+            .into(),
+            RoleNames::Harpy => ChangeRequest::new_builder(
+                ChangeType::ChoosePlayers(2),
+                format!(
+                    "{} broke madness about being the {}. Choose who dies: them, the other player \
+                     the Harpy chose, or both",
+                    name, role
+                ),
+            )
+            .state_change_func(StateChangeFuncPtr::new(|state, args| {
+                for player_index in args.extract_player_indicies()? {
+                    state.set_dead(player_index, true)?;
+                }
+                Ok(())
+            }))
+            .constraint(Constraint::Count { min: 0, max: 2 })
+            .constraint(Constraint::Distinct)
+            .constraint(Constraint::Alive)
+            .into(),
+            RoleNames::Pixie => ChangeRequest::new_builder(
+                ChangeType::YesNo,
+                format!(
+                    "{} broke madness about being the {}. Do they lose the chance to gain its \
+                     ability?",
+                    name, role
+                ),
+            )
+            .state_change_func(StateChangeFuncPtr::new(move |state, args| {
+                if args.extract_yes_no()? {
+                    state
+                        .get_player_mut(player_index)?
+                        .status_effects
+                        .retain(|status_effect| {
+                            status_effect.status_type != StatusType::Mad { role, by }
+                        });
+                }
+                Ok(())
+            }))
+            .into(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{scenario::Scenario, state::DayStep};
+
+    use super::*;
+
+    fn town() -> Scenario {
+        return Scenario::new(&[
+            ("Alice", RoleNames::Chef),
+            ("Bob", RoleNames::Empath),
+            ("Carol", RoleNames::Soldier),
+            ("Dan", RoleNames::Poisoner),
+            ("Eve", RoleNames::Imp),
+        ]);
+    }
+
+    #[test]
+    fn test_cerenovus_madness() {
+        let mut scenario = town();
+        let bob = scenario.index("Bob");
+        scenario
+            .state
+            .make_mad(
+                bob,
+                RoleNames::Chef,
+                RoleNames::Cerenovus,
+                Some(CleanupPhase::Dusk),
+            )
+            .unwrap();
+        assert_eq!(
+            scenario.state.break_madness(bob, RoleNames::Chef),
+            Err(GameError::IllegalAction {
+                action: "break madness",
+                step: Step::Start
+            })
+        );
+
+        scenario.advance_to(Step::Day(DayStep::Discussion));
+        assert_eq!(
+            scenario.state.break_madness(bob, RoleNames::Empath),
+            Err(GameError::NotMad {
+                player_index: bob,
+                role: RoleNames::Empath
+            })
+        );
+        scenario.state.break_madness(bob, RoleNames::Chef).unwrap();
+        scenario
+            .answer_yes_no(true)
+            .assert_dead("Bob")
+            .assert_logged(Event::MadnessBroken {
+                player_index: bob,
+                role: RoleNames::Chef,
+                by: RoleNames::Cerenovus,
+                day_num: 1,
+            })
+            .assert_logged(Event::Execution(bob));
+    }
+
+    #[test]
+    fn test_harpy_madness() {
+        let mut scenario = town();
+        let alice = scenario.index("Alice");
+        scenario
+            .state
+            .make_mad(alice, RoleNames::Empath, RoleNames::Harpy, None)
+            .unwrap();
+        scenario.advance_to(Step::Day(DayStep::Discussion));
+        scenario
+            .state
+            .break_madness(alice, RoleNames::Empath)
+            .unwrap();
+        scenario
+            .answer(&["Alice", "Carol"])
+            .assert_dead("Alice")
+            .assert_dead("Carol")
+            .assert_alive("Bob");
+    }
+}
@@ -0,0 +1,336 @@
+use std::fmt::Display;
+
+use crate::engine::{
+    change_request::{ChangeRequest, ChangeType},
+    error::GameError,
+    player::roles::{RoleNames, Roles},
+    state::{PlayerIndex, State, Step, log::Event},
+};
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum WakeReason {
+    /// The player's role wakes at this point of the night order
+    Ability,
+    /// The storyteller added this wake by hand
+    Storyteller(String),
+}
+
+impl Display for WakeReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WakeReason::Ability => f.write_str("Ability"),
+            WakeReason::Storyteller(reason) => f.write_str(reason),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct WakeEntry {
+    pub(crate) player_index: PlayerIndex,
+    pub(crate) role: RoleNames,
+    pub(crate) reason: WakeReason,
+    /// Where the role is in the night order. Storyteller wakes have no order
+    pub(crate) order: Option<usize>,
+    pub(crate) skipped: bool,
+}
+
+/// Everyone who wakes during a step, in the order they wake. Built at the start of the step so
+/// the storyteller can see and change it ahead of time
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct NightPlan {
+    pub(crate) entries: Vec<WakeEntry>,
+    /// The entry being resolved right now, None before anyone has woken
+    current: Option<usize>,
+}
+
+impl NightPlan {
+    pub(crate) fn new(mut entries: Vec<WakeEntry>) -> Self {
+        entries.sort_by_key(|entry| (entry.order, entry.player_index));
+        Self {
+            entries,
+            current: None,
+        }
+    }
+
+    pub(crate) fn current_index(&self) -> Option<usize> {
+        self.current
+    }
+
+    pub(crate) fn current(&self) -> Option<&WakeEntry> {
+        self.entries.get(self.current?)
+    }
+
+    /// Index of the first entry that hasn't woken yet
+    fn pending_start(&self) -> usize {
+        match self.current {
+            Some(current) => current + 1,
+            None => 0,
+        }
+    }
+
+    pub(crate) fn is_pending(&self, index: usize) -> bool {
+        index >= self.pending_start() && index < self.entries.len()
+    }
+
+    /// Moves on to the next entry that hasn't been skipped
+    fn advance(&mut self) -> Option<&WakeEntry> {
+        let next =
+            (self.pending_start()..self.entries.len()).find(|index| !self.entries[*index].skipped);
+        self.current = Some(next.unwrap_or(self.entries.len()));
+        self.current()
+    }
+
+    /// Drops every wake a player still has left, including ones added by the storyteller
+    pub(crate) fn remove_player(&mut self, player_index: PlayerIndex) {
+        let pending_start = self.pending_start();
+        let mut index = pending_start;
+        while index < self.entries.len() {
+            if self.entries[index].player_index == player_index {
+                self.entries.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    /// Replaces the wakes a player still has left with the ones of their new role. The new role
+    /// only wakes if its place in the order hasn't passed yet
+    fn role_changed(&mut self, player_index: PlayerIndex, role: RoleNames, order: Option<usize>) {
+        let pending_start = self.pending_start();
+        let mut index = pending_start;
+        while index < self.entries.len() {
+            let entry = &self.entries[index];
+            if entry.player_index == player_index && entry.reason == WakeReason::Ability {
+                self.entries.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+
+        let Some(order) = order else {
+            return;
+        };
+        let current_order = self.current().and_then(|entry| entry.order);
+        if current_order.is_some_and(|current_order| order <= current_order) {
+            return;
+        }
+
+        let insert_index = (pending_start..self.entries.len())
+            .find(|index| {
+                self.entries[*index].order.is_some_and(|entry_order| {
+                    (entry_order, self.entries[*index].player_index) > (order, player_index)
+                })
+            })
+            .unwrap_or(self.entries.len());
+        self.entries.insert(
+            insert_index,
+            WakeEntry {
+                player_index,
+                role,
+                reason: WakeReason::Ability,
+                order: Some(order),
+                skipped: false,
+            },
+        );
+    }
+}
+
+impl State {
+    /// Builds the plan for the current step out of everyone whose role wakes during it
+    pub(crate) fn build_night_plan(&mut self) {
+        let entries = (0..self.get_players().len())
+            .filter_map(|player_index| {
+                let order = self.wake_order(player_index, self.step)?;
+                Some(WakeEntry {
+                    player_index,
+                    role: self.wakes_as(player_index).ok()?,
+                    reason: WakeReason::Ability,
+                    order: Some(order),
+                    skipped: false,
+                })
+            })
+            .collect();
+        self.night_plan = NightPlan::new(entries);
+    }
+
+    /// The role a player is woken as during the current step
+    fn wakes_as(&self, player_index: PlayerIndex) -> Result<RoleNames, GameError> {
+        let role = &self.get_player(player_index)?.role;
+        Ok(match self.step {
+            Step::Setup => role.to_role_name(),
+            _ => role.wakes_as(),
+        })
+    }
+
+    /// Moves the night plan on to the next player to wake, if there is one
+    pub(crate) fn advance_active_player(&mut self) -> Option<PlayerIndex> {
+        self.night_plan.advance().map(|entry| entry.player_index)
+    }
+
+    /// Storyteller wakes don't use the player's ability, only show the reason they were woken
+    pub(crate) fn resolve_storyteller_wake(&mut self, player_index: PlayerIndex) -> bool {
+        let Some(entry) = self.night_plan.current() else {
+            return false;
+        };
+        let WakeReason::Storyteller(reason) = &entry.reason else {
+            return false;
+        };
+        if entry.player_index != player_index {
+            return false;
+        }
+
+        let Ok(player) = self.get_player(player_index) else {
+            return false;
+        };
+        let description = format!("Wake {}: {}", player.name, reason);
+        self.change_request_queue
+            .push_back(ChangeRequest::new_builder(ChangeType::Display, description));
+        true
+    }
+
+    /// Swaps out a player's role, keeping the night plan up to date
+    pub(crate) fn set_role(
+        &mut self,
+        player_index: PlayerIndex,
+        role: Roles,
+    ) -> Result<(), GameError> {
+        self.get_player_mut(player_index)?.role = role;
+        let order = self.wake_order(player_index, self.step);
+        let role = self.wakes_as(player_index)?;
+        self.night_plan.role_changed(player_index, role, order);
+        Ok(())
+    }
+
+    pub(crate) fn skip_wake(&mut self, index: usize) -> Result<(), GameError> {
+        self.check_pending(index)?;
+
+        let entry = &mut self.night_plan.entries[index];
+        entry.skipped = !entry.skipped;
+        let (player_index, skipped) = (entry.player_index, entry.skipped);
+        self.log_event(Event::WakeSkipped {
+            player_index,
+            skipped,
+        });
+        Ok(())
+    }
+
+    /// Moves a wake that hasn't happened yet to another spot that hasn't happened yet
+    pub(crate) fn move_wake(&mut self, from: usize, to: usize) -> Result<(), GameError> {
+        self.check_pending(from)?;
+        self.check_pending(to)?;
+
+        let entry = self.night_plan.entries.remove(from);
+        let player_index = entry.player_index;
+        self.night_plan.entries.insert(to, entry);
+        self.log_event(Event::WakeMoved {
+            player_index,
+            from,
+            to,
+        });
+        Ok(())
+    }
+
+    /// Only wakes that haven't happened yet can be changed
+    fn check_pending(&self, index: usize) -> Result<(), GameError> {
+        if self.night_plan.is_pending(index) {
+            return Ok(());
+        }
+        let player_index = self
+            .night_plan
+            .entries
+            .get(index)
+            .map_or(self.players.len(), |entry| entry.player_index);
+        Err(GameError::NoSuchEntry {
+            player_index,
+            kind: "wake still to come",
+            index,
+        })
+    }
+
+    /// Adds a wake for a player that isn't part of the night order. It goes after every wake
+    /// that is already planned
+    pub(crate) fn add_wake(
+        &mut self,
+        player_index: PlayerIndex,
+        reason: String,
+    ) -> Result<(), GameError> {
+        let role = self.wakes_as(player_index)?;
+        self.night_plan.entries.push(WakeEntry {
+            player_index,
+            role,
+            reason: WakeReason::Storyteller(reason.clone()),
+            order: None,
+            skipped: false,
+        });
+        self.log_event(Event::WakeAdded {
+            player_index,
+            reason,
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{scenario::Scenario, state::Step};
+
+    use super::*;
+
+    fn entry(player_index: PlayerIndex, role: RoleNames, order: usize) -> WakeEntry {
+        WakeEntry {
+            player_index,
+            role,
+            reason: WakeReason::Ability,
+            order: Some(order),
+            skipped: false,
+        }
+    }
+
+    #[test]
+    fn test_role_changed() {
+        let mut plan = NightPlan::new(vec![
+            entry(0, RoleNames::Poisoner, 10),
+            entry(1, RoleNames::Imp, 32),
+            entry(2, RoleNames::Empath, 66),
+        ]);
+        plan.advance();
+        plan.advance();
+        assert_eq!(plan.current().unwrap().role, RoleNames::Imp);
+
+        // Starpassing to the Poisoner doesn't wake them again as the Imp
+        plan.role_changed(0, RoleNames::Imp, Some(32));
+        assert_eq!(plan.entries.len(), 3);
+
+        // Someone becoming the Ravenkeeper still wakes later tonight
+        plan.role_changed(0, RoleNames::Ravenkeeper, Some(65));
+        assert_eq!(plan.entries[2].player_index, 0);
+        assert_eq!(plan.advance().unwrap().role, RoleNames::Ravenkeeper);
+        assert_eq!(plan.advance().unwrap().role, RoleNames::Empath);
+        assert!(plan.advance().is_none());
+    }
+
+    #[test]
+    fn test_move_wake() {
+        let mut scenario = Scenario::new(&[
+            ("Alice", RoleNames::Poisoner),
+            ("Bob", RoleNames::Chef),
+            ("Carol", RoleNames::Empath),
+            ("Dan", RoleNames::Soldier),
+            ("Eve", RoleNames::Imp),
+        ]);
+        scenario.advance_to(Step::Night);
+        let last = scenario.state.night_plan.entries.len() - 1;
+        let player_index = scenario.state.night_plan.entries[last].player_index;
+
+        scenario.state.move_wake(last, last - 1).unwrap();
+        assert_eq!(
+            scenario.state.night_plan.entries[last - 1].player_index,
+            player_index
+        );
+        scenario.assert_logged(Event::WakeMoved {
+            player_index,
+            from: last,
+            to: last - 1,
+        });
+    }
+}
@@ -0,0 +1,147 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::engine::{
+    error::GameError,
+    state::{PlayerIndex, State, Step, log::Event, timer::DayTimer},
+};
+
+/// What everyone sitting in the town square can see about a player
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PublicPlayer {
+    pub(crate) name: String,
+    pub(crate) dead: bool,
+    pub(crate) ghost_vote: bool,
+    pub(crate) traveller: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PublicNomination {
+    pub(crate) nominator: String,
+    pub(crate) target: String,
+    pub(crate) votes: Option<usize>,
+}
+
+/// The part of the game that isn't secret. Safe to show to players
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct PublicState {
+    pub(crate) step: Step,
+    pub(crate) day_num: usize,
+    pub(crate) players: Vec<PublicPlayer>,
+    /// Nominations made today, oldest first
+    pub(crate) nominations: Vec<PublicNomination>,
+    pub(crate) timer: Option<DayTimer>,
+}
+
+impl State {
+    pub(crate) fn public_state(&self) -> PublicState {
+        let players = self
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(_, player)| !player.left)
+            .map(|(player_index, player)| PublicPlayer {
+                name: player.name.clone(),
+                // Night deaths stay secret until dawn is announced
+                dead: player.dead && !self.death_hidden(player_index),
+                ghost_vote: player.ghost_vote,
+                traveller: player.traveller,
+            })
+            .collect();
+
+        return PublicState {
+            step: self.step,
+            day_num: self.day_num,
+            players,
+            nominations: self.todays_nominations(),
+            timer: self.timer,
+        };
+    }
+
+    fn todays_nominations(&self) -> Vec<PublicNomination> {
+        if !matches!(self.step, Step::Day(_)) {
+            return vec![];
+        }
+
+        let name = |player_index: PlayerIndex| {
+            self.get_player(player_index)
+                .map(|player| player.name.clone())
+                .unwrap_or_default()
+        };
+        let mut today: Vec<_> = self.log.latest_day().collect();
+        today.reverse();
+        let mut nominations: Vec<(PlayerIndex, PublicNomination)> = vec![];
+        for event in today.iter().flat_map(|day_phase| day_phase.log.iter()) {
+            match event {
+                Event::Nomination {
+                    nominator_player_index,
+                    target_player_index,
+                } => nominations.push((
+                    *target_player_index,
+                    PublicNomination {
+                        nominator: name(*nominator_player_index),
+                        target: name(*target_player_index),
+                        votes: None,
+                    },
+                )),
+                Event::Voting {
+                    players_voted,
+                    target_player_index,
+                } => {
+                    if let Some((_, nomination)) = nominations
+                        .iter_mut()
+                        .rev()
+                        .find(|(target, _)| target == target_player_index)
+                    {
+                        nomination.votes = Some(*players_voted);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        return nominations
+            .into_iter()
+            .map(|(_, nomination)| nomination)
+            .collect();
+    }
+
+    /// What a player is told about themselves when the game starts. Players who have been lied
+    /// to about their character are told the lie
+    pub(crate) fn starting_info(&self, player_index: PlayerIndex) -> Result<String, GameError> {
+        let player = self.get_player(player_index)?;
+        return Ok(format!(
+            "You are the {} ({})",
+            player.role.wakes_as(),
+            player.alignment
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{player::roles::RoleNames::*, scenario::Scenario};
+
+    #[test]
+    fn test_public_state() {
+        let seats = [
+            ("Alice", Chef),
+            ("Bob", Virgin),
+            ("Carol", Soldier),
+            ("Dan", Spy),
+            ("Eve", Imp),
+        ];
+        let mut scenario = Scenario::new(&seats);
+        let (bob, dan) = (scenario.index("Bob"), scenario.index("Dan"));
+        scenario.state.player_leaves(dan).unwrap();
+        scenario.state.set_traveller(bob, true).unwrap();
+
+        let public = scenario.state.public_state();
+        assert_eq!(public.players.len(), 4);
+        assert!(public.players[bob].traveller);
+        // Nothing secret makes it into the public view
+        let json = serde_json::to_string(&public).unwrap();
+        for (_, role) in seats {
+            assert!(!json.contains(&role.to_string()));
+        }
+    }
+}
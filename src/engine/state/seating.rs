@@ -0,0 +1,232 @@
+use crate::engine::{
+    error::GameError,
+    state::{PlayerIndex, State},
+};
+
+impl State {
+    /// Players can only change seats while nothing is waiting to be resolved, since pending
+    /// change requests hold on to player indices
+    pub(crate) fn can_reseat(&self) -> bool {
+        self.change_request_queue.is_empty()
+    }
+
+    /// Moves the player in seat from to seat to, shifting everyone in between over by one
+    pub(crate) fn move_seat(
+        &mut self,
+        from: PlayerIndex,
+        to: PlayerIndex,
+    ) -> Result<(), GameError> {
+        self.get_player(from)?;
+        self.get_player(to)?;
+        let mut seats: Vec<PlayerIndex> = (0..self.players.len()).collect();
+        let player_index = seats.remove(from);
+        seats.insert(to, player_index);
+        return self.reseat(seats);
+    }
+
+    pub(crate) fn swap_seats(
+        &mut self,
+        first: PlayerIndex,
+        second: PlayerIndex,
+    ) -> Result<(), GameError> {
+        self.get_player(first)?;
+        self.get_player(second)?;
+        let mut seats: Vec<PlayerIndex> = (0..self.players.len()).collect();
+        seats.swap(first, second);
+        return self.reseat(seats);
+    }
+
+    /// Links a seat to the one after it as part of the same couch. Couches belong to the seats,
+    /// so they don't move when players do, and they don't change who neighbours who
+    pub(crate) fn toggle_couch_link(&mut self, seat: PlayerIndex) -> Result<(), GameError> {
+        let linked = self
+            .couch_links
+            .get_mut(seat)
+            .ok_or(GameError::NoSuchPlayer(seat))?;
+        *linked = !*linked;
+        return Ok(());
+    }
+
+    /// Which couch each seat is on. Seats that aren't linked to anyone are on their own
+    pub(crate) fn couches(&self) -> Vec<usize> {
+        let seat_count = self.couch_links.len();
+        // Start counting from a seat that begins a couch, so a couch that wraps around the circle
+        // isn't split in two
+        let start = (0..seat_count)
+            .find(|seat| !self.couch_links[(seat + seat_count - 1) % seat_count])
+            .unwrap_or(0);
+
+        let mut couches = vec![0; seat_count];
+        let mut couch = 0;
+        for offset in 0..seat_count {
+            let seat = (start + offset) % seat_count;
+            couches[seat] = couch;
+            if !self.couch_links[seat] {
+                couch += 1;
+            }
+        }
+
+        couches
+    }
+
+    /// Puts players in new seats. seats[i] is the player that sits in seat i afterwards. Every
+    /// player index the state holds on to is updated
+    fn reseat(&mut self, seats: Vec<PlayerIndex>) -> Result<(), GameError> {
+        if !self.can_reseat() {
+            return Err(GameError::IllegalAction {
+                action: "reseat",
+                step: self.step,
+            });
+        }
+        // Nothing is left for the last ability to ask, and what it kept from before is still by
+        // the old seats
+        self.commit_ability();
+
+        let mut seat_map = vec![0; seats.len()];
+        for (new_index, old_index) in seats.iter().enumerate() {
+            seat_map[*old_index] = new_index;
+        }
+
+        self.players = seats
+            .iter()
+            .map(|old_index| self.players[*old_index].clone())
+            .collect();
+        self.claims = seats
+            .iter()
+            .map(|old_index| self.claims[*old_index].clone())
+            .collect();
+
+        for player in self.players.iter_mut() {
+            for status_effect in player.status_effects.iter_mut() {
+                status_effect.source_player_index = seat_map[status_effect.source_player_index];
+            }
+        }
+        self.win_cond_i = self.win_cond_i.map(|player_index| seat_map[player_index]);
+
+        macro_rules! remap_listeners {
+            ($($listeners:ident),*) => {
+                $(for listener in self.$listeners.iter_mut() {
+                    listener.state.source_player_index =
+                        seat_map[listener.state.source_player_index];
+                })*
+            };
+        }
+        remap_listeners!(
+            nomination_listeners,
+            attempted_kill_listeners,
+            death_listeners,
+            execution_listeners,
+            phase_listeners
+        );
+
+        if let Some(dawn_report) = self.dawn_report.as_mut() {
+            for player_index in dawn_report
+                .deaths
+                .iter_mut()
+                .chain(dawn_report.failed_kills.iter_mut())
+            {
+                *player_index = seat_map[*player_index];
+            }
+            dawn_report.deaths.sort();
+            dawn_report.failed_kills.sort();
+        }
+
+        for entry in self.night_plan.entries.iter_mut() {
+            entry.player_index = seat_map[entry.player_index];
+        }
+        self.log.remap_players(&seat_map);
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{
+        change_request::{ChangeRequest, ChangeType},
+        player::roles::RoleNames::*,
+        scenario::Scenario,
+    };
+
+    use super::*;
+
+    fn town() -> Scenario {
+        return Scenario::new(&[
+            ("Alice", Chef),
+            ("Bob", Virgin),
+            ("Carol", Soldier),
+            ("Dan", Spy),
+            ("Eve", Imp),
+        ]);
+    }
+
+    #[test]
+    fn test_reseating() {
+        let mut scenario = town();
+        let names = |scenario: &Scenario| {
+            scenario
+                .state
+                .get_players()
+                .iter()
+                .map(|player| player.name.clone())
+                .collect::<Vec<String>>()
+        };
+
+        scenario.state.move_seat(0, 2).unwrap();
+        assert_eq!(
+            names(&scenario),
+            vec!["Bob", "Carol", "Alice", "Dan", "Eve"]
+        );
+        let alice = scenario.index("Alice");
+        assert_eq!(scenario.state.right_player(scenario.index("Carol")), alice);
+
+        scenario.state.swap_seats(0, 4).unwrap();
+        assert_eq!(
+            names(&scenario),
+            vec!["Eve", "Carol", "Alice", "Dan", "Bob"]
+        );
+        assert_eq!(scenario.state.win_cond_i, Some(scenario.index("Eve")));
+
+        scenario.state.toggle_couch_link(4).unwrap();
+        scenario.state.toggle_couch_link(1).unwrap();
+        assert_eq!(scenario.state.couches(), vec![2, 0, 0, 1, 2]);
+
+        // Pending change requests hold on to seats
+        scenario
+            .state
+            .change_request_queue
+            .push_back(ChangeRequest::new_builder(
+                ChangeType::Display,
+                "Wait".into(),
+            ));
+        assert!(matches!(
+            scenario.state.move_seat(0, 1),
+            Err(GameError::IllegalAction {
+                action: "reseat",
+                ..
+            })
+        ));
+        assert_eq!(
+            names(&scenario),
+            vec!["Eve", "Carol", "Alice", "Dan", "Bob"]
+        );
+    }
+
+    #[test]
+    fn test_replacing_and_leaving() {
+        let mut scenario = town();
+        let bob = scenario.index("Bob");
+
+        scenario
+            .state
+            .replace_player(bob, "Newcomer".to_string())
+            .unwrap();
+        assert_eq!(scenario.player("Newcomer").role.to_role_name(), Virgin);
+
+        scenario.state.player_leaves(bob).unwrap();
+        assert!(scenario.player("Newcomer").left);
+        scenario.assert_dead("Newcomer");
+        let (alice, carol) = (scenario.index("Alice"), scenario.index("Carol"));
+        assert_eq!(scenario.state.right_player(alice), carol);
+        assert_eq!(scenario.state.left_player(carol), alice);
+    }
+}
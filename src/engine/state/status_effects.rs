@@ -1,13 +1,8 @@
 use std::fmt::{Debug, Display};
-use std::ops::Deref;
-use std::sync::Arc;
 
-use leptos::leptos_dom::logging::{console_error, console_log};
-
-use crate::engine::player::Player;
 use crate::engine::{
-    player::PlayerBehaviors,
-    state::{PlayerIndex, State},
+    player::{PlayerBehaviors, roles::RoleNames},
+    state::PlayerIndex,
 };
 
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
@@ -16,7 +11,16 @@ pub(crate) enum CleanupPhase {
     Dawn,
 }
 
-#[derive(Clone, Copy, Hash, Debug, PartialEq, Eq, PartialOrd, Ord)]
+impl Display for CleanupPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CleanupPhase::Dusk => f.write_str("Dusk"),
+            CleanupPhase::Dawn => f.write_str("Dawn"),
+        }
+    }
+}
+
+#[derive(Clone, Hash, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum StatusType {
     // General Effects
     Poisoned,
@@ -32,6 +36,16 @@ pub enum StatusType {
     ButlerMaster,
     FortuneTellerRedHerring,
     DemonProtected,
+    /// The player has to be mad that they are `role`. `by` is the role whose ability made them
+    /// mad, which decides what happens if they break madness
+    Mad {
+        role: RoleNames,
+        by: RoleNames,
+    },
+
+    // Storyteller Effects
+    /// A reminder token with free text, placed by the storyteller
+    Custom(String),
 }
 
 impl StatusType {
@@ -44,7 +58,7 @@ impl Display for StatusType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             StatusType::Drunk => f.write_str("Drunk"),
-            // StatusType::Mad => f.write_str("Mad"),
+            StatusType::Mad { role, by } => write!(f, "Mad about being the {} ({})", role, by),
             StatusType::Poisoned => f.write_str("Poisoned"),
             StatusType::DemonProtected => f.write_str("Demon Protected"),
             // StatusType::NightProtected => f.write_str("Night Protected"),
@@ -62,6 +76,7 @@ impl Display for StatusType {
             StatusType::LibrarianWrong => f.write_str("Librarian Wrong"),
             StatusType::InvestigatorMinion => f.write_str("Investigator Minion"),
             StatusType::InvestigatorWrong => f.write_str("Investigator Wrong"),
+            StatusType::Custom(text) => f.write_str(text),
         }
     }
 }
@@ -73,6 +88,8 @@ pub(crate) struct StatusEffect {
     pub(crate) source_player_index: PlayerIndex,
     pub(crate) cleanup_phase: Option<CleanupPhase>,
     pub(crate) behavior_types: Option<Vec<PlayerBehaviors>>,
+    /// Whether this status was placed by hand by the storyteller instead of by a role
+    pub(crate) manual: bool,
 }
 
 impl StatusEffect {
@@ -81,11 +98,14 @@ impl StatusEffect {
         source_player_index: PlayerIndex,
         cleanup_phase: Option<CleanupPhase>,
     ) -> Self {
-        let behavior_types = match status_type {
+        let behavior_types = match &status_type {
             StatusType::Poisoned | StatusType::Drunk => Some(vec![
+                PlayerBehaviors::SetupAbility,
                 PlayerBehaviors::DayAbility,
                 PlayerBehaviors::NightOneAbility,
                 PlayerBehaviors::NightAbility,
+                PlayerBehaviors::EventListener,
+                PlayerBehaviors::PassiveAbility,
             ]),
             StatusType::DemonProtected => Some(vec![PlayerBehaviors::Kill]),
             _ => None,
@@ -96,7 +116,29 @@ impl StatusEffect {
             source_player_index,
             cleanup_phase,
             behavior_types,
+            manual: false,
+        }
+    }
+
+    /// Creates a reminder token placed by the storyteller. If it doesn't affect behaviour, it is
+    /// only there for the storyteller to see and the engine will ignore it
+    pub(crate) fn new_reminder(
+        status_type: StatusType,
+        source_player_index: PlayerIndex,
+        cleanup_phase: Option<CleanupPhase>,
+        affects_behaviour: bool,
+    ) -> Self {
+        let mut status_effect = Self::new(status_type, source_player_index, cleanup_phase);
+        status_effect.manual = true;
+        if !affects_behaviour {
+            status_effect.behavior_types = None;
         }
+
+        status_effect
+    }
+
+    pub(crate) fn affects_behaviour(&self) -> bool {
+        !self.manual || self.behavior_types.is_some()
     }
 }
 
@@ -105,6 +147,7 @@ impl Debug for StatusEffect {
         f.debug_struct("StatusEffect")
             .field("status_type", &self.status_type.name())
             .field("source_player_index", &self.source_player_index)
+            .field("manual", &self.manual)
             .finish()
     }
 }
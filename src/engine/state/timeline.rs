@@ -0,0 +1,208 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use crate::engine::state::{PlayerIndex, State, Step, log::EventId};
+
+/// A player as they were at some point in the game
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SnapshotPlayer {
+    pub(crate) name: String,
+    pub(crate) role: String,
+    pub(crate) alignment: String,
+    pub(crate) dead: bool,
+    pub(crate) ghost_vote: bool,
+    pub(crate) left: bool,
+    pub(crate) statuses: Vec<String>,
+}
+
+/// Read only copy of the grimoire
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct GrimoireSnapshot {
+    pub(crate) step: Step,
+    pub(crate) day_num: usize,
+    pub(crate) players: Vec<SnapshotPlayer>,
+    /// The change request the storyteller was resolving, if any
+    pub(crate) change_request: Option<String>,
+    pub(crate) acting_player: Option<PlayerIndex>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TimelineEntry {
+    pub(crate) event_id: EventId,
+    pub(crate) phase: String,
+    pub(crate) description: String,
+    /// The grimoire right after the event happened
+    pub(crate) grimoire: GrimoireSnapshot,
+}
+
+/// Where the state leaves a snapshot of the grimoire each time it logs an event, until the
+/// timeline picks them up. Copies of the state share the same store, so they don't copy any
+/// snapshots
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SnapshotStore(Arc<Mutex<Vec<(EventId, GrimoireSnapshot)>>>);
+
+impl SnapshotStore {
+    pub(crate) fn push(&self, event_id: EventId, snapshot: GrimoireSnapshot) {
+        if let Ok(mut snapshots) = self.0.lock() {
+            snapshots.push((event_id, snapshot));
+        }
+    }
+
+    fn take(&self) -> Vec<(EventId, GrimoireSnapshot)> {
+        return self
+            .0
+            .lock()
+            .map(|mut snapshots| std::mem::take(&mut *snapshots))
+            .unwrap_or_default();
+    }
+}
+
+/// The grimoire after every logged event, so the game can be stepped through afterwards
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Timeline {
+    pub(crate) entries: Vec<TimelineEntry>,
+    /// Give this to the state so it snapshots the grimoire for the timeline
+    pub(crate) store: SnapshotStore,
+}
+
+impl Timeline {
+    /// Adds an entry for every snapshot the state took since the last call. Snapshots of events
+    /// that were rolled back in the meantime are dropped
+    pub(crate) fn record(&mut self, state: &State) {
+        for (event_id, grimoire) in self.store.take() {
+            let Some((day_phase, _, event)) = state
+                .log
+                .entries()
+                .find(|(_, logged_id, _)| *logged_id == event_id)
+            else {
+                continue;
+            };
+            self.entries.push(TimelineEntry {
+                event_id,
+                phase: day_phase.title(),
+                description: state.describe_event(event.clone()),
+                grimoire,
+            });
+        }
+    }
+
+    /// Drops the entries for events that were rolled back, like when an ability is cancelled
+    pub(crate) fn rewind(&mut self, state: &State) {
+        let logged: HashSet<EventId> = state
+            .log
+            .entries()
+            .map(|(_, event_id, _)| event_id)
+            .collect();
+        self.entries
+            .retain(|entry| logged.contains(&entry.event_id));
+    }
+}
+
+impl State {
+    pub(crate) fn snapshot(
+        &self,
+        change_request: Option<String>,
+        acting_player: Option<PlayerIndex>,
+    ) -> GrimoireSnapshot {
+        let players = self
+            .players
+            .iter()
+            .map(|player| SnapshotPlayer {
+                name: player.name.clone(),
+                role: player.role.to_string(),
+                alignment: player.alignment.to_string(),
+                dead: player.dead,
+                ghost_vote: player.ghost_vote,
+                left: player.left,
+                statuses: player
+                    .status_effects
+                    .iter()
+                    .map(|status_effect| status_effect.status_type.to_string())
+                    .collect(),
+            })
+            .collect();
+
+        return GrimoireSnapshot {
+            step: self.step,
+            day_num: self.day_num,
+            players,
+            change_request,
+            acting_player,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{player::roles::RoleNames::*, scenario::Scenario};
+
+    use super::*;
+
+    #[test]
+    fn test_timeline() {
+        let mut scenario = Scenario::new(&[
+            ("Alice", Chef),
+            ("Bob", Virgin),
+            ("Carol", Soldier),
+            ("Dan", Spy),
+            ("Eve", Imp),
+        ]);
+        let mut timeline = Timeline::default();
+        scenario.state.snapshot_store = Some(timeline.store.clone());
+        let (carol, dan) = (scenario.index("Carol"), scenario.index("Dan"));
+
+        scenario.advance_to(Step::NightOne);
+        let before = scenario.state.clone();
+        scenario.state.set_dead(carol, true).unwrap();
+        timeline.record(&scenario.state);
+        // Both events get the grimoire from when they happened, even though they're recorded
+        // together
+        scenario.state.set_dead(dan, true).unwrap();
+        scenario.state.set_dead(carol, false).unwrap();
+        timeline.record(&scenario.state);
+        // Nothing new happened
+        timeline.record(&scenario.state);
+
+        let deaths: Vec<_> = timeline
+            .entries
+            .iter()
+            .filter(|entry| entry.description.contains("died"))
+            .collect();
+        assert_eq!(deaths.len(), 2);
+        assert!(deaths[0].grimoire.players[carol].dead);
+        assert!(deaths[1].grimoire.players[carol].dead);
+        assert!(deaths[1].grimoire.players[dan].dead);
+        let last = timeline.entries.last().unwrap();
+        assert!(!last.grimoire.players[carol].dead);
+        assert_eq!(last.phase, "Night 1");
+        assert_eq!(last.grimoire.change_request, None);
+
+        // Rolling back and logging the same thing again makes new entries, not old ones
+        let recorded = timeline.entries.len();
+        scenario.state = before;
+        timeline.rewind(&scenario.state);
+        assert!(timeline.entries.len() < recorded);
+        scenario.state.set_dead(carol, true).unwrap();
+        timeline.record(&scenario.state);
+        assert!(timeline.entries.last().unwrap().grimoire.players[carol].dead);
+
+        // Events get the change request and ability that caused them, even when they're
+        // recorded later
+        scenario
+            .advance_to(Step::Night)
+            .wake("Eve")
+            .answer(&["Alice"]);
+        timeline.record(&scenario.state);
+        let kill = timeline
+            .entries
+            .iter()
+            .rev()
+            .find(|entry| entry.description.contains("died"))
+            .unwrap();
+        assert!(kill.grimoire.players[scenario.index("Alice")].dead);
+        assert_eq!(kill.grimoire.acting_player, Some(scenario.index("Eve")));
+        assert!(kill.grimoire.change_request.is_some());
+    }
+}
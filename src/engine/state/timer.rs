@@ -0,0 +1,223 @@
+use std::fmt::Display;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::engine::{
+    error::GameError,
+    state::{State, Step, log::Event},
+};
+
+/// The parts of the day that get timed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum TimerKind {
+    Discussion,
+    PrivateChats,
+    Nominations,
+}
+
+impl TimerKind {
+    pub(crate) fn all() -> [TimerKind; 3] {
+        [
+            TimerKind::Discussion,
+            TimerKind::PrivateChats,
+            TimerKind::Nominations,
+        ]
+    }
+}
+
+impl Display for TimerKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            TimerKind::Discussion => "Open Discussion",
+            TimerKind::PrivateChats => "Private Chats",
+            TimerKind::Nominations => "Nominations",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+/// How long each part of the day lasts by default. Durations are per living player, so days get
+/// shorter as the town shrinks
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct TimerSettings {
+    pub(crate) discussion_secs_per_player: u64,
+    pub(crate) private_chat_secs_per_player: u64,
+    pub(crate) nomination_secs_per_player: u64,
+    /// The first day is longer since nobody has talked yet. As a percentage of a normal day
+    pub(crate) first_day_percent: u64,
+    pub(crate) minimum_secs: u64,
+}
+
+impl Default for TimerSettings {
+    fn default() -> Self {
+        Self {
+            discussion_secs_per_player: 20,
+            private_chat_secs_per_player: 30,
+            nomination_secs_per_player: 15,
+            first_day_percent: 150,
+            minimum_secs: 60,
+        }
+    }
+}
+
+impl TimerSettings {
+    pub(crate) fn secs_per_player(&self, kind: TimerKind) -> u64 {
+        match kind {
+            TimerKind::Discussion => self.discussion_secs_per_player,
+            TimerKind::PrivateChats => self.private_chat_secs_per_player,
+            TimerKind::Nominations => self.nomination_secs_per_player,
+        }
+    }
+
+    pub(crate) fn set_secs_per_player(&mut self, kind: TimerKind, secs: u64) {
+        match kind {
+            TimerKind::Discussion => self.discussion_secs_per_player = secs,
+            TimerKind::PrivateChats => self.private_chat_secs_per_player = secs,
+            TimerKind::Nominations => self.nomination_secs_per_player = secs,
+        }
+    }
+
+    pub(crate) fn duration(
+        &self,
+        kind: TimerKind,
+        living_player_count: usize,
+        day_num: usize,
+    ) -> u64 {
+        let mut secs = self.secs_per_player(kind) * living_player_count as u64;
+        if day_num <= 1 {
+            secs = secs * self.first_day_percent / 100;
+        }
+        return secs.max(self.minimum_secs);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct DayTimer {
+    pub(crate) kind: TimerKind,
+    pub(crate) remaining_secs: u64,
+    pub(crate) running: bool,
+}
+
+impl DayTimer {
+    pub(crate) fn expired(&self) -> bool {
+        self.remaining_secs == 0
+    }
+}
+
+/// Formats seconds as m:ss
+pub(crate) fn format_secs(secs: u64) -> String {
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+impl State {
+    /// Starts a timer for part of the day, replacing any timer already running
+    pub(crate) fn start_timer(&mut self, kind: TimerKind) -> Result<(), GameError> {
+        if !matches!(self.step, Step::Day(_)) {
+            return Err(GameError::IllegalAction {
+                action: "start a timer",
+                step: self.step,
+            });
+        }
+
+        let duration_secs =
+            self.timer_settings
+                .duration(kind, self.living_player_count(), self.day_num);
+        self.timer = Some(DayTimer {
+            kind,
+            remaining_secs: duration_secs,
+            running: true,
+        });
+        self.log_event(Event::TimerStarted {
+            kind,
+            duration_secs,
+        });
+        return Ok(());
+    }
+
+    pub(crate) fn set_timer_running(&mut self, running: bool) {
+        if let Some(timer) = self.timer.as_mut()
+            && !timer.expired()
+        {
+            timer.running = running;
+        }
+    }
+
+    pub(crate) fn extend_timer(&mut self, secs: u64) {
+        let Some(timer) = self.timer.as_mut() else {
+            return;
+        };
+        timer.remaining_secs += secs;
+        timer.running = true;
+        let kind = timer.kind;
+        self.log_event(Event::TimerExtended { kind, secs });
+    }
+
+    pub(crate) fn stop_timer(&mut self) {
+        self.timer = None;
+    }
+
+    /// Counts a running timer down. Returns true if the timer ran out on this tick
+    pub(crate) fn tick_timer(&mut self, secs: u64) -> bool {
+        let Some(timer) = self.timer.as_mut() else {
+            return false;
+        };
+        if !timer.running {
+            return false;
+        }
+
+        timer.remaining_secs = timer.remaining_secs.saturating_sub(secs);
+        if !timer.expired() {
+            return false;
+        }
+
+        timer.running = false;
+        let kind = timer.kind;
+        self.log_event(Event::TimerExpired(kind));
+        return true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{player::roles::RoleNames::*, scenario::Scenario, state::DayStep};
+
+    use super::*;
+
+    #[test]
+    fn test_day_timer() {
+        let settings = TimerSettings::default();
+        assert_eq!(settings.duration(TimerKind::Discussion, 10, 2), 200);
+        assert_eq!(settings.duration(TimerKind::Discussion, 10, 1), 300);
+        assert_eq!(settings.duration(TimerKind::Nominations, 2, 4), 60);
+
+        let mut scenario = Scenario::new(&[
+            ("Alice", Chef),
+            ("Bob", Virgin),
+            ("Carol", Soldier),
+            ("Dan", Spy),
+            ("Eve", Imp),
+        ]);
+
+        // No timers at night
+        assert!(matches!(
+            scenario.state.start_timer(TimerKind::Discussion),
+            Err(GameError::IllegalAction { .. })
+        ));
+        assert!(scenario.state.timer.is_none());
+
+        scenario.advance_to(Step::Day(DayStep::Dawn));
+        let state = &mut scenario.state;
+        state.start_timer(TimerKind::Nominations).unwrap();
+        let duration = state.timer.unwrap().remaining_secs;
+        state.set_timer_running(false);
+        assert!(!state.tick_timer(duration));
+        state.set_timer_running(true);
+        assert!(!state.tick_timer(duration - 1));
+        assert!(state.tick_timer(1));
+        assert!(!state.tick_timer(1));
+
+        // Timers don't carry over into the next part of the day
+        state.next_step();
+        assert!(state.timer.is_none());
+    }
+}
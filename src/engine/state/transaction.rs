@@ -0,0 +1,282 @@
+use crate::engine::{
+    change_request::{ChangeRequestBuilder, ChangeRequestId},
+    error::GameError,
+    player::Player,
+    state::{
+        EventListener, PlayerIndex, State,
+        log::{self, EventId},
+    },
+};
+
+/// Everything a player's ability does while it resolves, from the first change request it
+/// queues to the last one in the chain. Keeps what the ability could change from before it
+/// started, so the storyteller can throw the whole chain away
+#[derive(Clone, Debug)]
+pub(crate) struct AbilityTransaction {
+    pub(crate) player_index: PlayerIndex,
+    /// The players before the ability started. Only the parts abilities change are put back
+    players: Vec<Player>,
+    listeners: Listeners,
+    win_cond_i: Option<PlayerIndex>,
+    /// Every change request the chain queued
+    queued: Vec<ChangeRequestId>,
+    /// Everything the chain logged
+    events: Vec<EventId>,
+}
+
+#[derive(Clone, Debug)]
+struct Listeners {
+    nomination: Vec<EventListener<log::Nomination>>,
+    attempted_kill: Vec<EventListener<log::AttemptedKill>>,
+    death: Vec<EventListener<log::Death>>,
+    execution: Vec<EventListener<log::Execution>>,
+    phase: Vec<EventListener<log::PhaseStart>>,
+}
+
+impl State {
+    /// Remembers what a player's ability can change before it starts resolving. Starting
+    /// another one keeps everything the previous ability did
+    pub(crate) fn begin_ability(&mut self, player_index: PlayerIndex) {
+        self.ability_transaction = Some(AbilityTransaction {
+            player_index,
+            players: self.players.clone(),
+            listeners: Listeners {
+                nomination: self.nomination_listeners.clone(),
+                attempted_kill: self.attempted_kill_listeners.clone(),
+                death: self.death_listeners.clone(),
+                execution: self.execution_listeners.clone(),
+                phase: self.phase_listeners.clone(),
+            },
+            win_cond_i: self.win_cond_i,
+            queued: vec![],
+            events: vec![],
+        });
+    }
+
+    /// Runs part of the current ability's chain, remembering what it logs and queues so
+    /// cancelling can take it back out
+    pub(crate) fn in_ability<T>(&mut self, change: impl FnOnce(&mut State) -> T) -> T {
+        if self.ability_transaction.is_none() {
+            return change(self);
+        }
+
+        let last_event = self.log.last_event_id();
+        let queued_before: Vec<ChangeRequestId> =
+            self.change_request_queue.iter().map(|cr| cr.id).collect();
+        let result = change(self);
+        if let Some(transaction) = self.ability_transaction.as_mut() {
+            transaction
+                .events
+                .extend(self.log.event_ids_after(last_event));
+            transaction.queued.extend(
+                self.change_request_queue
+                    .iter()
+                    .map(|cr| cr.id)
+                    .filter(|id| !queued_before.contains(id)),
+            );
+        }
+        return result;
+    }
+
+    /// Takes the next change request off the queue. Once nothing is left, the ability that
+    /// queued them has finished resolving and is kept
+    pub(crate) fn next_change_request(&mut self) -> Option<ChangeRequestBuilder> {
+        let cr = self.change_request_queue.pop_front();
+        if cr.is_none() {
+            self.commit_ability();
+        }
+        return cr;
+    }
+
+    /// Keeps everything the current ability did. It can't be cancelled after this
+    pub(crate) fn commit_ability(&mut self) {
+        self.ability_transaction = None;
+    }
+
+    /// Undoes every change request in the current ability's chain and drops the ones still
+    /// queued. Roles, deaths, alignments and the statuses abilities placed go back to how they
+    /// were, while notes, reminders, claims and anything else the storyteller did in the
+    /// meantime are kept. Returns whose ability it was
+    pub(crate) fn cancel_ability(&mut self) -> Result<PlayerIndex, GameError> {
+        let transaction = self
+            .ability_transaction
+            .take()
+            .ok_or(GameError::NoAbilityInProgress)?;
+
+        for (player_index, before) in transaction.players.into_iter().enumerate() {
+            let Ok(player) = self.get_player_mut(player_index) else {
+                continue;
+            };
+            player.dead = before.dead;
+            player.alignment = before.alignment;
+            let reminders: Vec<_> = player
+                .status_effects
+                .drain(..)
+                .filter(|status_effect| status_effect.manual)
+                .collect();
+            player.status_effects = before
+                .status_effects
+                .into_iter()
+                .filter(|status_effect| !status_effect.manual)
+                .chain(reminders)
+                .collect();
+            // Goes through set_role so the night plan follows
+            if player.role.to_role_name() == before.role.to_role_name() {
+                player.role = before.role;
+            } else {
+                self.set_role(player_index, before.role)?;
+            }
+        }
+
+        let listeners = transaction.listeners;
+        self.nomination_listeners = listeners.nomination;
+        self.attempted_kill_listeners = listeners.attempted_kill;
+        self.death_listeners = listeners.death;
+        self.execution_listeners = listeners.execution;
+        self.phase_listeners = listeners.phase;
+        self.win_cond_i = transaction.win_cond_i;
+        self.prevent_kill_default = false;
+        self.change_request_queue
+            .retain(|cr| !transaction.queued.contains(&cr.id));
+        self.log.remove_events(&transaction.events);
+        return Ok(transaction.player_index);
+    }
+
+    /// Cancels the current ability and wakes the player again, so the chain starts over from
+    /// their setup or night ability
+    pub(crate) fn restart_ability(&mut self) -> Result<PlayerIndex, GameError> {
+        let player_index = self.cancel_ability()?;
+        self.resolve(player_index);
+        return Ok(player_index);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{
+        change_request::{ChangeRequest, ChangeType},
+        player::{notes::NoteKind, roles::RoleNames},
+        scenario::Scenario,
+        state::{Step, log::Event, status_effects::StatusType},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_restart_washerwoman() {
+        let mut scenario = Scenario::new(&[
+            ("Alice", RoleNames::Washerwoman),
+            ("Bob", RoleNames::Chef),
+            ("Carol", RoleNames::Butler),
+            ("Dan", RoleNames::Poisoner),
+            ("Eve", RoleNames::Imp),
+        ]);
+        scenario
+            .advance_to(Step::Setup)
+            .wake("Alice")
+            .answer(&["Dan"])
+            .assert_status("Dan", StatusType::WasherwomanTownsfolk);
+        assert_eq!(scenario.state.change_request_queue.len(), 1);
+
+        // The storyteller's own edits in the middle of the chain aren't part of it
+        let (bob, dan) = (scenario.index("Bob"), scenario.index("Dan"));
+        scenario
+            .state
+            .add_note(bob, NoteKind::FreeText, "Quiet".into())
+            .unwrap();
+        scenario
+            .state
+            .add_reminder(dan, StatusType::Custom("Suspect".into()), None, None, false)
+            .unwrap();
+
+        let alice = scenario.index("Alice");
+        assert_eq!(scenario.state.restart_ability(), Ok(alice));
+        scenario
+            .assert_no_status("Dan", StatusType::WasherwomanTownsfolk)
+            .assert_status("Dan", StatusType::Custom("Suspect".into()));
+        assert_eq!(scenario.player("Bob").notes.len(), 1);
+
+        scenario
+            .answer(&["Bob"])
+            .answer(&["Carol"])
+            .assert_status("Bob", StatusType::WasherwomanTownsfolk)
+            .assert_status("Carol", StatusType::WasherwomanWrong)
+            .assert_settled();
+
+        // The chain is kept once there's nothing left for it to ask
+        assert_eq!(
+            scenario.state.cancel_ability(),
+            Err(GameError::NoAbilityInProgress)
+        );
+    }
+
+    #[test]
+    fn test_cancel_imp_kill() {
+        let mut scenario = Scenario::new(&[
+            ("Alice", RoleNames::Monk),
+            ("Bob", RoleNames::Soldier),
+            ("Carol", RoleNames::Chef),
+            ("Dan", RoleNames::Slayer),
+            ("Eve", RoleNames::Spy),
+            ("Fred", RoleNames::Imp),
+        ]);
+        scenario
+            .advance_to(Step::Night)
+            .wake("Fred")
+            .answer(&["Dan"])
+            .assert_dead("Dan");
+
+        let dan = scenario.index("Dan");
+        scenario
+            .state
+            .add_note(dan, NoteKind::FreeText, "Claimed Slayer".into())
+            .unwrap();
+        assert_eq!(scenario.state.cancel_ability(), Ok(scenario.index("Fred")));
+        scenario.assert_alive("Dan");
+        assert_eq!(scenario.player("Dan").notes.len(), 1);
+        let death_logged = scenario
+            .state
+            .log
+            .entries()
+            .any(|(_, _, event)| *event == Event::Death(dan));
+        assert!(!death_logged);
+    }
+
+    #[test]
+    fn test_cancel_keeps_other_requests() {
+        let mut scenario = Scenario::new(&[
+            ("Alice", RoleNames::Washerwoman),
+            ("Bob", RoleNames::Chef),
+            ("Carol", RoleNames::Butler),
+            ("Dan", RoleNames::Poisoner),
+            ("Eve", RoleNames::Imp),
+        ]);
+        scenario.advance_to(Step::Setup);
+        let storyteller_request =
+            |description: &str| ChangeRequest::new_builder(ChangeType::Display, description.into());
+        let queue = |scenario: &Scenario| {
+            scenario
+                .state
+                .change_request_queue
+                .iter()
+                .map(|cr| cr.description.clone())
+                .collect::<Vec<_>>()
+        };
+        scenario
+            .state
+            .change_request_queue
+            .push_back(storyteller_request("Before"));
+        scenario.state.resolve(scenario.index("Alice"));
+        scenario
+            .state
+            .change_request_queue
+            .push_back(storyteller_request("After"));
+
+        // Asking the earlier request first doesn't change which ones belong to the chain
+        let before = scenario.state.next_change_request().unwrap();
+        assert_eq!(before.description, "Before");
+        assert_eq!(queue(&scenario).len(), 2);
+        scenario.state.cancel_ability().unwrap();
+        assert_eq!(queue(&scenario), vec!["After".to_string()]);
+    }
+}
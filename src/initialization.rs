@@ -1,7 +1,14 @@
 use crate::engine::player::roles::Role;
-use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::{
+    collections::HashMap,
+    ops::{Add, AddAssign, Sub, SubAssign},
+};
 
-use crate::engine::player::{CharacterType, roles::RoleNames};
+use crate::engine::{
+    error::{GameError, SetupError},
+    night_order,
+    player::{CharacterType, roles::RoleNames},
+};
 
 use serde_derive::{Deserialize, Serialize};
 
@@ -13,32 +20,101 @@ struct Metadata {
     name: String,
 }
 
+/// A character written out as an object, which can override where it is in the night order.
+/// An order of 0 means the character doesn't wake that night
+#[derive(Debug, Serialize, Deserialize)]
+struct Character {
+    id: RoleNames,
+    #[serde(rename = "firstNight")]
+    first_night: Option<usize>,
+    #[serde(rename = "otherNight")]
+    other_night: Option<usize>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 enum ScriptEntry {
     Metadata(Metadata),
     Role(RoleNames),
+    Character(Character),
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct ScriptJson(Vec<ScriptEntry>);
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(crate) struct NightOrderOverride {
+    pub(crate) first_night: Option<usize>,
+    pub(crate) other_night: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default)]
 pub(crate) struct Script {
     pub(crate) roles: Vec<RoleNames>,
+    /// Night orders set by the script, which take precedence over the night order table
+    pub(crate) night_order_overrides: HashMap<RoleNames, NightOrderOverride>,
 }
 
 impl Script {
+    pub(crate) fn new(roles: Vec<RoleNames>) -> Self {
+        Self {
+            roles,
+            night_order_overrides: HashMap::new(),
+        }
+    }
+
     pub(crate) fn new_from_json(json: ScriptJson) -> Self {
-        let mut roles: Vec<RoleNames> = vec![];
+        let mut script = Self::new(vec![]);
         for entry in json.0 {
             match entry {
                 ScriptEntry::Metadata(_metadata) => (),
-                ScriptEntry::Role(role) => roles.push(role),
+                ScriptEntry::Role(role) => script.roles.push(role),
+                ScriptEntry::Character(character) => {
+                    script.roles.push(character.id);
+                    if character.first_night.is_some() || character.other_night.is_some() {
+                        script.night_order_overrides.insert(
+                            character.id,
+                            NightOrderOverride {
+                                first_night: character.first_night,
+                                other_night: character.other_night,
+                            },
+                        );
+                    }
+                }
             }
         }
 
-        Self { roles }
+        script
+    }
+
+    /// The script's firstNight for the role if it has one, otherwise the official order. Both
+    /// count from 1, and 0 means the role doesn't wake
+    pub(crate) fn first_night_order(&self, role: RoleNames) -> Option<usize> {
+        match self
+            .night_order_overrides
+            .get(&role)
+            .and_then(|night_order| night_order.first_night)
+        {
+            Some(0) => None,
+            Some(order) => Some(order),
+            None => night_order::first_night_order(&role.id()),
+        }
+    }
+
+    pub(crate) fn other_night_order(&self, role: RoleNames) -> Option<usize> {
+        match self
+            .night_order_overrides
+            .get(&role)
+            .and_then(|night_order| night_order.other_night)
+        {
+            Some(0) => None,
+            Some(order) => Some(order),
+            None => night_order::other_night_order(&role.id()),
+        }
+    }
+
+    pub(crate) fn setup_order(&self, role: RoleNames) -> usize {
+        night_order::setup_order(&role.id())
     }
 }
 // -- Setup Structures --
@@ -51,9 +127,9 @@ pub struct CharacterTypeCounts {
 }
 
 impl CharacterTypeCounts {
-    pub(crate) fn new(num_players: usize) -> Result<Self, ()> {
+    pub(crate) fn new(num_players: usize) -> Result<Self, GameError> {
         match num_players {
-            0..=4 => Err(()),
+            0..=4 => Err(SetupError::UnsupportedPlayerCount(num_players).into()),
             5 => Ok(Self {
                 townsfolk: 3,
                 outsiders: 0,
@@ -120,7 +196,7 @@ impl CharacterTypeCounts {
                 minions: 3,
                 demons: 1,
             }),
-            _ => Err(()),
+            _ => Err(SetupError::UnsupportedPlayerCount(num_players).into()),
         }
     }
 
@@ -129,14 +205,20 @@ impl CharacterTypeCounts {
     }
 
     pub(crate) fn on_choose(&mut self, role: RoleNames) {
-        let delta = role.convert().initialization_effect();
+        let delta = role
+            .convert()
+            .ok()
+            .and_then(|role| role.initialization_effect());
         if let Some(delta) = delta {
             *self += delta
         }
     }
 
     pub(crate) fn on_remove(&mut self, role: RoleNames) {
-        let delta = role.convert().initialization_effect();
+        let delta = role
+            .convert()
+            .ok()
+            .and_then(|role| role.initialization_effect());
         if let Some(delta) = delta {
             *self -= delta
         }
@@ -215,7 +297,37 @@ mod tests {
         // NOTE: Should have a test for all roles that modify character_types
 
         // Baron
-        let _character_counts = CharacterTypeCounts::new(5).unwrap();
-        todo!()
+        let mut character_counts = CharacterTypeCounts::new(5).unwrap();
+        character_counts.on_choose(RoleNames::Baron);
+        assert_eq!(character_counts.townsfolk, 1);
+        assert_eq!(character_counts.outsiders, 2);
+
+        character_counts.on_remove(RoleNames::Baron);
+        assert_eq!(character_counts, CharacterTypeCounts::new(5).unwrap());
+    }
+
+    #[test]
+    fn script_night_order_overrides() {
+        let json: ScriptJson = serde_json::from_str(
+            r#"[
+                {"id": "_meta", "author": "", "name": "Test"},
+                "imp",
+                "washerwoman",
+                {"id": "poisoner", "firstNight": 47, "otherNight": 0}
+            ]"#,
+        )
+        .unwrap();
+        let script = Script::new_from_json(json);
+
+        assert_eq!(
+            script.roles,
+            vec![RoleNames::Imp, RoleNames::Washerwoman, RoleNames::Poisoner]
+        );
+        // Overrides and the built-in order are on the same scale
+        assert_eq!(script.first_night_order(RoleNames::Washerwoman), Some(46));
+        assert_eq!(script.first_night_order(RoleNames::Poisoner), Some(47));
+        assert!(Some(47) < night_order::first_night_order("dawn"));
+        assert_eq!(script.other_night_order(RoleNames::Poisoner), None);
+        assert!(script.other_night_order(RoleNames::Imp).is_some());
     }
 }
@@ -1,10 +1,8 @@
 #![allow(clippy::needless_return)]
 
+use leptos::error::Errors;
 use leptos::mount::mount_to_body;
-use leptos::{
-    leptos_dom::logging::{console_error, console_log},
-    prelude::*,
-};
+use leptos::{leptos_dom::logging::console_log, prelude::*};
 use reactive_stores::Store;
 
 mod initialization;
@@ -13,15 +11,31 @@ use initialization::{CharacterTypeCounts, Script, ScriptJson};
 mod engine;
 use engine::{
     change_request::{ChangeArgs, ChangeRequest, ChangeType},
-    player::{CharacterType, Player, roles::RoleNames},
-    state::{PlayerIndex, State, StateStoreFields, Step},
+    error::GameError,
+    night_order::NightOrderSheet,
+    player::{Alignment, CharacterType, Player, notes::NoteKind, roles::RoleNames},
+    report::{GameReport, ReportFormat},
+    state::{
+        DayStep, GameMode, PlayerIndex, State, StateStoreFields, Step,
+        public::PublicState,
+        status_effects::{CleanupPhase, StatusType},
+        timeline::{GrimoireSnapshot, Timeline},
+        timer::{DayTimer, TimerKind, format_secs},
+    },
 };
 
 mod scripts;
 use scripts::*;
 
+mod alert;
+
+mod companion;
+use companion::{CompanionServer, Connection};
+
+mod town_square;
+use town_square::{TownSquareChannel, TownSquareMessage};
+
 use crate::engine::change_request::{ChangeRequestBuilder, StateChangeFuncPtr, check_len};
-use crate::engine::state::{self, log};
 
 const DEBUG: bool = true;
 // use leptos_router::components::*;
@@ -31,6 +45,10 @@ fn main() {
     // Stack Traces
     console_error_panic_hook::set_once();
 
+    if town_square::is_town_square_window() {
+        mount_to_body(TownSquare);
+        return;
+    }
     mount_to_body(App);
 }
 
@@ -48,7 +66,7 @@ fn App() -> impl IntoView {
     let initialization_stage = RwSignal::new(InitializationStage::Start);
     let player_names = RwSignal::new(Vec::<String>::new());
     let roles = RwSignal::new(Vec::<RoleNames>::new());
-    let script = RwSignal::new(Script { roles: vec![] });
+    let script = RwSignal::new(Script::default());
     provide_context(script);
 
     // NOTE: Debug only
@@ -347,8 +365,18 @@ fn RoleChooser(
     // If it isn't do nothing
     // Add a done button
 
-    let desired_character_type_counts =
-        RwSignal::new(CharacterTypeCounts::new(num_players).unwrap());
+    let desired_character_type_counts = match CharacterTypeCounts::new(num_players) {
+        Ok(counts) => RwSignal::new(counts),
+        Err(err) => {
+            return view! {
+                <ErrorBoundary fallback=error_list>{Err::<(), GameError>(err)}</ErrorBoundary>
+                <button on:click=move |_| {
+                    setup_stage.set(InitializationStage::InputPlayers)
+                }>"Back"</button>
+            }
+            .into_any();
+        }
+    };
     let curr_character_type_counts = RwSignal::new(CharacterTypeCounts::new_empty());
 
     let role_button = move |role: RoleNames| {
@@ -475,6 +503,7 @@ fn RoleChooser(
             </div>
         </div>
     }
+    .into_any()
 }
 
 #[derive(Clone, Debug, Store, Default)]
@@ -483,25 +512,76 @@ struct TempState {
     curr_change_request: Option<ChangeRequest>,
     selected_players: Vec<PlayerIndex>,
     selected_roles: Vec<RoleNames>,
+    selected_yes_no: Option<bool>,
+    selected_number: Option<usize>,
+    /// The storyteller chose to skip an optional change request
+    skipped: bool,
     currently_acting_player: Option<PlayerIndex>,
 }
 
 impl TempState {
-    fn clear_selected(&mut self) {
+    /// Selected players and roles are paired up in the order they were picked
+    fn selected_pairs(&self) -> Vec<(PlayerIndex, RoleNames)> {
+        self.selected_players
+            .iter()
+            .copied()
+            .zip(self.selected_roles.iter().copied())
+            .collect()
+    }
+    fn clear_answers(&mut self) {
         self.selected_players.clear();
         self.selected_roles.clear();
+        self.selected_yes_no = None;
+        self.selected_number = None;
+        self.skipped = false;
+    }
+    fn clear_selected(&mut self) {
+        self.clear_answers();
         self.curr_change_request = None;
         self.selected_player = None;
     }
     fn reset(&mut self) {
-        self.selected_players.clear();
-        self.selected_roles.clear();
+        self.clear_answers();
         self.curr_change_request = None;
         self.selected_player = None;
         self.currently_acting_player = None;
     }
 }
 
+/// Fallback for error boundaries
+fn error_list(errors: ArcRwSignal<Errors>) -> impl IntoView {
+    view! {
+        <p>"Errors:"</p>
+        <ul>
+            {move || {
+                errors
+                    .get()
+                    .into_iter()
+                    .map(|(_, e)| view! { <li>{e.to_string()}</li> })
+                    .collect_view()
+            }}
+        </ul>
+    }
+}
+
+/// The last change the engine refused. The game's error boundary shows it until the storyteller
+/// dismisses it
+#[derive(Clone, Copy)]
+struct LastError(RwSignal<Option<GameError>>);
+
+impl LastError {
+    /// Applies a change to the game, keeping the error if the engine refuses it
+    fn update(
+        &self,
+        game_state: Store<State>,
+        change: impl FnOnce(&mut State) -> Result<(), GameError>,
+    ) {
+        if let Some(Err(err)) = game_state.try_update(change) {
+            self.0.set(Some(err));
+        }
+    }
+}
+
 #[component]
 fn GameInterface(
     roles: Vec<RoleNames>,
@@ -509,26 +589,45 @@ fn GameInterface(
     script: Script,
 ) -> impl IntoView {
     // Create a new game using the data we have just collected from the user
-    let state = Store::new(State::new(roles, player_names, script).unwrap());
+    // Snapshot the grimoire whenever something new is logged
+    let timeline = RwSignal::new(Timeline::default());
+    provide_context(timeline);
+    let state = match State::new(roles, player_names, script) {
+        Ok(mut state) => {
+            state.snapshot_store = Some(timeline.with_untracked(|tl| tl.store.clone()));
+            Store::new(state)
+        }
+        Err(err) => return view! { <p>{err.to_string()}</p> }.into_any(),
+    };
     provide_context(state);
+    let last_error = LastError(RwSignal::new(None));
+    provide_context(last_error);
     let temp_state = Store::new(TempState::default());
     provide_context(temp_state);
+    provide_context(CompanionServer(RwSignal::new(None)));
+
+    Effect::new(move |_| state.with(|gs| timeline.update(|tl| tl.record(gs))));
+
+    // Keep any town square windows up to date
+    if let Some(channel) = TownSquareChannel::new() {
+        let sync_channel = channel.clone();
+        sync_channel.clone().on_message(move |message| {
+            if let TownSquareMessage::SyncRequest = message {
+                sync_channel.send_public_state(&state.with_untracked(|gs| gs.public_state()));
+            }
+        });
+        Effect::new(move |_| channel.send_public_state(&state.with(|gs| gs.public_state())));
+    }
 
     view! {
-        <ErrorBoundary fallback=|errors| {
+        <ErrorBoundary fallback=move |errors| {
             view! {
-                <p>"Errors:"</p>
-                <ul>
-                    {move || {
-                        errors
-                            .get()
-                            .into_iter()
-                            .map(|(_, e)| view! { <li>{e.to_string()}</li> })
-                            .collect_view()
-                    }}
-                </ul>
+                {error_list(errors)}
+                <button on:click=move |_| last_error.0.set(None)>"Dismiss"</button>
             }
         }>
+            {move || last_error.0.get().map_or(Ok(()), Err)}
+            <button on:click=|_| town_square::open_window()>"Open Town Square"</button>
             <div class="h-screen border border-dashed flex justify-between">
                 <Info />
                 <Game />
@@ -537,6 +636,8 @@ fn GameInterface(
         </ErrorBoundary>
 
         <LogDisplay />
+        <TimelineScrubber />
+        <NightOrderDisplay />
     }
     .into_any()
 }
@@ -544,6 +645,7 @@ fn GameInterface(
 #[component]
 fn Info() -> impl IntoView {
     let game_state = expect_context::<Store<State>>();
+    let last_error = expect_context::<LastError>();
     let temp_state = expect_context::<Store<TempState>>();
 
     let stage_info = move || {
@@ -551,13 +653,7 @@ fn Info() -> impl IntoView {
         match step {
             Step::Start => "Start".to_string(),
             Step::Setup => "Setup".to_string(),
-            // Step::DayDiscussion => {
-            //     format!("Day {} Discussion", game_state.day_num().get()).to_string()
-            // }
-            // Step::DayExecution => {
-            //     format!("Day {} Execution", game_state.day_num().get()).to_string()
-            // }
-            Step::Day => format!("Day {}", game_state.day_num().get()).to_string(),
+            Step::Day(day_step) => format!("Day {} {}", game_state.day_num().get(), day_step),
             Step::NightOne | Step::Night => {
                 format!("Night {}", game_state.day_num().get()).to_string()
             }
@@ -577,6 +673,14 @@ fn Info() -> impl IntoView {
             None => "None".to_string(),
         }
     };
+    let change_rules = move || {
+        temp_state.curr_change_request().get().map(|cr| {
+            cr.get_constraints()
+                .iter()
+                .map(|constraint| view! { <li>{constraint.to_string()}</li> })
+                .collect_view()
+        })
+    };
 
     let current_player_info = move || {
         let player_index = temp_state.currently_acting_player().get();
@@ -596,12 +700,7 @@ fn Info() -> impl IntoView {
                 <p>
                     "Status: "{if player.dead { "Dead" } else { "Alive" }}
                     <button on:click=move |_| {
-                        game_state
-                            .players()
-                            .update(|players: &mut Vec<Player>| {
-                                let dead = &mut players[player_index].dead;
-                                *dead = !*dead;
-                            });
+                        last_error.update(game_state, |gs| gs.set_dead(player_index, !player.dead));
                     }>"Toggle"</button>
                 </p>
                 <p>"Ghost Vote: "{if player.dead { "Yes" } else { "No" }}</p>
@@ -629,9 +728,14 @@ fn Info() -> impl IntoView {
                     "Status: "{if player.dead { "Dead" } else { "Alive" }}
                     <button
                         on:click=move |_| {
-                            game_state.update(|gs| gs.execute_player(player_index));
+                            last_error.update(game_state, |gs| gs.execute_player(player_index));
+                        }
+                        disabled=move || {
+                            !matches!(
+                                game_state.step().get(),
+                                Step::Day(DayStep::Nominations | DayStep::Execution)
+                            )
                         }
-                        disabled=move || { !matches!(game_state.step().get(), Step::Day) }
                     >
                         "Execute"
                     </button>
@@ -642,13 +746,22 @@ fn Info() -> impl IntoView {
                         game_state
                             .players()
                             .update(|players: &mut Vec<Player>| {
-                                let ghost_vote = &mut players[player_index].ghost_vote;
-                                *ghost_vote = !*ghost_vote;
+                                if let Some(player) = players.get_mut(player_index) {
+                                    player.ghost_vote = !player.ghost_vote;
+                                }
                             });
                     }>"Toggle"</button>
                 </p>
                 <p>"Alignment: " {player.alignment.to_string()}</p>
             </div>
+            <Show when=move || game_state.mode().get() == GameMode::Manual>
+                <ManualEditor player_index=player_index />
+            </Show>
+            <ReplacementEditor player_index=player_index />
+            <ReminderEditor player_index=player_index />
+            <MadnessEditor player_index=player_index />
+            <ClaimEditor player_index=player_index />
+            <NotesEditor player_index=player_index />
         }
         .into_any();
     };
@@ -658,6 +771,20 @@ fn Info() -> impl IntoView {
                 <h3>"Game Info"</h3>
                 <p>{stage_info}</p>
                 <p>"Change Type: "{change_info}</p>
+                <ul class="list-disc list-inside">{change_rules}</ul>
+                <p>
+                    "Mode: "{move || game_state.mode().get().to_string()}
+                    <button on:click=move |_| {
+                        game_state
+                            .update(|gs| {
+                                let mode = match gs.mode {
+                                    GameMode::Automatic => GameMode::Manual,
+                                    GameMode::Manual => GameMode::Automatic,
+                                };
+                                gs.set_mode(mode);
+                            });
+                    }>"Switch"</button>
+                </p>
             // <For
             // each=move || players.get()
             // key=|p| p.name.clone()
@@ -666,171 +793,1267 @@ fn Info() -> impl IntoView {
             // }
             // />
             </div>
+            <GameOverPanel />
+            <DawnReportDisplay />
+            <DayTimerDisplay />
+            <NightPlanDisplay />
+            <CompanionLink />
+            <SeatingEditor />
             {selected_player_info}
             {current_player_info}
+            <NoteSearch />
         </div>
     }
 }
 
 #[component]
-fn Game() -> impl IntoView {
+fn NightPlanDisplay() -> impl IntoView {
     let game_state = expect_context::<Store<State>>();
-    let temp_state = expect_context::<Store<TempState>>();
-    let next_button = move || {
-        // Ways we can start a next button
-        // No currently_acting_player and no current_cr -> when we just started a step, get the
-        // next currently_acting_player, load up their crs
-        // A currently_acting_player but no current_cr -> Shouldn't actually happen. needs to be
-        // handled within the loop (skipped in a loop)
-        // A currently_acting_player and a cr -> Need to just handle the cr and load up the next
-        // cr, if there no next cr, we move onto the next player, if there is no next_player, we
-        // know the step is over and we call next step
-        // No currently_acting_player but a cr -> this should never happen
-        // If current cr is None, load up a cr and get args for it
-
-        let apply_cr = || {
-            let mut applied_cr = false;
-            loop {
-                let cr = temp_state.curr_change_request().get();
-                if let Some(cr) = cr {
-                    // Resolve Cr
-                    let change_type = cr.get_change_type();
-                    let args = match change_type {
-                        ChangeType::ChoosePlayers(_) => Some(ChangeArgs::PlayerIndices(
-                            temp_state.selected_players().get(),
-                        )),
-                        ChangeType::ChooseRoles(_) => {
-                            Some(ChangeArgs::Roles(temp_state.selected_roles().get()))
-                        }
-                        ChangeType::NoStoryteller => Some(ChangeArgs::Blank),
-                        _ => None,
-                    };
+    let last_error = expect_context::<LastError>();
+
+    let wake_player = RwSignal::new(None::<PlayerIndex>);
+    let wake_reason = RwSignal::new(String::new());
+
+    let entries = move || {
+        let (plan, players) =
+            game_state.with(|gs| (gs.night_plan.clone(), gs.get_players().clone()));
+        plan.entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let pending = plan.is_pending(index);
+                let previous_pending = index > 0 && plan.is_pending(index - 1);
+                let last = index + 1 >= plan.entries.len();
+                let color = if plan.current_index() == Some(index) {
+                    "aquamarine"
+                } else {
+                    ""
+                };
+                let text = format!(
+                    "{}({}) - {}",
+                    players
+                        .get(entry.player_index)
+                        .map(|player| player.name.as_str())
+                        .unwrap_or_default(),
+                    entry.role,
+                    entry.reason
+                );
+                view! {
+                    <p style:background=color class:line-through=entry.skipped class:text-gray-500=!pending>
+                        {text}
+                        <Show when=move || pending>
+                            <button on:click=move |_| {
+                                last_error.update(game_state, |gs| gs.skip_wake(index))
+                            }>"Skip"</button>
+                            <button
+                                on:click=move |_| {
+                                    last_error.update(game_state, |gs| gs.move_wake(index, index - 1))
+                                }
+                                disabled=move || !previous_pending
+                            >
+                                "Up"
+                            </button>
+                            <button
+                                on:click=move |_| {
+                                    last_error.update(game_state, |gs| gs.move_wake(index, index + 1))
+                                }
+                                disabled=move || last
+                            >
+                                "Down"
+                            </button>
+                        </Show>
+                    </p>
+                }
+            })
+            .collect_view()
+    };
 
-                    if let Some(args) = args
-                        && let Some(state_func) = cr.get_state_change_func()
-                    {
-                        let err = game_state
-                            .try_update(|gs| state_func.call(gs, args))
-                            .unwrap();
-                        if let Err(err) = err {
-                            console_log(format!("ChangeType {:?}", change_type).as_str());
-                            console_log(format!("cr: {:#?}", cr).as_str());
-                            console_error(format!("Error: {:?}", err).as_str());
-                            // console_log(format!("TempState: {:#?}", temp_state.get()).as_str());
-                            return (true, applied_cr);
+    view! {
+        <Show when=move || !game_state.night_plan().read().entries.is_empty()>
+            <div class="border border-solid w-full p-[1rem]">
+                <h3>"Night Plan"</h3>
+                {entries}
+                <select on:change=move |ev| {
+                    wake_player.set(event_target_value(&ev).parse::<PlayerIndex>().ok());
+                }>
+                    <option value="">"Choose Player"</option>
+                    {move || {
+                        game_state
+                            .players()
+                            .get()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(player_index, player)| {
+                                view! { <option value=player_index.to_string()>{player.name}</option> }
+                            })
+                            .collect_view()
+                    }}
+                </select>
+                <input type="text" bind:value=wake_reason />
+                <button
+                    on:click=move |_| {
+                        if let Some(player_index) = wake_player.get() {
+                            last_error.update(game_state, |gs| gs.add_wake(player_index, wake_reason.get()));
+                            wake_reason.set(String::new());
                         }
-                        applied_cr = true;
                     }
-                    temp_state.update(|ts| ts.clear_selected());
+                    disabled=move || wake_player.read().is_none() || wake_reason.read().is_empty()
+                >
+                    "Add Wake"
+                </button>
+            </div>
+        </Show>
+    }
+}
 
-                    // Get next cr
-                    let cr = game_state
-                        .try_update(|gs| gs.change_request_queue.pop_front())
-                        .unwrap();
-                    if let Some(cr) = cr {
-                        let cr = cr.build();
-                        let change_type = cr.get_change_type();
-                        temp_state.curr_change_request().set(Some(cr));
-                        if matches!(change_type, ChangeType::NoStoryteller) {
-                            continue;
-                        }
-                        return (true, applied_cr);
+#[component]
+fn GameOverPanel() -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
+
+    let declare = move || {
+        let suggested = game_state.with(|gs| gs.suggested_winner());
+        view! {
+            <p>
+                {suggested.map(|winner| format!("It looks like {} has won. ", winner))}
+                <button on:click=move |_| {
+                    game_state.update(|gs| gs.declare_winner(Alignment::Good))
+                }>"Good Wins"</button>
+                <button on:click=move |_| {
+                    game_state.update(|gs| gs.declare_winner(Alignment::Evil))
+                }>"Evil Wins"</button>
+            </p>
+        }
+    };
+
+    let downloads = move || {
+        let report = game_state.with(GameReport::new);
+        ReportFormat::all()
+            .into_iter()
+            .map(|format| {
+                view! {
+                    <a class="mr-[1rem]" href=report.to_data_url(format) download=format.file_name()>
+                        {format!("Download {}", format)}
+                    </a>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div class="border border-solid w-full p-[1rem]">
+            <Show
+                when=move || game_state.winner().get().is_some()
+                fallback=move || {
+                    view! {
+                        <h3>"End Game"</h3>
+                        {declare}
                     }
-                    break;
                 }
+            >
+                <h3>{move || format!("Game Over: {} Wins", game_state.winner().get().unwrap())}</h3>
+                <p>{downloads}</p>
+            </Show>
+        </div>
+    }
+}
 
-                console_log(format!("Temp State: {:#?}", temp_state.get()).as_str());
-                break;
-            }
+#[component]
+fn DawnReportDisplay() -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
 
-            (false, applied_cr)
+    let report = move || {
+        if game_state.step().get() != Step::Day(DayStep::Dawn) {
+            return None;
+        }
+        let dawn_report = game_state.dawn_report().get()?;
+        let names = move |player_indices: &Vec<PlayerIndex>| {
+            game_state.with(|gs| {
+                player_indices
+                    .iter()
+                    .filter_map(|player_index| gs.get_player(*player_index).ok())
+                    .map(|player| player.name.clone())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
         };
+        let deaths = if dawn_report.deaths.is_empty() {
+            "Nobody died".to_string()
+        } else {
+            format!("Died: {}", names(&dawn_report.deaths))
+        };
+        let failed_kills = (!dawn_report.failed_kills.is_empty())
+            .then(|| format!("Survived a kill: {}", names(&dawn_report.failed_kills)));
 
-        let (ret, mut applied_cr) = apply_cr();
-        if ret {
-            return;
-        }
+        Some(view! {
+            <div class="border border-solid w-full p-[1rem]">
+                <h3>"Dawn Report"</h3>
+                <p>{deaths}</p>
+                <p>{failed_kills}</p>
+                <button
+                    on:click=move |_| game_state.update(|gs| gs.announce_dawn())
+                    disabled=dawn_report.announced
+                >
+                    {if dawn_report.announced { "Announced" } else { "Announce" }}
+                </button>
+            </div>
+        })
+    };
 
-        let mut currently_acting_player = temp_state.currently_acting_player().get();
-        loop {
-            // At this point we know that the change_request_queue has no change_requests in it
-            if currently_acting_player.is_none() {
-                let next_player = game_state.read().get_next_active_player(None);
-                currently_acting_player = next_player;
-                // Advance currently_acting_player by one, if they have something to do, load their
-                // change request and let it be that, if not, then go on to the next loop
-                if let Some(next_player) = next_player {
-                    game_state.update(|gs| gs.resolve(next_player));
-                    if let Some(cr) = game_state
-                        .change_request_queue()
-                        .try_update(|queue| queue.pop_front())
-                        .unwrap()
-                    {
-                        let cr = cr.build();
-                        let change_type = cr.get_change_type();
-                        temp_state.curr_change_request().set(Some(cr));
-                        temp_state.currently_acting_player().set(Some(next_player));
+    view! { {report} }
+}
 
-                        if change_type == ChangeType::NoStoryteller {
-                            let (ret, app_cr) = apply_cr();
-                            applied_cr = app_cr;
-                            if ret {
-                                return;
+#[component]
+fn DayTimerDisplay() -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
+    let last_error = expect_context::<LastError>();
+
+    let show_settings = RwSignal::new(false);
+
+    // Only touch the store while a timer is actually counting down
+    let handle = set_interval_with_handle(
+        move || {
+            let running =
+                game_state.with_untracked(|gs| gs.timer.is_some_and(|timer| timer.running));
+            if running
+                && game_state
+                    .try_update(|gs| gs.tick_timer(1))
+                    .unwrap_or(false)
+            {
+                alert::play_alert();
+            }
+        },
+        std::time::Duration::from_secs(1),
+    );
+    if let Ok(handle) = handle {
+        on_cleanup(move || handle.clear());
+    }
+
+    let timer = move || game_state.timer().get();
+    let controls = move || {
+        let Some(DayTimer {
+            kind,
+            remaining_secs,
+            running,
+        }) = timer()
+        else {
+            return ().into_any();
+        };
+        let expired = remaining_secs == 0;
+        view! {
+            <p style:color=if expired { "red" } else { "" }>
+                {format!("{}: {}", kind, format_secs(remaining_secs))}
+                {if expired { " - Time's up!" } else { "" }}
+            </p>
+            <p>
+                <button
+                    on:click=move |_| game_state.update(|gs| gs.set_timer_running(!running))
+                    disabled=expired
+                >
+                    {if running { "Pause" } else { "Resume" }}
+                </button>
+                <button on:click=move |_| game_state.update(|gs| gs.extend_timer(60))>"+1:00"</button>
+                <button on:click=move |_| game_state.update(|gs| gs.stop_timer())>"Stop"</button>
+            </p>
+        }
+            .into_any()
+    };
+
+    let settings = move || {
+        TimerKind::all()
+            .into_iter()
+            .map(|kind| {
+                view! {
+                    <label class="block">
+                        {format!("{} (seconds per living player) ", kind)}
+                        <input
+                            type="number"
+                            min="0"
+                            prop:value=move || game_state.timer_settings().get().secs_per_player(kind)
+                            on:change=move |ev| {
+                                if let Ok(secs) = event_target_value(&ev).parse() {
+                                    game_state
+                                        .timer_settings()
+                                        .update(|settings| settings.set_secs_per_player(kind, secs));
+                                }
                             }
-                            continue;
-                        }
-                        return;
-                    }
+                        />
+                    </label>
                 }
-            }
+            })
+            .collect_view()
+    };
 
-            // TODO: Can maybe be just a loop
-            while game_state.read().change_request_queue.is_empty()
-                && let Some(acting_player) = currently_acting_player
-            {
-                let next_player = game_state
-                    .read()
-                    .get_next_active_player(Some(acting_player));
+    view! {
+        <Show when=move || matches!(game_state.step().get(), Step::Day(_))>
+            <div class="border border-solid w-full p-[1rem]">
+                <h3>"Day Timer"</h3>
+                {controls}
+                <p>
+                    {TimerKind::all()
+                        .into_iter()
+                        .map(|kind| {
+                            view! {
+                                <button on:click=move |_| {
+                                    last_error.update(game_state, |gs| gs.start_timer(kind))
+                                }>{format!("Start {}", kind)}</button>
+                            }
+                        })
+                        .collect_view()}
+                </p>
+                <label>
+                    <input type="checkbox" bind:checked=show_settings />
+                    "Timer Settings"
+                </label>
+                <Show when=move || show_settings.get()>{settings}</Show>
+            </div>
+        </Show>
+    }
+}
 
-                console_log(format!("Next Player is {:?}", next_player).as_str());
+#[component]
+fn CompanionLink() -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
+    let temp_state = expect_context::<Store<TempState>>();
+    let CompanionServer(server) = expect_context::<CompanionServer>();
 
-                if let Some(next_player) = next_player {
-                    game_state.update(|gs| gs.resolve(next_player));
-                    if let Some(cr) = game_state
-                        .try_update(|gs| gs.change_request_queue.pop_front())
-                        .unwrap()
-                    {
-                        let cr = cr.build();
-                        let change_type = cr.get_change_type();
-                        temp_state.curr_change_request().set(Some(cr));
-                        temp_state.currently_acting_player().set(Some(next_player));
+    let address = RwSignal::new("http://localhost:3030".to_string());
+    let secret = RwSignal::new(String::new());
 
-                        if change_type == ChangeType::NoStoryteller {
-                            let (ret, app_cr) = apply_cr();
-                            applied_cr = app_cr;
-                            if ret {
-                                return;
-                            }
-                            continue;
-                        }
-                        return;
-                    }
-                }
-                currently_acting_player = next_player;
+    // Players' phones always show the latest public state
+    Effect::new(move |_| {
+        let public_state = game_state.with(|gs| gs.public_state());
+        server.with(|server| {
+            if let Some(server) = server {
+                companion::send_public_state(server, &public_state);
             }
+        });
+    });
 
-            // This means the next_player is None
-            temp_state.update(|ts| ts.reset());
-            if game_state.read().step == Step::Day && applied_cr {
-                return;
+    // Tokens are made up as they're needed, which doesn't change anything worth redrawing
+    let send_private_message = move |name: &str, text: &str| {
+        server.update_untracked(|server| {
+            if let Some(server) = server {
+                companion::send_private_message(server, name, text);
             }
-            console_log(format!("Applied cr: {}", applied_cr).as_str());
-            game_state.update(|gs| gs.next_step());
-            if matches!(game_state.read().step, Step::Day) {
-                return;
+        });
+    };
+
+    let send_starting_info = move |_| {
+        game_state.with_untracked(|gs| {
+            for (player_index, player) in gs.get_players().iter().enumerate() {
+                if player.left {
+                    continue;
+                }
+                if let Ok(info) = gs.starting_info(player_index) {
+                    send_private_message(&player.name, &info);
+                }
             }
-        }
-        // console_log("Moving to next player");
+        });
+    };
+
+    let player_links = move || {
+        let names: Vec<String> = game_state.with(|gs| {
+            gs.get_players()
+                .iter()
+                .filter(|player| !player.left)
+                .map(|player| player.name.clone())
+                .collect()
+        });
+        server.track();
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let link = server
+                    .try_update_untracked(|server| Some(server.as_mut()?.player_link(&name)))??;
+                Some(view! {
+                    <li>
+                        {format!("{}: ", name)}
+                        <a href=link.clone() target="_blank">
+                            {link.clone()}
+                        </a>
+                    </li>
+                })
+            })
+            .collect_view()
+    };
+
+    // Whatever the storyteller would have shown the acting player
+    let shown_info = move || {
+        let cr = temp_state.curr_change_request().get()?;
+        if !matches!(cr.get_change_type(), ChangeType::Display) {
+            return None;
+        }
+        let player_index = temp_state.currently_acting_player().get()?;
+        let name = game_state.with(|gs| Some(gs.get_player(player_index).ok()?.name.clone()))?;
+        Some((name, cr.get_description()))
+    };
+
+    view! {
+        <div class="border border-solid w-full p-[1rem]">
+            <h3>"Player Companion"</h3>
+            <Show
+                when=move || server.read().is_some()
+                fallback=move || {
+                    view! {
+                        <input type="text" bind:value=address />
+                        <input
+                            type="password"
+                            placeholder="Secret the server printed"
+                            bind:value=secret
+                        />
+                        <button on:click=move |_| {
+                            let connection = Connection::new(address.get(), secret.get());
+                            companion::reset(&connection);
+                            server.set(Some(connection));
+                        }>"Connect"</button>
+                    }
+                }
+            >
+                <p>
+                    {move || {
+                        server
+                            .with(|server| {
+                                server
+                                    .as_ref()
+                                    .map(|server| format!("Connected to {}", server.address))
+                            })
+                    }}
+                    <button on:click=move |_| server.set(None)>"Disconnect"</button>
+                </p>
+                <p>
+                    <button on:click=send_starting_info>"Send Players Their Characters"</button>
+                </p>
+                <p>"Give each player their own link:"</p>
+                <ul>{player_links}</ul>
+                {move || {
+                    shown_info()
+                        .map(|(name, text)| {
+                            view! {
+                                <button on:click=move |_| {
+                                    send_private_message(&name, &text);
+                                }>{format!("Send Info To {}", name)}</button>
+                            }
+                        })
+                }}
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+fn SeatingEditor() -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
+    let last_error = expect_context::<LastError>();
+    let temp_state = expect_context::<Store<TempState>>();
+
+    let dragged_seat = RwSignal::new(None::<PlayerIndex>);
+    let swap_seat = RwSignal::new(None::<PlayerIndex>);
+
+    let can_reseat = move || {
+        game_state.with(|gs| gs.can_reseat()) && temp_state.curr_change_request().read().is_none()
+    };
+    let reseat = move |f: &dyn Fn(&mut State) -> Result<(), GameError>| {
+        if !can_reseat() {
+            return;
+        }
+        last_error.update(game_state, |gs| f(gs));
+        // Anything the interface remembers by seat is out of date now
+        let acting_player =
+            game_state.with(|gs| gs.night_plan.current().map(|entry| entry.player_index));
+        temp_state.update(|ts| {
+            ts.clear_selected();
+            ts.currently_acting_player = acting_player;
+        });
+    };
+
+    let seats = move || {
+        let (players, couch_links) =
+            game_state.with(|gs| (gs.get_players().clone(), gs.couch_links.clone()));
+        players
+            .into_iter()
+            .enumerate()
+            .filter(|(_, player)| !player.left)
+            .map(|(seat, player)| {
+                let swapping = move || swap_seat.get() == Some(seat);
+                view! {
+                    <p
+                        draggable="true"
+                        class="border border-solid cursor-move"
+                        style:background=move || if swapping() { "aquamarine" } else { "" }
+                        on:dragstart=move |_| dragged_seat.set(Some(seat))
+                        on:dragover=move |ev| ev.prevent_default()
+                        on:drop=move |ev| {
+                            ev.prevent_default();
+                            if let Some(from) = dragged_seat.get() {
+                                reseat(&|gs| gs.move_seat(from, seat));
+                            }
+                            dragged_seat.set(None);
+                        }
+                    >
+                        {format!("{}. {}", seat + 1, player.name)}
+                        <button on:click=move |_| {
+                            match swap_seat.get() {
+                                Some(other) if other != seat => {
+                                    reseat(&|gs| gs.swap_seats(other, seat));
+                                    swap_seat.set(None);
+                                }
+                                Some(_) => swap_seat.set(None),
+                                None => swap_seat.set(Some(seat)),
+                            }
+                        }>"Swap"</button>
+                        <label>
+                            <input
+                                type="checkbox"
+                                prop:checked=couch_links[seat]
+                                on:change=move |_| {
+                                    last_error.update(game_state, |gs| gs.toggle_couch_link(seat))
+                                }
+                            />
+                            "Couch With Next"
+                        </label>
+                    </p>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div class="border border-solid w-full p-[1rem]">
+            <h3>"Seating"</h3>
+            <Show
+                when=can_reseat
+                fallback=|| view! { <p>"Finish resolving the current ability to change seats"</p> }
+            >
+                {seats}
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+fn ManualEditor(player_index: PlayerIndex) -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
+    let last_error = expect_context::<LastError>();
+    let script = expect_context::<RwSignal<Script>>();
+
+    let player = move || game_state.players().read()[player_index].clone();
+
+    view! {
+        <div class="border border-solid w-full p-[1rem]">
+            <h3>"Storyteller Changes"</h3>
+            <p>
+                <button on:click=move |_| {
+                    let dead = player().dead;
+                    last_error.update(game_state, |gs| gs.set_dead(player_index, !dead));
+                }>{move || if player().dead { "Revive" } else { "Kill" }}</button>
+            </p>
+            <p>
+                "Role: "
+                <select
+                    prop:value=move || player().role.to_string()
+                    on:change=move |ev| {
+                        let value = event_target_value(&ev);
+                        let role = script
+                            .read()
+                            .roles
+                            .iter()
+                            .find(|role| role.to_string() == value)
+                            .copied();
+                        if let Some(role) = role {
+                            last_error.update(game_state, |gs| gs.change_role(player_index, role));
+                        }
+                    }
+                >
+                    {move || {
+                        script
+                            .get()
+                            .roles
+                            .into_iter()
+                            .map(|role| {
+                                view! { <option value=role.to_string()>{role.to_string()}</option> }
+                            })
+                            .collect_view()
+                    }}
+                </select>
+            </p>
+            <p>
+                "Alignment: "
+                <select
+                    prop:value=move || player().alignment.to_string()
+                    on:change=move |ev| {
+                        let alignment = match event_target_value(&ev).as_str() {
+                            "Evil" => Alignment::Evil,
+                            _ => Alignment::Good,
+                        };
+                        last_error.update(game_state, |gs| gs.set_alignment(player_index, alignment));
+                    }
+                >
+                    <option value="Good">"Good"</option>
+                    <option value="Evil">"Evil"</option>
+                </select>
+            </p>
+        </div>
+    }
+}
+
+#[component]
+fn ReplacementEditor(player_index: PlayerIndex) -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
+    let last_error = expect_context::<LastError>();
+    let temp_state = expect_context::<Store<TempState>>();
+
+    let new_name = RwSignal::new(String::new());
+    let traveller = move || {
+        game_state.with(|gs| {
+            gs.get_player(player_index)
+                .is_ok_and(|player| player.traveller)
+        })
+    };
+
+    view! {
+        <div class="border border-solid w-full p-[1rem]">
+            <h3>"Seat"</h3>
+            <p>
+                <input type="text" placeholder="New player name" bind:value=new_name />
+                <button
+                    on:click=move |_| {
+                        let name = new_name.get().trim().to_string();
+                        last_error.update(game_state, |gs| gs.replace_player(player_index, name));
+                        new_name.set(String::new());
+                    }
+                    disabled=move || new_name.read().trim().is_empty()
+                >
+                    "Replace Player"
+                </button>
+            </p>
+            <p>
+                <button on:click=move |_| {
+                    last_error.update(game_state, |gs| gs.player_leaves(player_index));
+                    temp_state.selected_player().set(None);
+                }>"Player Leaves"</button>
+            </p>
+            <p>
+                <label>
+                    <input
+                        type="checkbox"
+                        prop:checked=traveller
+                        on:change=move |ev| {
+                            let checked = event_target_checked(&ev);
+                            last_error.update(game_state, |gs| gs.set_traveller(player_index, checked));
+                        }
+                    />
+                    "Traveller"
+                </label>
+                <button
+                    on:click=move |_| {
+                        last_error.update(game_state, |gs| gs.exile_player(player_index));
+                    }
+                    disabled=move || !traveller()
+                >
+                    "Exile"
+                </button>
+            </p>
+        </div>
+    }
+}
+
+#[component]
+fn ClaimEditor(player_index: PlayerIndex) -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
+    let last_error = expect_context::<LastError>();
+    let script = expect_context::<RwSignal<Script>>();
+
+    let claimed_role = RwSignal::new(None::<RoleNames>);
+    let public = RwSignal::new(true);
+
+    let claims = move || {
+        let claims = game_state.with(|gs| gs.get_claims(player_index).cloned().unwrap_or_default());
+        claims
+            .into_iter()
+            .enumerate()
+            .map(|(claim_index, claim)| {
+                view! {
+                    <p>
+                        {claim.to_string()}
+                        <button on:click=move |_| {
+                            last_error.update(game_state, |gs| gs.remove_claim(player_index, claim_index))
+                        }>"Remove"</button>
+                    </p>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div class="border border-solid w-full p-[1rem]">
+            <h3>"Claims"</h3>
+            {claims}
+            <select on:change=move |ev| {
+                let value = event_target_value(&ev);
+                claimed_role
+                    .set(script.read().roles.iter().find(|role| role.to_string() == value).copied());
+            }>
+                <option value="">"Choose Role"</option>
+                {move || {
+                    script
+                        .get()
+                        .roles
+                        .into_iter()
+                        .map(|role| view! { <option value=role.to_string()>{role.to_string()}</option> })
+                        .collect_view()
+                }}
+            </select>
+            <label>
+                <input type="checkbox" bind:checked=public />
+                "Public"
+            </label>
+            <button
+                on:click=move |_| {
+                    if let Some(role) = claimed_role.get() {
+                        last_error.update(game_state, |gs| gs.add_claim(player_index, role, public.get_untracked()));
+                    }
+                }
+                disabled=move || claimed_role.read().is_none()
+            >
+                "Add Claim"
+            </button>
+        </div>
+    }
+}
+
+#[component]
+fn NotesEditor(player_index: PlayerIndex) -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
+    let last_error = expect_context::<LastError>();
+
+    let note_kind = RwSignal::new(NoteKind::FreeText);
+    let note_text = RwSignal::new(String::new());
+
+    let current_notes = move || {
+        let notes = game_state.with(|gs| {
+            gs.get_player(player_index)
+                .map(|player| player.notes.clone())
+                .unwrap_or_default()
+        });
+        notes
+            .into_iter()
+            .enumerate()
+            .map(|(note_index, note)| {
+                view! {
+                    <p>
+                        {format!("[{}] {}: ", note.timestamp(), note.kind)}
+                        <input
+                            type="text"
+                            prop:value=note.text
+                            on:change=move |ev| {
+                                let text = event_target_value(&ev);
+                                last_error.update(game_state, |gs| gs.edit_note(player_index, note_index, text));
+                            }
+                        />
+                        <button on:click=move |_| {
+                            last_error.update(game_state, |gs| gs.remove_note(player_index, note_index))
+                        }>"Remove"</button>
+                    </p>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div class="border border-solid w-full p-[1rem]">
+            <h3>"Notes"</h3>
+            {current_notes}
+            <select on:change=move |ev| {
+                let value = event_target_value(&ev);
+                if let Some(kind) = NoteKind::all().into_iter().find(|kind| kind.to_string() == value) {
+                    note_kind.set(kind);
+                }
+            }>
+                {NoteKind::all()
+                    .into_iter()
+                    .map(|kind| view! { <option value=kind.to_string()>{kind.to_string()}</option> })
+                    .collect_view()}
+            </select>
+            <input type="text" bind:value=note_text />
+            <button
+                on:click=move |_| {
+                    let text = note_text.get();
+                    last_error.update(game_state, |gs| gs.add_note(player_index, note_kind.get_untracked(), text));
+                    note_text.set(String::new());
+                }
+                disabled=move || note_text.read().is_empty()
+            >
+                "Add"
+            </button>
+        </div>
+    }
+}
+
+#[component]
+fn NoteSearch() -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
+    let query = RwSignal::new(String::new());
+
+    let results = move || {
+        let query = query.get();
+        if query.is_empty() {
+            return ().into_any();
+        }
+        game_state
+            .with(|gs| {
+                gs.search_notes(&query)
+                    .into_iter()
+                    .filter_map(|(player_index, note)| {
+                        let player = gs.get_player(player_index).ok()?;
+                        Some(format!("{} {}", player.name, note))
+                    })
+                    .collect::<Vec<String>>()
+            })
+            .into_iter()
+            .map(|result| view! { <p>{result}</p> })
+            .collect_view()
+            .into_any()
+    };
+
+    view! {
+        <div class="border border-solid w-full p-[1rem]">
+            <h3>"Search Notes"</h3>
+            <input type="text" bind:value=query />
+            {results}
+        </div>
+    }
+}
+
+/// Roles whose abilities can make a player mad
+const MADNESS_ROLES: [RoleNames; 4] = [
+    RoleNames::Cerenovus,
+    RoleNames::Mutant,
+    RoleNames::Harpy,
+    RoleNames::Pixie,
+];
+
+#[component]
+fn MadnessEditor(player_index: PlayerIndex) -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
+    let temp_state = expect_context::<Store<TempState>>();
+    let last_error = expect_context::<LastError>();
+    let script = expect_context::<RwSignal<Script>>();
+
+    let mad_role = RwSignal::new(None::<RoleNames>);
+    let by = RwSignal::new(MADNESS_ROLES[0]);
+    let cleanup_phase = RwSignal::new(Some(CleanupPhase::Dusk));
+
+    let break_madness = move |role: RoleNames| {
+        last_error.update(game_state, |gs| gs.break_madness(player_index, role));
+        // Let the storyteller deal with the consequence right away
+        if temp_state.curr_change_request().read_untracked().is_none()
+            && let Some(cr) = game_state
+                .try_update(|gs| gs.next_change_request())
+                .unwrap()
+        {
+            temp_state.curr_change_request().set(Some(cr.build()));
+        }
+    };
+
+    let current_madness = move || {
+        game_state
+            .with(|gs| gs.madness(player_index))
+            .into_iter()
+            .map(|(role, by)| {
+                view! {
+                    <p>
+                        {format!("Mad about being the {} ({})", role, by)}
+                        <button
+                            disabled=move || !matches!(game_state.step().get(), Step::Day(_))
+                            on:click=move |_| break_madness(role)
+                        >
+                            "Broke madness"
+                        </button>
+                    </p>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div class="border border-solid w-full p-[1rem]">
+            <h3>"Madness"</h3>
+            {current_madness}
+            <select on:change=move |ev| {
+                let value = event_target_value(&ev);
+                mad_role
+                    .set(script.read().roles.iter().find(|role| role.to_string() == value).copied());
+            }>
+                <option value="">"Mad about being..."</option>
+                {move || {
+                    script
+                        .get()
+                        .roles
+                        .into_iter()
+                        .map(|role| view! { <option value=role.to_string()>{role.to_string()}</option> })
+                        .collect_view()
+                }}
+            </select>
+            <select on:change=move |ev| {
+                let value = event_target_value(&ev);
+                if let Some(role) = MADNESS_ROLES.iter().find(|role| role.to_string() == value) {
+                    by.set(*role);
+                }
+            }>
+                {MADNESS_ROLES
+                    .iter()
+                    .map(|role| view! { <option value=role.to_string()>{role.to_string()}</option> })
+                    .collect_view()}
+            </select>
+            <select on:change=move |ev| {
+                let phase = match event_target_value(&ev).as_str() {
+                    "Dusk" => Some(CleanupPhase::Dusk),
+                    _ => None,
+                };
+                cleanup_phase.set(phase);
+            }>
+                <option value="Dusk">"Until Dusk"</option>
+                <option value="">"For the rest of the game"</option>
+            </select>
+            <button
+                disabled=move || mad_role.get().is_none()
+                on:click=move |_| {
+                    if let Some(role) = mad_role.get_untracked() {
+                        last_error
+                            .update(
+                                game_state,
+                                |gs| {
+                                    gs.make_mad(
+                                        player_index,
+                                        role,
+                                        by.get_untracked(),
+                                        cleanup_phase.get_untracked(),
+                                    )
+                                },
+                            );
+                    }
+                }
+            >
+                "Make mad"
+            </button>
+        </div>
+    }
+}
+
+#[component]
+fn ReminderEditor(player_index: PlayerIndex) -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
+    let last_error = expect_context::<LastError>();
+    let script = expect_context::<RwSignal<Script>>();
+
+    let reminder_role = RwSignal::new(None::<RoleNames>);
+    let custom_text = RwSignal::new(String::new());
+    let cleanup_phase = RwSignal::new(None::<CleanupPhase>);
+    let affects_behaviour = RwSignal::new(false);
+
+    let add_reminder = move |status_type: StatusType| {
+        last_error.update(game_state, |gs| {
+            gs.add_reminder(
+                player_index,
+                status_type,
+                reminder_role.get_untracked(),
+                cleanup_phase.get_untracked(),
+                affects_behaviour.get_untracked(),
+            )
+        });
+    };
+
+    let current_reminders = move || {
+        let status_effects = game_state.with(|gs| {
+            gs.get_player(player_index)
+                .map(|player| player.status_effects.clone())
+                .unwrap_or_default()
+        });
+        status_effects
+            .into_iter()
+            .enumerate()
+            .map(|(status_index, status_effect)| {
+                view! {
+                    <p>
+                        {status_effect.to_string()}
+                        {if status_effect.manual { " (Manual)" } else { "" }}
+                        <button on:click=move |_| {
+                            last_error.update(game_state, |gs| gs.remove_reminder(player_index, status_index))
+                        }>"Remove"</button>
+                    </p>
+                }
+            })
+            .collect_view()
+    };
+
+    let reminder_picker = move || {
+        match reminder_role.get() {
+        Some(role) => role
+            .reminders()
+            .into_iter()
+            .map(|status_type| {
+                let label = status_type.to_string();
+                view! { <button on:click=move |_| add_reminder(status_type.clone())>{label}</button> }
+            })
+            .collect_view()
+            .into_any(),
+        None => view! {
+            <input type="text" bind:value=custom_text />
+            <button
+                on:click=move |_| {
+                    add_reminder(StatusType::Custom(custom_text.get()));
+                    custom_text.set(String::new());
+                }
+                disabled=move || custom_text.read().is_empty()
+            >
+                "Add"
+            </button>
+        }
+        .into_any(),
+    }
+    };
+
+    view! {
+        <div class="border border-solid w-full p-[1rem]">
+            <h3>"Reminders"</h3>
+            {current_reminders}
+            <select on:change=move |ev| {
+                let value = event_target_value(&ev);
+                reminder_role
+                    .set(script.read().roles.iter().find(|role| role.to_string() == value).copied());
+            }>
+                <option value="">"Custom"</option>
+                {move || {
+                    script
+                        .get()
+                        .roles
+                        .into_iter()
+                        .filter(|role| !role.reminders().is_empty())
+                        .map(|role| view! { <option value=role.to_string()>{role.to_string()}</option> })
+                        .collect_view()
+                }}
+            </select>
+            <div>{reminder_picker}</div>
+            <select on:change=move |ev| {
+                let phase = match event_target_value(&ev).as_str() {
+                    "Dusk" => Some(CleanupPhase::Dusk),
+                    "Dawn" => Some(CleanupPhase::Dawn),
+                    _ => None,
+                };
+                cleanup_phase.set(phase);
+            }>
+                <option value="">"Never Cleaned Up"</option>
+                <option value="Dusk">"Cleaned Up At Dusk"</option>
+                <option value="Dawn">"Cleaned Up At Dawn"</option>
+            </select>
+            <label>
+                <input type="checkbox" bind:checked=affects_behaviour />
+                "Affects Behaviour"
+            </label>
+        </div>
+    }
+}
+
+#[component]
+fn Game() -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
+    let temp_state = expect_context::<Store<TempState>>();
+    let timeline = expect_context::<RwSignal<Timeline>>();
+    let last_error = expect_context::<LastError>();
+    let next_button = move || {
+        // Ways we can start a next button
+        // No currently_acting_player and no current_cr -> when we just started a step, get the
+        // next currently_acting_player, load up their crs
+        // A currently_acting_player but no current_cr -> Shouldn't actually happen. needs to be
+        // handled within the loop (skipped in a loop)
+        // A currently_acting_player and a cr -> Need to just handle the cr and load up the next
+        // cr, if there no next cr, we move onto the next player, if there is no next_player, we
+        // know the step is over and we call next step
+        // No currently_acting_player but a cr -> this should never happen
+        // If current cr is None, load up a cr and get args for it
+
+        let apply_cr = || {
+            let mut applied_cr = false;
+            loop {
+                let cr = temp_state.curr_change_request().get();
+                if let Some(cr) = cr {
+                    // Resolve Cr
+                    let change_type = cr.get_change_type();
+                    let args = match change_type {
+                        _ if temp_state.skipped().get() => Some(ChangeArgs::Blank),
+                        ChangeType::ChoosePlayers(_) => Some(ChangeArgs::PlayerIndices(
+                            temp_state.selected_players().get(),
+                        )),
+                        ChangeType::ChooseRoles(_) => {
+                            Some(ChangeArgs::Roles(temp_state.selected_roles().get()))
+                        }
+                        ChangeType::ChoosePlayersAndRoles(_) => Some(ChangeArgs::PlayersAndRoles(
+                            temp_state.with(|ts| ts.selected_pairs()),
+                        )),
+                        ChangeType::YesNo => Some(
+                            temp_state
+                                .selected_yes_no()
+                                .get()
+                                .map_or(ChangeArgs::Blank, ChangeArgs::YesNo),
+                        ),
+                        ChangeType::ChooseNumber { min, .. } => Some(ChangeArgs::Number(
+                            temp_state.selected_number().get().unwrap_or(min),
+                        )),
+                        ChangeType::NoStoryteller => Some(ChangeArgs::Blank),
+                        _ => None,
+                    };
+
+                    // Keep track of what each player was told
+                    if change_type == ChangeType::Display
+                        && let Some(player_index) = temp_state.currently_acting_player().get()
+                    {
+                        game_state.update(|gs| gs.record_info(player_index, cr.get_description()));
+                    }
+
+                    // In manual mode the storyteller makes the changes themselves
+                    if let Some(args) = args
+                        && cr.get_state_change_func().is_some()
+                        && game_state.read().mode == GameMode::Automatic
+                    {
+                        let err = game_state.try_update(|gs| cr.apply(gs, args)).unwrap();
+                        // The selection stays so the storyteller can fix it
+                        if let Err(err) = err {
+                            last_error.0.set(Some(err));
+                            return (true, applied_cr);
+                        }
+                        applied_cr = true;
+                    }
+                    temp_state.update(|ts| ts.clear_selected());
+
+                    // Get next cr
+                    let cr = game_state
+                        .try_update(|gs| gs.next_change_request())
+                        .unwrap();
+                    if let Some(cr) = cr {
+                        let cr = cr.build();
+                        let change_type = cr.get_change_type();
+                        temp_state.curr_change_request().set(Some(cr));
+                        if matches!(change_type, ChangeType::NoStoryteller) {
+                            continue;
+                        }
+                        return (true, applied_cr);
+                    }
+                    break;
+                }
+
+                console_log(format!("Temp State: {:#?}", temp_state.get()).as_str());
+                break;
+            }
+
+            (false, applied_cr)
+        };
+
+        let (ret, mut applied_cr) = apply_cr();
+        if ret {
+            return;
+        }
+
+        // Change requests queued outside of anyone's wake (executions, the start of a part of
+        // the day) are resolved before moving on
+        if temp_state.curr_change_request().read().is_none()
+            && let Some(cr) = game_state
+                .try_update(|gs| gs.next_change_request())
+                .unwrap()
+        {
+            let cr = cr.build();
+            let change_type = cr.get_change_type();
+            temp_state.curr_change_request().set(Some(cr));
+            if change_type != ChangeType::NoStoryteller {
+                return;
+            }
+            let (ret, app_cr) = apply_cr();
+            applied_cr = app_cr;
+            if ret {
+                return;
+            }
+        }
+
+        let mut currently_acting_player = temp_state.currently_acting_player().get();
+        loop {
+            // At this point we know that the change_request_queue has no change_requests in it
+            if currently_acting_player.is_none() {
+                let next_player = game_state
+                    .try_update(|gs| gs.advance_active_player())
+                    .unwrap();
+                currently_acting_player = next_player;
+                // Advance currently_acting_player by one, if they have something to do, load their
+                // change request and let it be that, if not, then go on to the next loop
+                if let Some(next_player) = next_player {
+                    game_state.update(|gs| gs.resolve(next_player));
+                    if let Some(cr) = game_state
+                        .try_update(|gs| gs.next_change_request())
+                        .unwrap()
+                    {
+                        let cr = cr.build();
+                        let change_type = cr.get_change_type();
+                        temp_state.curr_change_request().set(Some(cr));
+                        temp_state.currently_acting_player().set(Some(next_player));
+
+                        if change_type == ChangeType::NoStoryteller {
+                            let (ret, app_cr) = apply_cr();
+                            applied_cr = app_cr;
+                            if ret {
+                                return;
+                            }
+                            continue;
+                        }
+                        return;
+                    }
+                }
+            }
+
+            // TODO: Can maybe be just a loop
+            while game_state.read().change_request_queue.is_empty()
+                && currently_acting_player.is_some()
+            {
+                let next_player = game_state
+                    .try_update(|gs| gs.advance_active_player())
+                    .unwrap();
+
+                console_log(format!("Next Player is {:?}", next_player).as_str());
+
+                if let Some(next_player) = next_player {
+                    game_state.update(|gs| gs.resolve(next_player));
+                    if let Some(cr) = game_state
+                        .try_update(|gs| gs.next_change_request())
+                        .unwrap()
+                    {
+                        let cr = cr.build();
+                        let change_type = cr.get_change_type();
+                        temp_state.curr_change_request().set(Some(cr));
+                        temp_state.currently_acting_player().set(Some(next_player));
+
+                        if change_type == ChangeType::NoStoryteller {
+                            let (ret, app_cr) = apply_cr();
+                            applied_cr = app_cr;
+                            if ret {
+                                return;
+                            }
+                            continue;
+                        }
+                        return;
+                    }
+                }
+                currently_acting_player = next_player;
+            }
+
+            // This means the next_player is None
+            temp_state.update(|ts| ts.reset());
+            if matches!(game_state.read().step, Step::Day(_)) && applied_cr {
+                return;
+            }
+            console_log(format!("Applied cr: {}", applied_cr).as_str());
+            game_state.update(|gs| gs.next_step());
+            if matches!(game_state.read().step, Step::Day(_)) {
+                return;
+            }
+        }
+        // console_log("Moving to next player");
         // console_log(
         //     format!(
         //         "Cr stack is currently {:#?}",
@@ -842,6 +2065,31 @@ fn Game() -> impl IntoView {
         // Only get the next player's change requests if the current change request queue is empty
     };
 
+    // Throws away everything the acting player's ability did and wakes them again
+    let restart_ability = move |_| match game_state.try_update(|gs| gs.restart_ability()).unwrap() {
+        Err(err) => last_error.0.set(Some(err)),
+        Ok(player_index) => {
+            game_state.with_untracked(|gs| timeline.update(|tl| tl.rewind(gs)));
+            temp_state.update(|ts| ts.clear_selected());
+            let cr = game_state
+                .try_update(|gs| gs.next_change_request())
+                .unwrap();
+            temp_state
+                .curr_change_request()
+                .set(cr.map(|cr| cr.build()));
+            temp_state.currently_acting_player().set(Some(player_index));
+        }
+    };
+    let restart_button = move || {
+        temp_state.currently_acting_player().get().map(|_| {
+            view! {
+                <button class="absolute right-[0px] top-[2rem]" on:click=restart_ability>
+                    "Restart ability"
+                </button>
+            }
+        })
+    };
+
     let game_element: NodeRef<leptos::html::Div> = NodeRef::new();
 
     view! {
@@ -870,6 +2118,7 @@ fn Game() -> impl IntoView {
             <button class="absolute right-[0px] top-[0px]" on:click=move |_| next_button()>
                 "Next"
             </button>
+            {restart_button}
         </div>
     }
 }
@@ -878,10 +2127,34 @@ fn Game() -> impl IntoView {
 fn Player_Display() -> impl IntoView {
     let game_state = expect_context::<Store<State>>();
     let players = game_state.players();
-    let player_positions = calc_circle(players.read_untracked().len(), 75.0);
+    // Players who left don't take up a spot in the circle anymore
+    let player_positions = Memo::new(move |_| {
+        let players = players.read();
+        let seated = players.iter().filter(|player| !player.left).count();
+        let mut circle = calc_circle(seated, 75.0).into_iter();
+        players
+            .iter()
+            .map(|player| if player.left { None } else { circle.next() })
+            .collect::<Vec<_>>()
+    });
 
     let temp_state = expect_context::<Store<TempState>>();
     let currently_selected_player = temp_state.selected_player();
+
+    // Seats on the same couch get the same border color. Seats on their own don't get one
+    let couch_color = move |seat: PlayerIndex| {
+        const COUCH_COLORS: [&str; 6] = [
+            "#e6194b", "#3cb44b", "#ffe119", "#4363d8", "#f58231", "#911eb4",
+        ];
+        game_state.with(|gs| {
+            let couches = gs.couches();
+            let couch = couches[seat];
+            if couches.iter().filter(|other| **other == couch).count() < 2 {
+                return "";
+            }
+            COUCH_COLORS[couch % COUCH_COLORS.len()]
+        })
+    };
     let selected_players = temp_state.selected_players();
     // Want to place children in a circle within the container, centered around it's origin
     // Radius of circle should dynamically grow based on number of items
@@ -889,32 +2162,40 @@ fn Player_Display() -> impl IntoView {
     view! {
         <div class="relative origin-bottom-right size-1/2 flex flex-wrap rounded-full justify-between items-between">
             <For
-                each=move || players.get().into_iter().enumerate()
+                each=move || {
+                    players.get().into_iter().enumerate().filter(|(_, player)| !player.left)
+                }
                 key=|(i, _)| *i
                 children=move |(i, _)| {
-                    let pos = player_positions[i];
+                    let pos = move || player_positions.read()[i].unwrap_or_default();
                     let player = Memo::new(move |_| players.get()[i].clone());
                     console_log("New Signal Created");
                     let selected = move || temp_state.selected_players().get().contains(&i);
+                    // Why the current change request won't accept this player
+                    let disabled_reason = move || {
+                        temp_state
+                            .curr_change_request()
+                            .get()
+                            .and_then(|cr| cr.player_disabled_reason(i, &player.read()))
+                    };
 
                     view! {
                         <div
                             class="translate-1/2 absolute size-fit"
-                            style:right=move || { format!("calc(50% + {}%)", pos.0) }
-                            style:top=move || format!("calc(35% + {}%)", pos.1)
+                            style:right=move || { format!("calc(50% + {}%)", pos().0) }
+                            style:top=move || format!("calc(35% + {}%)", pos().1)
                         >
-                            <p class="absolute left-1/2 -translate-x-1/2 bottom-3/5 border-solid border text-center bg-[#ffffff]">
+                            <p
+                                class="absolute left-1/2 -translate-x-1/2 bottom-3/5 border-solid border text-center bg-[#ffffff]"
+                                style:border-color=move || couch_color(i)
+                                style:border-width=move || if couch_color(i).is_empty() { "" } else { "3px" }
+                            >
                                 {player.get().name}
                             </p>
                             <button
                                 class="size-[5rem] rounded-full text-center border border-[#000000]"
-                                disabled=move || {
-                                    if let Some(cr) = temp_state.curr_change_request().get() && let Some(filter_func) = cr.get_filter_func() {
-                                            return !filter_func.call(i, &player.read());
-                                        }
-
-                                    false
-                                }
+                                disabled=move || disabled_reason().is_some()
+                                title=move || disabled_reason().unwrap_or_default()
                                 style:border-style=move || {
                                     if selected() {
                                         "solid"
@@ -952,8 +2233,12 @@ fn Player_Display() -> impl IntoView {
                                         return;
                                     }
                                     let cr = temp_state.curr_change_request().get().unwrap();
-                                    let requested_num = match cr.get_change_type() {
-                                        ChangeType::ChoosePlayers(num) => num,
+                                    let requested_num = match (cr.get_change_type(), cr.count_range()) {
+                                        (
+                                            ChangeType::ChoosePlayers(_)
+                                            | ChangeType::ChoosePlayersAndRoles(_),
+                                            Some((_, max)),
+                                        ) => max,
                                         _ => {
                                             currently_selected_player.set(Some(i));
                                             return;
@@ -977,19 +2262,45 @@ fn Player_Display() -> impl IntoView {
                                 }
                             >
                                 {move || { player.get().role.to_string() }}
+                                {move || {
+                                    game_state
+                                        .with(|gs| gs.current_claim(i).copied())
+                                        .map(|claim| {
+                                            view! {
+                                                <span class="block text-[0.6rem] italic">
+                                                    {format!("Claims {}", claim.role)}
+                                                </span>
+                                            }
+                                        })
+                                }}
                             </button>
+                            // Note count, the notes themselves are edited from the selected player
+                            <Show when=move || !player.read().notes.is_empty()>
+                                <p class="absolute right-[0px] top-[0px] text-[0.5rem] border border-solid rounded-full px-[3px] bg-[#ffffff]">
+                                    {move || player.read().notes.len()}
+                                </p>
+                            </Show>
                             // Status effects
                             <div class="text-[0.5rem] flex flex-row flex-wrap justify-center items-start absolute w-fit border left-1/2 -translate-x-1/2 top-9/10 ">
                                 {move || {
                                     let status_effects = game_state
-                                        .with(|gs| gs.get_player(i).status_effects.clone());
+                                        .with(|gs| {
+                                            gs.get_player(i)
+                                                .map(|player| player.status_effects.clone())
+                                                .unwrap_or_default()
+                                        });
                                     status_effects
                                         .iter()
                                         .map(|status_effect| {
                                             let str = status_effect.status_type.to_string();
+                                            // Storyteller placed reminders are a different color
+                                            let color = if status_effect.manual { "#ccccff" } else { "#ffff00" };
                                             view! {
                                                 // TODO: Standardize effect box sizes
-                                                <p class="size-fit text-center border border-solid m-[0%] rounded-full p-[5px] bg-[#ffff00]">
+                                                <p
+                                                    class="size-fit text-center border border-solid m-[0%] rounded-full p-[5px]"
+                                                    style:background=color
+                                                >
                                                     {str}
                                                 </p>
                                             }
@@ -1005,6 +2316,103 @@ fn Player_Display() -> impl IntoView {
     }
 }
 
+/// Second screen for the players. Only ever sees the public state the storyteller's window sends
+/// it
+#[component]
+fn TownSquare() -> impl IntoView {
+    let public_state = RwSignal::new(None::<PublicState>);
+
+    if let Some(channel) = TownSquareChannel::new() {
+        channel.on_message(move |message| {
+            if let TownSquareMessage::PublicState(state) = message {
+                public_state.set(Some(state));
+            }
+        });
+        channel.request_sync();
+    }
+
+    let header = move || {
+        let state = public_state.get()?;
+        let phase = match state.step {
+            Step::Start | Step::Setup => "Setting Up".to_string(),
+            Step::Day(day_step) => format!("Day {}: {}", state.day_num, day_step),
+            Step::NightOne | Step::Night => format!("Night {}", state.day_num),
+        };
+        let nomination = state.nominations.last().map(|nomination| {
+            let votes = match nomination.votes {
+                Some(votes) => format!("{} votes", votes),
+                None => "Voting".to_string(),
+            };
+            format!(
+                "{} nominated {}: {}",
+                nomination.nominator, nomination.target, votes
+            )
+        });
+        let timer = state.timer.map(|timer| {
+            let time = if timer.remaining_secs == 0 {
+                "Time's up!".to_string()
+            } else {
+                format_secs(timer.remaining_secs)
+            };
+            view! {
+                <h2 style:color=if timer.remaining_secs == 0 {
+                    "red"
+                } else {
+                    ""
+                }>{format!("{}: {}", timer.kind, time)}</h2>
+            }
+        });
+        Some(view! {
+            <h1>{phase}</h1>
+            {timer}
+            <h2>{nomination}</h2>
+        })
+    };
+
+    let seats = move || {
+        let Some(state) = public_state.get() else {
+            return view! { <p>"Waiting for the storyteller"</p> }.into_any();
+        };
+        let positions = calc_circle(state.players.len(), 40.0);
+        state
+            .players
+            .into_iter()
+            .zip(positions)
+            .map(|(player, pos)| {
+                view! {
+                    <div
+                        class="absolute -translate-1/2 text-center"
+                        style:left=format!("calc(50% - {}%)", pos.0)
+                        style:top=format!("calc(50% + {}%)", pos.1)
+                    >
+                        <div
+                            class="size-[6rem] rounded-full border border-solid border-[#000000] flex items-center justify-center"
+                            style:background=if player.dead { "#999999" } else { "#ffffff" }
+                        >
+                            {player.name}
+                        </div>
+                        <Show when=move || player.traveller>
+                            <p>"Traveller"</p>
+                        </Show>
+                        // Ghost vote token
+                        <Show when=move || player.dead>
+                            <p>{if player.ghost_vote { "Ghost Vote" } else { "Vote Used" }}</p>
+                        </Show>
+                    </div>
+                }
+            })
+            .collect_view()
+            .into_any()
+    };
+
+    view! {
+        <div class="h-screen flex flex-col items-center">
+            {header}
+            <div class="relative flex-1 w-full">{seats}</div>
+        </div>
+    }
+}
+
 fn calc_circle(num_players: usize, radius: f64) -> Vec<(f64, f64)> {
     // Calculate circumference
     // Calculate position using radius and angle
@@ -1035,15 +2443,27 @@ fn Picker_Bar() -> impl IntoView {
     let temp_state = expect_context::<Store<TempState>>();
 
     let display = move || {
-        if temp_state
+        let change_type = temp_state
             .curr_change_request()
             .get()
-            .is_some_and(|cr| matches!(cr.get_change_type(), ChangeType::ChooseRoles(_)))
-        {
-            return RoleSelector().into_any();
+            .map(|cr| cr.get_change_type());
+        match change_type {
+            Some(ChangeType::ChooseRoles(_)) => return RoleSelector().into_any(),
+            Some(ChangeType::ChoosePlayersAndRoles(_)) => {
+                return view! {
+                    <PairList />
+                    <RoleSelector />
+                }
+                .into_any();
+            }
+            Some(ChangeType::YesNo) => return YesNoPicker().into_any(),
+            Some(ChangeType::ChooseNumber { min, max }) => {
+                return view! { <NumberPicker min max /> }.into_any();
+            }
+            _ => (),
         }
 
-        if matches!(state.step().get(), Step::Day)
+        if matches!(state.step().get(), Step::Day(_))
             && temp_state.curr_change_request().get().is_none()
         {
             return DayAbilitySelector().into_any();
@@ -1052,16 +2472,108 @@ fn Picker_Bar() -> impl IntoView {
         ().into_any()
     };
 
+    let skip_button = move || {
+        temp_state
+            .curr_change_request()
+            .get()
+            .filter(|cr| cr.is_optional())
+            .map(|_| {
+                view! {
+                    <label class="block">
+                        <input
+                            type="checkbox"
+                            prop:checked=move || temp_state.skipped().get()
+                            on:change=move |_| temp_state.skipped().update(|skipped| *skipped = !*skipped)
+                        />
+                        " Skip (the ability isn't used)"
+                    </label>
+                }
+            })
+    };
+
     view! {
         <div class="flex-1 border-solid border p-[1rem]">
+            <div>{skip_button}</div>
             <div>{display}</div>
         </div>
     }
 }
 
+/// The players and roles chosen so far, in the pairs they'll be given as
+#[component]
+fn PairList() -> impl IntoView {
+    let game_state = expect_context::<Store<State>>();
+    let temp_state = expect_context::<Store<TempState>>();
+
+    let pairs = move || {
+        temp_state
+            .with(|ts| ts.selected_pairs())
+            .into_iter()
+            .map(|(player_index, role)| {
+                let name = game_state
+                    .with(|gs| {
+                        gs.get_player(player_index)
+                            .map(|player| player.name.clone())
+                    })
+                    .unwrap_or_default();
+                view! { <li>{format!("{} -> {}", name, role)}</li> }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <p>"Pick a player, then the role for them"</p>
+        <ul>{pairs}</ul>
+    }
+}
+
+#[component]
+fn YesNoPicker() -> impl IntoView {
+    let temp_state = expect_context::<Store<TempState>>();
+    let answer_button = move |answer: bool, label: &'static str| {
+        view! {
+            <button
+                style:color=move || {
+                    if temp_state.selected_yes_no().get() == Some(answer) { "red" } else { "" }
+                }
+                on:click=move |_| temp_state.selected_yes_no().set(Some(answer))
+            >
+                {label}
+            </button>
+        }
+    };
+
+    view! {
+        <div class="flex gap-[1rem]">{answer_button(true, "Yes")} {answer_button(false, "No")}</div>
+    }
+}
+
+#[component]
+fn NumberPicker(min: usize, max: usize) -> impl IntoView {
+    let temp_state = expect_context::<Store<TempState>>();
+
+    view! {
+        <label class="block">
+            {format!("Number ({} to {}) ", min, max)}
+            <input
+                type="number"
+                min=min
+                max=max
+                prop:value=move || temp_state.selected_number().get().unwrap_or(min)
+                on:change=move |ev| {
+                    if let Ok(number) = event_target_value(&ev).parse() {
+                        temp_state.selected_number().set(Some(number));
+                    }
+                }
+            />
+        </label>
+    }
+}
+
 #[component]
 fn RoleSelector() -> impl IntoView {
     let script = expect_context::<RwSignal<Script>>();
+    let game_state = expect_context::<Store<State>>();
     let temp_state = expect_context::<Store<TempState>>();
     view! {
         <div class="flex flex-col">
@@ -1072,8 +2584,18 @@ fn RoleSelector() -> impl IntoView {
                     .into_iter()
                     .map(move |role| {
                         let selected = RwSignal::new(false);
+                        let disabled_reason = move || {
+                            temp_state
+                                .curr_change_request()
+                                .get()
+                                .and_then(|cr| {
+                                    game_state.with(|gs| cr.role_disabled_reason(role, gs))
+                                })
+                        };
                         view! {
                             <button
+                                disabled=move || disabled_reason().is_some()
+                                title=move || disabled_reason().unwrap_or_default()
                                 style:color=move || { if selected.get() { "red" } else { "" } }
                                 on:click=move |_| {
                                     let cr = {
@@ -1082,8 +2604,12 @@ fn RoleSelector() -> impl IntoView {
                                         }
                                         temp_state.curr_change_request().get().unwrap()
                                     };
-                                    let requested_num = match cr.get_change_type() {
-                                        ChangeType::ChooseRoles(num) => num,
+                                    let requested_num = match (cr.get_change_type(), cr.count_range()) {
+                                        (
+                                            ChangeType::ChooseRoles(_)
+                                            | ChangeType::ChoosePlayersAndRoles(_),
+                                            Some((_, max)),
+                                        ) => max,
                                         _ => {
                                             return;
                                         }
@@ -1141,8 +2667,7 @@ fn DayAbilitySelector() -> impl IntoView {
                 check_len(&target_players, 1)?;
 
                 let nominated_player = target_players[0];
-                state.nominate_player(nominating_player, nominated_player);
-                Ok(())
+                state.nominate_player(nominating_player, nominated_player)
             });
 
             state.change_request_queue.push_back(
@@ -1161,22 +2686,27 @@ fn DayAbilitySelector() -> impl IntoView {
 
     view! {
         <div class="flex flex-col">
-            <button on:click=nominate_button>"Nominate"</button>
+            <button
+                on:click=nominate_button
+                disabled=move || state.step().get() != Step::Day(DayStep::Nominations)
+            >
+                "Nominate"
+            </button>
             {move || {
                 let active_players = state.read().get_day_active();
                 active_players
                     .into_iter()
-                    .map(|player_index| {
-                        let role = state.read().get_player(player_index).role.clone();
+                    .filter_map(|player_index| {
+                        let role = state.read().get_player(player_index).ok()?.role.clone();
 
-                        view! {
+                        Some(view! {
                             <button on:click=move |_| {
                                 temp_state.update(|ts| ts.reset());
                                 let player_ability = state.read().day_ability(player_index);
                                 temp_state.curr_change_request().set(build(player_ability));
                                 temp_state.currently_acting_player().set(Some(player_index));
                             }>{move || { format!("{} Ability", role) }}</button>
-                        }
+                        })
                     })
                     .collect_view()
             }}
@@ -1184,6 +2714,54 @@ fn DayAbilitySelector() -> impl IntoView {
     }
 }
 
+#[component]
+fn NightOrderDisplay() -> impl IntoView {
+    let state = expect_context::<Store<State>>();
+    let script = expect_context::<RwSignal<Script>>();
+    let sheet = Memo::new(move |_| NightOrderSheet::new(&script.read()));
+
+    let night = move |first_night: bool| {
+        let rows = state.with(|gs| sheet.read().rows(first_night, gs));
+        rows.into_iter()
+            .map(|row| {
+                let color = match (row.in_play, row.dead) {
+                    (_, true) => "gray",
+                    (true, false) => "black",
+                    (false, false) => "silver",
+                };
+                view! {
+                    <tr style:color=color class:font-bold=row.in_play && !row.dead>
+                        <td class="border p-[4px]">{row.entry.to_string()}</td>
+                        <td class="border p-[4px]">{row.entry.reminder(first_night)}</td>
+                    </tr>
+                }
+            })
+            .collect_view()
+    };
+
+    view! {
+        <div class="border">
+            <h2>"Night Order"</h2>
+            <a
+                href=move || state.with(|gs| sheet.read().to_data_url(gs))
+                download="night_order.html"
+            >
+                "Export"
+            </a>
+            <div class="flex">
+                <table class="flex-1">
+                    <caption>"First Night"</caption>
+                    {move || night(true)}
+                </table>
+                <table class="flex-1">
+                    <caption>"Other Nights"</caption>
+                    {move || night(false)}
+                </table>
+            </div>
+        </div>
+    }
+}
+
 #[component]
 fn LogDisplay() -> impl IntoView {
     let state = expect_context::<Store<State>>();
@@ -1219,14 +2797,118 @@ fn LogDisplay() -> impl IntoView {
     }
 }
 
+#[component]
+fn TimelineScrubber() -> impl IntoView {
+    let timeline = expect_context::<RwSignal<Timeline>>();
+
+    let selected = RwSignal::new(None::<usize>);
+    let entry_count = move || timeline.with(|tl| tl.entries.len());
+    // Follow the game until the storyteller picks a point in time
+    let current = move || {
+        let count = entry_count();
+        if count == 0 {
+            return None;
+        }
+        Some(selected.get().unwrap_or(count - 1).min(count - 1))
+    };
+
+    let entry_view = move || {
+        let index = current()?;
+        let entry = timeline.with(|tl| tl.entries[index].clone());
+        Some(view! {
+            <p>{format!("{}: {}", entry.phase, entry.description)}</p>
+            <GrimoireSnapshotDisplay grimoire=entry.grimoire />
+        })
+    };
+
+    view! {
+        <div class="border">
+            <h2>"Timeline"</h2>
+            <Show
+                when=move || entry_count() != 0
+                fallback=|| view! { <p>"Nothing has happened yet"</p> }
+            >
+                <input
+                    type="range"
+                    class="w-full"
+                    min="0"
+                    max=move || entry_count().saturating_sub(1)
+                    prop:value=move || current().unwrap_or_default()
+                    on:input=move |ev| {
+                        if let Ok(index) = event_target_value(&ev).parse() {
+                            selected.set(Some(index));
+                        }
+                    }
+                />
+                <button on:click=move |_| selected.set(None)>"Back To Now"</button>
+                {entry_view}
+            </Show>
+        </div>
+    }
+}
+
+/// Read only grimoire for looking back at the game
+#[component]
+fn GrimoireSnapshotDisplay(grimoire: GrimoireSnapshot) -> impl IntoView {
+    let header = match grimoire.step {
+        Step::Start | Step::Setup => "Setup".to_string(),
+        Step::Day(day_step) => format!("Day {} {}", grimoire.day_num, day_step),
+        Step::NightOne | Step::Night => format!("Night {}", grimoire.day_num),
+    };
+    let change_request = grimoire
+        .change_request
+        .map(|description| format!("Resolving: {}", description));
+    let seats = grimoire
+        .players
+        .iter()
+        .filter(|player| !player.left)
+        .count();
+    let positions = calc_circle(seats, 40.0);
+
+    let players = grimoire
+        .players
+        .into_iter()
+        .enumerate()
+        .filter(|(_, player)| !player.left)
+        .zip(positions)
+        .map(|((player_index, player), pos)| {
+            let acting = grimoire.acting_player == Some(player_index);
+            view! {
+                <div
+                    class="absolute -translate-1/2 text-center text-[0.7rem]"
+                    style:left=format!("calc(50% - {}%)", pos.0)
+                    style:top=format!("calc(50% + {}%)", pos.1)
+                >
+                    <div
+                        class="size-[5rem] rounded-full border border-[#000000] flex flex-col items-center justify-center"
+                        style:border-style=if player.ghost_vote { "solid" } else { "dashed" }
+                        style:background=if acting { "aquamarine" } else { "" }
+                        style:color=if player.dead { "gray" } else { "" }
+                    >
+                        <span>{player.name}</span>
+                        <span>{player.role}</span>
+                        <span>{player.alignment}</span>
+                    </div>
+                    <p>{player.statuses.join(", ")}</p>
+                </div>
+            }
+        })
+        .collect_view();
+
+    view! {
+        <div>
+            <h3>{header}</h3>
+            <p>{change_request}</p>
+            <div class="relative h-[30rem] w-full">{players}</div>
+        </div>
+    }
+}
+
 // #[component]
 // fn DayPhaseDisplay(day_phase: Memo<log::DayPhaseLog>) -> impl IntoView {
 //     view! {};
 // }
 
 fn build(change_option: Option<ChangeRequestBuilder>) -> Option<ChangeRequest> {
-    match change_option {
-        Some(cr) => Some(cr.build()),
-        None => None,
-    }
+    change_option.map(|cr| cr.build())
 }
@@ -32,5 +32,6 @@ pub(crate) fn trouble_brewing() -> Script {
                 RoleNames::Imp,
             ]
         },
+        ..Default::default()
     }
 }
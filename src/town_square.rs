@@ -0,0 +1,73 @@
+use leptos::{
+    leptos_dom::logging::console_error,
+    wasm_bindgen::{JsCast, JsValue, closure::Closure},
+};
+
+use crate::engine::state::public::PublicState;
+
+const CHANNEL_NAME: &str = "botc-town-square";
+const WINDOW_QUERY: &str = "?townsquare";
+/// Sent by a town square window that just opened and has nothing to show yet
+const SYNC_REQUEST: &str = "sync";
+
+pub(crate) enum TownSquareMessage {
+    SyncRequest,
+    PublicState(PublicState),
+}
+
+/// Link between the storyteller's window and any town square windows in the same browser. Works
+/// without a network connection
+#[derive(Clone)]
+pub(crate) struct TownSquareChannel(web_sys::BroadcastChannel);
+
+impl TownSquareChannel {
+    pub(crate) fn new() -> Option<Self> {
+        match web_sys::BroadcastChannel::new(CHANNEL_NAME) {
+            Ok(channel) => Some(Self(channel)),
+            Err(err) => {
+                console_error(format!("Could not open town square channel: {:?}", err).as_str());
+                None
+            }
+        }
+    }
+
+    pub(crate) fn send_public_state(&self, public_state: &PublicState) {
+        if let Ok(json) = serde_json::to_string(public_state) {
+            let _ = self.0.post_message(&JsValue::from_str(&json));
+        }
+    }
+
+    pub(crate) fn request_sync(&self) {
+        let _ = self.0.post_message(&JsValue::from_str(SYNC_REQUEST));
+    }
+
+    pub(crate) fn on_message(&self, mut callback: impl FnMut(TownSquareMessage) + 'static) {
+        let closure = Closure::<dyn FnMut(web_sys::MessageEvent)>::new(
+            move |event: web_sys::MessageEvent| {
+                let Some(data) = event.data().as_string() else {
+                    return;
+                };
+                if data == SYNC_REQUEST {
+                    callback(TownSquareMessage::SyncRequest);
+                } else if let Ok(public_state) = serde_json::from_str(&data) {
+                    callback(TownSquareMessage::PublicState(public_state));
+                }
+            },
+        );
+        self.0.set_onmessage(Some(closure.as_ref().unchecked_ref()));
+        // The channel lives as long as the page does
+        closure.forget();
+    }
+}
+
+pub(crate) fn is_town_square_window() -> bool {
+    web_sys::window()
+        .and_then(|window| window.location().search().ok())
+        .is_some_and(|search| search == WINDOW_QUERY)
+}
+
+pub(crate) fn open_window() {
+    if let Some(window) = web_sys::window() {
+        let _ = window.open_with_url_and_target(WINDOW_QUERY, CHANNEL_NAME);
+    }
+}